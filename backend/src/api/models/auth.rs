@@ -1,5 +1,7 @@
 use serde::Serialize;
+use time::OffsetDateTime;
 use uuid::Uuid;
+use crate::infra::repositories::session_repo::SessionRow;
 use crate::utils::token::TokenDTO;
 
 #[derive(Debug, Serialize)]
@@ -23,4 +25,34 @@ pub struct RegisterOut {
 pub struct LoginOut {
     pub user:   UserOut,
     pub tokens: TokenDTO,
-}
\ No newline at end of file
+}
+// Устройство, с которого выполнен вход
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct SessionOut {
+    pub id: Uuid,
+    pub device_label: Option<String>,
+    pub user_agent: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_used_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+    // сессия, которой принадлежит токен запроса
+    pub current: bool,
+}
+
+impl SessionOut {
+    pub fn from_row(r: SessionRow, current: Option<Uuid>) -> Self {
+        Self {
+            current: current == Some(r.id),
+            id: r.id,
+            device_label: r.device_label,
+            user_agent: r.user_agent,
+            created_at: r.created_at,
+            last_used_at: r.last_used_at,
+            expires_at: r.expires_at,
+        }
+    }
+}
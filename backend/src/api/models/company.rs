@@ -8,6 +8,7 @@ pub struct CompanyOut {
     pub id: Uuid,
     pub name: String,
     pub status: CompanyStatus,
+    pub requires_event_review: bool,
     pub manager_count: Option<i64>,
     pub event_count: Option<i64>,
 }
@@ -18,6 +19,7 @@ impl From<CompanyWithCounts> for CompanyOut {
             id: v.id,
             name: v.name,
             status: v.status,
+            requires_event_review: v.requires_event_review,
             manager_count: v.manager_count,
             event_count: v.event_count,
         }
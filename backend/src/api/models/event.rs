@@ -2,7 +2,10 @@ use serde::Serialize;
 use sqlx::FromRow;
 use time::OffsetDateTime;
 use uuid::Uuid;
-use crate::domain::mappers::event::EventWithCount;
+use crate::domain::entities::event_row::EventStatus;
+use crate::domain::entities::event_series::SeriesFrequency;
+use crate::api::models::tag::TagFacetOut;
+use crate::domain::entities::registration::{RegistrationState, RegistrationStatus};
 
 #[derive(Debug, Serialize)]
 pub struct EventOut {
@@ -21,6 +24,35 @@ pub struct EventOut {
     pub registered_count: Option<i64>,
     pub capacity: Option<i32>,
     pub is_published: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series_index: Option<i32>,
+    pub tags: Vec<String>,
+    pub status: EventStatus,
+    // причина отклонения модератором
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancel_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", with = "time::serde::rfc3339::option")]
+    pub canceled_at: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none", with = "time::serde::rfc3339::option")]
+    pub publish_at: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none", with = "time::serde::rfc3339::option")]
+    pub unpublish_at: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registration_status: Option<RegistrationStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub waitlist_position: Option<i64>,
+}
+
+impl EventOut {
+    pub fn with_registration(mut self, state: Option<RegistrationState>) -> Self {
+        self.registration_status = state.map(|s| s.status);
+        self.waitlist_position = state.and_then(|s| s.waitlist_position);
+        self
+    }
 }
 
 
@@ -56,6 +88,36 @@ impl From<EventRow> for EventOut {
             registered_count: r.registered_count,
             capacity: r.capacity,
             is_published: r.is_published,
+            series_id: None,
+            series_index: None,
+            tags: Vec::new(),
+            status: if r.is_published { EventStatus::Published } else { EventStatus::Draft },
+            review_reason: None,
+            cancel_reason: None,
+            canceled_at: None,
+            publish_at: None,
+            unpublish_at: None,
+            registration_status: None,
+            waitlist_position: None,
         }
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeriesOut {
+    pub id: Uuid,
+    pub company_id: Uuid,
+    pub frequency: SeriesFrequency,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub until: Option<OffsetDateTime>,
+    pub count: Option<i32>,
+    pub events: Vec<EventOut>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventPageOut {
+    pub items: Vec<EventOut>,
+    pub next_cursor: Option<String>,
+    pub total: i64,
+    pub facets: Vec<TagFacetOut>,
+}
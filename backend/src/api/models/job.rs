@@ -0,0 +1,35 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::infra::repositories::job_repo::{JobRow, JobStatus};
+
+#[derive(Debug, Serialize)]
+pub struct JobOut {
+    pub id: Uuid,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub status: JobStatus,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub run_at: OffsetDateTime,
+    pub last_error: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub finished_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl From<JobRow> for JobOut {
+    fn from(r: JobRow) -> Self {
+        Self {
+            id: r.id, kind: r.kind, payload: r.payload, status: r.status,
+            attempts: r.attempts, max_attempts: r.max_attempts, run_at: r.run_at,
+            last_error: r.last_error, finished_at: r.finished_at,
+            created_at: r.created_at, updated_at: r.updated_at,
+        }
+    }
+}
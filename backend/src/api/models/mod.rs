@@ -2,4 +2,9 @@ pub mod company;
 pub mod event;
 pub mod registration;
 pub mod auth;
-pub mod manager;
\ No newline at end of file
+pub mod manager;
+pub mod search;
+pub mod tag;
+pub mod job;
+pub mod notification;
+pub mod settings;
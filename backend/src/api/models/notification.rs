@@ -0,0 +1,9 @@
+use serde::Serialize;
+
+// true — канал включён; отправка в Telegram всё равно требует привязанного аккаунта
+#[derive(Debug, Serialize)]
+pub struct NotificationPrefsOut {
+    pub telegram: bool,
+    // письма о записях и изменениях событий; решения по аккаунту приходят всегда
+    pub email: bool,
+}
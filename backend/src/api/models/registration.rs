@@ -2,6 +2,8 @@ use serde::Serialize;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+use crate::domain::entities::registration::{RegistrationState, RegistrationStatus};
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct RegistrationOut {
@@ -10,4 +12,45 @@ pub struct RegistrationOut {
     pub student_email: String,
     #[serde(with = "time::serde::rfc3339")]
     pub registered_at: OffsetDateTime,
+    pub status: RegistrationStatus,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub checked_in_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RegistrationStateOut {
+    pub status: RegistrationStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub waitlist_position: Option<i64>,
+}
+
+impl From<RegistrationState> for RegistrationStateOut {
+    fn from(s: RegistrationState) -> Self {
+        Self { status: s.status, waitlist_position: s.waitlist_position }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CheckInOut {
+    pub checked_in: Vec<Uuid>,
+    // не записаны, в листе ожидания или отменили запись
+    pub not_registered: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TicketOut {
+    pub event_id: Uuid,
+    pub student_id: Uuid,
+    pub token: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct SeriesRegistrationOut {
+    pub event_id: Uuid,
+    #[serde(flatten)]
+    pub state: RegistrationStateOut,
 }
@@ -0,0 +1,56 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::domain::entities::search::{SearchHitRow, SearchKind};
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SearchHitOut {
+    Event {
+        id: Uuid,
+        title: String,
+        // HTML: текст экранирован, совпадения обёрнуты в <mark>
+        snippet: String,
+        rank: f32,
+        company_id: Uuid,
+        company_name: String,
+        #[serde(with = "time::serde::rfc3339")]
+        starts_at: OffsetDateTime,
+        is_published: bool,
+    },
+    Company {
+        id: Uuid,
+        name: String,
+        snippet: String,
+        rank: f32,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchOut {
+    pub items: Vec<SearchHitOut>,
+}
+
+impl From<SearchHitRow> for SearchHitOut {
+    fn from(r: SearchHitRow) -> Self {
+        match r.kind {
+            SearchKind::Event => Self::Event {
+                id: r.id,
+                title: r.title,
+                snippet: r.snippet,
+                rank: r.rank,
+                company_id: r.company_id.unwrap_or_default(),
+                company_name: r.company_name.unwrap_or_default(),
+                starts_at: r.starts_at.unwrap_or(OffsetDateTime::UNIX_EPOCH),
+                is_published: r.is_published.unwrap_or(false),
+            },
+            SearchKind::Company => Self::Company {
+                id: r.id,
+                name: r.title,
+                snippet: r.snippet,
+                rank: r.rank,
+            },
+        }
+    }
+}
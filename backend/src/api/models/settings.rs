@@ -0,0 +1,20 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::infra::repositories::settings_repo::AppSettings;
+
+#[derive(Debug, Serialize)]
+pub struct SettingsOut {
+    // подтверждение студента деканом требует проверенного email
+    pub require_verified_email: bool,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    pub updated_by: Option<Uuid>,
+}
+
+impl From<AppSettings> for SettingsOut {
+    fn from(s: AppSettings) -> Self {
+        Self { require_verified_email: s.require_verified_email, updated_at: s.updated_at, updated_by: s.updated_by }
+    }
+}
@@ -0,0 +1,32 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::domain::entities::tag::{TagFacet, TagRow};
+
+#[derive(Debug, Serialize)]
+pub struct TagOut {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+    pub category: Option<String>,
+}
+
+impl From<TagRow> for TagOut {
+    fn from(r: TagRow) -> Self {
+        Self { id: r.id, slug: r.slug, name: r.name, category: r.category }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagFacetOut {
+    pub slug: String,
+    pub name: String,
+    pub category: Option<String>,
+    pub count: i64,
+}
+
+impl From<TagFacet> for TagFacetOut {
+    fn from(f: TagFacet) -> Self {
+        Self { slug: f.slug, name: f.name, category: f.category, count: f.count }
+    }
+}
@@ -17,6 +17,8 @@ impl From<CreateCompanyIn> for Company {
 #[derive(Debug, Deserialize)]
 pub struct UpdateCompanyIn {
     pub name: Option<String>,
+    // false — проверенный партнёр, события публикуются без модерации
+    pub requires_event_review: Option<bool>,
 }
 
 impl From<UpdateCompanyIn> for CompanyPatch {
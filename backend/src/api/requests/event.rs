@@ -1,7 +1,8 @@
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 use time::OffsetDateTime;
 
 use crate::domain::entities::event::{Event, EventPatch};
+use crate::domain::entities::event_series::{Recurrence, SeriesFrequency};
 
 #[derive(Debug, Deserialize)]
 pub struct CreateEventIn {
@@ -16,6 +17,12 @@ pub struct CreateEventIn {
     pub signup_deadline: Option<OffsetDateTime>,
     pub capacity: Option<i32>,
     pub is_published: Option<bool>,
+    // slug'и из словаря тегов
+    pub tags: Option<Vec<String>>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub publish_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub unpublish_at: Option<OffsetDateTime>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,6 +38,60 @@ pub struct UpdateEventIn {
     pub signup_deadline: Option<OffsetDateTime>,
     pub capacity: Option<i32>,
     pub is_published: Option<bool>,
+    // заменяет набор тегов целиком
+    pub tags: Option<Vec<String>>,
+    // null снимает время из расписания
+    #[serde(default, deserialize_with = "rfc3339_nullable")]
+    pub publish_at: Option<Option<OffsetDateTime>>,
+    #[serde(default, deserialize_with = "rfc3339_nullable")]
+    pub unpublish_at: Option<Option<OffsetDateTime>>,
+    // для вхождения серии: this | following | all
+    pub scope: Option<EditScope>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EditScope {
+    #[default]
+    This,
+    Following,
+    All,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecurrenceIn {
+    pub frequency: SeriesFrequency,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub until: Option<OffsetDateTime>,
+    pub count: Option<i32>,
+    #[serde(default, deserialize_with = "rfc3339_vec")]
+    pub dates: Vec<OffsetDateTime>,
+}
+
+impl From<RecurrenceIn> for Recurrence {
+    fn from(v: RecurrenceIn) -> Self {
+        Recurrence { frequency: v.frequency, until: v.until, count: v.count, dates: v.dates }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSeriesIn {
+    #[serde(flatten)]
+    pub event: CreateEventIn,
+    pub recurrence: RecurrenceIn,
+}
+
+fn rfc3339_vec<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<OffsetDateTime>, D::Error> {
+    #[derive(Deserialize)]
+    struct At(#[serde(with = "time::serde::rfc3339")] OffsetDateTime);
+    Ok(Vec::<At>::deserialize(d)?.into_iter().map(|a| a.0).collect())
+}
+
+// отличает явный null от отсутствующего поля
+fn rfc3339_nullable<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Option<OffsetDateTime>>, D::Error> {
+    #[derive(Deserialize)]
+    struct At(#[serde(with = "time::serde::rfc3339::option")] Option<OffsetDateTime>);
+    Ok(Some(At::deserialize(d)?.0))
 }
 
 impl From<UpdateEventIn> for EventPatch {
@@ -44,6 +105,8 @@ impl From<UpdateEventIn> for EventPatch {
             signup_deadline: v.signup_deadline,
             capacity: v.capacity,
             is_published: v.is_published,
+            publish_at: v.publish_at,
+            unpublish_at: v.unpublish_at,
         }
     }
 }
\ No newline at end of file
@@ -10,4 +10,6 @@ pub struct ManagerRegisterRequest {
     pub password: String,
     pub company_id: Uuid,
     pub telegram_user_id: Option<i64>,
+    // подпись устройства для списка сессий («Telegram-бот», «Веб»)
+    pub device: Option<String>,
 }
\ No newline at end of file
@@ -1,7 +1,13 @@
 pub mod company;
 pub mod event;
+pub mod registration;
 pub mod manager_register;
 pub mod student_register;
 pub mod login;
 pub mod refresh_token;
-pub mod refresh;
\ No newline at end of file
+pub mod refresh;
+pub mod password;
+pub mod verify_email;
+pub mod tag;
+pub mod notification;
+pub mod settings;
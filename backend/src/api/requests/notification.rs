@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+// не переданный канал не меняется
+#[derive(Debug, Deserialize)]
+pub struct UpdateNotificationPrefsIn {
+    pub telegram: Option<bool>,
+    pub email: Option<bool>,
+}
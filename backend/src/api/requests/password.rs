@@ -0,0 +1,32 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResetChannel {
+    #[default]
+    Email,
+    // в привязанный Telegram-чат; без активной привязки уходит письмом
+    Telegram,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+    #[serde(default)]
+    pub channel: ResetChannel,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ChangePasswordRequest {
+    pub old_password: String,
+    pub new_password: String,
+}
@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+// не переданное поле не меняется
+#[derive(Debug, Deserialize)]
+pub struct UpdateSettingsIn {
+    pub require_verified_email: Option<bool>,
+}
@@ -7,4 +7,6 @@ pub struct StudentRegisterRequest {
     pub email: String,
     pub password: String,
     pub telegram_user_id: Option<i64>,
+    // подпись устройства для списка сессий («Telegram-бот», «Веб»)
+    pub device: Option<String>,
 }
\ No newline at end of file
@@ -0,0 +1,16 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTagIn {
+    pub slug: String,
+    pub name: String,
+    pub category: Option<String>,
+}
+
+// пустая category снимает тег с группы
+#[derive(Debug, Deserialize)]
+pub struct UpdateTagIn {
+    pub slug: Option<String>,
+    pub name: Option<String>,
+    pub category: Option<String>,
+}
@@ -4,21 +4,33 @@ use http::header;
 
 use crate::routes;
 use crate::state::AppState;
-use crate::middleware::{request_id::RequestIdLayer, json_errors::JsonErrorLayer};
+use crate::infra::repositories::idempotency_repo::PgIdempotencyRepository;
+use crate::middleware::{request_id::RequestIdLayer, json_errors::JsonErrorLayer, idempotency::IdempotencyLayer};
 
 pub fn build_router(state: AppState) -> Router {
+    let idempotency = IdempotencyLayer::new(
+        PgIdempotencyRepository::new(state.db.clone()),
+        state.auth.token_service.clone(),
+    );
     Router::new()
         .merge(routes::health::router())
         .merge(routes::auth::router(state.clone()))
         .merge(routes::me::router(state.clone()))
         .merge(routes::oauth::router(state.clone()))
         .merge(routes::dean_student::router(state.clone()))
+        .merge(routes::dean_settings::router(state.clone()))
         .merge(routes::companies::router(state.clone()))
         .merge(routes::events::router(state.clone()))
         .merge(routes::telegram::router(state.clone()))
+        .merge(routes::calendar::router(state.clone()))
+        .merge(routes::search::router(state.clone()))
+        .merge(routes::tags::router(state.clone()))
+        .merge(routes::jobs::router(state))
+        // внутри RequestIdLayer, чтобы в переделанные ошибки попал request_id
+        .layer(JsonErrorLayer::new())
+        .layer(idempotency)
         .layer(TraceLayer::new_for_http())
         .layer(RequestIdLayer::new())
-        .layer(JsonErrorLayer::new())
         .layer(CorsLayer::permissive())
         .layer(SetResponseHeaderLayer::if_not_present(
             header::SERVER,
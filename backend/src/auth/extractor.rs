@@ -6,7 +6,10 @@ use axum::{
 use uuid::Uuid;
 use crate::auth::roles::{ManagerStatus, UserRole, StudentStatus};
 use crate::error::ApiError;
+use crate::infra::errors::RepoError;
 use crate::infra::security::jwt::{Claims, TokenService};
+use crate::infra::security::session_state::SessionStates;
+use crate::infra::security::token_version::TokenVersions;
 use crate::state::AppState;
 
 #[derive(Debug, Clone)]
@@ -16,12 +19,16 @@ pub struct AuthUser {
     pub manager_status: Option<ManagerStatus>,
     pub company_id: Option<Uuid>,
     pub student_status: Option<StudentStatus>,
+    // сессия входа из токена (None у токенов, выданных до появления сессий)
+    pub session_id: Option<Uuid>,
     pub raw: Claims,
 }
 
 #[derive(Clone)]
 pub struct AuthState {
     pub token_service: TokenService,
+    pub versions: TokenVersions,
+    pub sessions: SessionStates,
 }
 
 #[async_trait]
@@ -41,6 +48,22 @@ impl FromRequestParts<AppState> for AuthUser {
             .validate_token(token)
             .map_err(|_| ApiError::Unauthorized)?;
 
+        // статус или роль поменялись после выпуска токена — нужен refresh с актуальными правами
+        let current = match auth.versions.current(claims.user_id).await {
+            Ok(v) => v,
+            Err(RepoError::NotFound) => return Err(ApiError::Unauthorized),
+            Err(e) => return Err(e.into()),
+        };
+        if claims.ver != current {
+            return Err(ApiError::Unauthorized);
+        }
+        // после выхода или отзыва сессии её токены не принимаются, не дожидаясь exp
+        if let Some(sid) = claims.sid {
+            if !auth.sessions.is_active(claims.user_id, sid).await? {
+                return Err(ApiError::Unauthorized);
+            }
+        }
+
         let role = match claims.role.as_str() {
             "student" => UserRole::Student,
             "manager" => UserRole::Manager,
@@ -72,6 +95,7 @@ impl FromRequestParts<AppState> for AuthUser {
             manager_status,
             company_id: claims.company_id,
             student_status,
+            session_id: claims.sid,
             raw: claims,
         })
     }
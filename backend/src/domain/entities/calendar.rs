@@ -0,0 +1,33 @@
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarEntryStatus {
+    Confirmed,
+    Tentative,
+    Cancelled,
+}
+
+impl CalendarEntryStatus {
+    pub fn from_db(s: &str) -> Self {
+        match s {
+            "tentative" => Self::Tentative,
+            "cancelled" => Self::Cancelled,
+            _ => Self::Confirmed,
+        }
+    }
+}
+
+// Строка ленты: живое событие либо надгробие удалённого
+#[derive(Debug, Clone)]
+pub struct CalendarEntryRow {
+    pub id: Uuid,
+    pub sequence: i32,
+    pub title: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub starts_at: OffsetDateTime,
+    pub ends_at: Option<OffsetDateTime>,
+    pub updated_at: OffsetDateTime,
+    pub status: CalendarEntryStatus,
+}
@@ -1,6 +1,7 @@
 use thiserror::Error;
 use uuid::Uuid;
 use crate::domain::entities::company_row::CompanyStatus;
+use crate::domain::entities::validation::FieldError;
 
 #[derive(Debug, Clone)]
 pub struct Company {
@@ -14,27 +15,39 @@ pub enum CompanyValidationError {
     EmptyName,
 }
 
+impl From<CompanyValidationError> for FieldError {
+    fn from(e: CompanyValidationError) -> Self {
+        match e {
+            CompanyValidationError::EmptyName => FieldError { field: "name", code: "required", message: e.to_string() },
+        }
+    }
+}
+
+pub fn validate_name(name: &str) -> Result<(), CompanyValidationError> {
+    if name.trim().is_empty() {
+        return Err(CompanyValidationError::EmptyName);
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 pub struct CompanyWithCounts {
     pub id: Uuid,
     pub name: String,
     pub status: CompanyStatus,
+    pub requires_event_review: bool,
     pub manager_count: Option<i64>,
     pub event_count: Option<i64>,
 }
 
 impl Company {
     pub fn new(id: Uuid, name: String) -> Result<Self, CompanyValidationError> {
-        if name.trim().is_empty() {
-            return Err(CompanyValidationError::EmptyName);
-        }
+        validate_name(&name)?;
         Ok(Self { id, name })
     }
 
     pub fn apply_name(&mut self, name: String) -> Result<(), CompanyValidationError> {
-        if name.trim().is_empty() {
-            return Err(CompanyValidationError::EmptyName);
-        }
+        validate_name(&name)?;
         self.name = name;
         Ok(())
     }
@@ -2,6 +2,8 @@ use thiserror::Error;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+use crate::domain::entities::validation::{FieldError, ValidationErrors};
+
 #[derive(Debug, Clone)]
 pub struct Event {
     pub id: Uuid,
@@ -15,6 +17,9 @@ pub struct Event {
     pub signup_deadline: Option<OffsetDateTime>,
     pub capacity: Option<i32>,
     pub is_published: bool,
+    // расписание публикации; исполняет фоновый воркер
+    pub publish_at: Option<OffsetDateTime>,
+    pub unpublish_at: Option<OffsetDateTime>,
 }
 
 #[derive(Debug, Error)]
@@ -27,6 +32,37 @@ pub enum EventValidationError {
     DeadlineAfterStart,
     #[error("capacity must be >= 0")]
     NegativeCapacity,
+    #[error("unpublish_at must be after publish_at")]
+    UnpublishBeforePublish,
+}
+
+impl EventValidationError {
+    // поле запроса (CreateEventIn / UpdateEventIn), к которому относится ошибка
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::EmptyTitle => "title",
+            Self::EndsBeforeStart => "ends_at",
+            Self::DeadlineAfterStart => "signup_deadline",
+            Self::NegativeCapacity => "capacity",
+            Self::UnpublishBeforePublish => "unpublish_at",
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::EmptyTitle => "required",
+            Self::EndsBeforeStart => "before_start",
+            Self::DeadlineAfterStart => "after_start",
+            Self::NegativeCapacity => "negative",
+            Self::UnpublishBeforePublish => "before_publish",
+        }
+    }
+}
+
+impl From<EventValidationError> for FieldError {
+    fn from(e: EventValidationError) -> Self {
+        FieldError { field: e.field(), code: e.code(), message: e.to_string() }
+    }
 }
 
 impl Event {
@@ -42,35 +78,48 @@ impl Event {
         signup_deadline: Option<OffsetDateTime>,
         capacity: Option<i32>,
         is_published: bool,
-    ) -> Result<Self, EventValidationError> {
+    ) -> Result<Self, ValidationErrors> {
         let e = Self {
             id, company_id, manager_id, title, description, location,
-            starts_at, ends_at, signup_deadline, capacity, is_published
+            starts_at, ends_at, signup_deadline, capacity, is_published,
+            publish_at: None, unpublish_at: None,
         };
         e.validate()?;
         Ok(e)
     }
 
-    pub fn validate(&self) -> Result<(), EventValidationError> {
+    pub fn with_schedule(
+        mut self,
+        publish_at: Option<OffsetDateTime>,
+        unpublish_at: Option<OffsetDateTime>,
+    ) -> Result<Self, ValidationErrors> {
+        self.publish_at = publish_at;
+        self.unpublish_at = unpublish_at;
+        self.validate()?;
+        Ok(self)
+    }
+
+    // Проверяет всё сразу, а не до первой ошибки
+    pub fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errs = ValidationErrors::new();
         if self.title.trim().is_empty() {
-            return Err(EventValidationError::EmptyTitle);
+            errs.push(EventValidationError::EmptyTitle);
         }
-        if let Some(ends) = self.ends_at {
-            if ends < self.starts_at {
-                return Err(EventValidationError::EndsBeforeStart);
-            }
+        if self.ends_at.is_some_and(|ends| ends < self.starts_at) {
+            errs.push(EventValidationError::EndsBeforeStart);
         }
-        if let Some(dl) = self.signup_deadline {
-            if dl > self.starts_at {
-                return Err(EventValidationError::DeadlineAfterStart);
-            }
+        if self.signup_deadline.is_some_and(|dl| dl > self.starts_at) {
+            errs.push(EventValidationError::DeadlineAfterStart);
         }
-        if let Some(cap) = self.capacity {
-            if cap < 0 {
-                return Err(EventValidationError::NegativeCapacity);
+        if self.capacity.is_some_and(|cap| cap < 0) {
+            errs.push(EventValidationError::NegativeCapacity);
+        }
+        if let (Some(on), Some(off)) = (self.publish_at, self.unpublish_at) {
+            if off <= on {
+                errs.push(EventValidationError::UnpublishBeforePublish);
             }
         }
-        Ok(())
+        errs.check()
     }
 }
 
@@ -84,10 +133,13 @@ pub struct EventPatch {
     pub signup_deadline: Option<OffsetDateTime>,
     pub capacity: Option<i32>,
     pub is_published: Option<bool>,
+    // внешний None — не менять, Some(None) — убрать из расписания
+    pub publish_at: Option<Option<OffsetDateTime>>,
+    pub unpublish_at: Option<Option<OffsetDateTime>>,
 }
 
 impl Event {
-    pub fn apply(&mut self, p: EventPatch) -> Result<(), EventValidationError> {
+    pub fn apply(&mut self, p: EventPatch) -> Result<(), ValidationErrors> {
         if let Some(v) = p.title { self.title = v; }
         if let Some(v) = p.description { self.description = Some(v); }
         if let Some(v) = p.location { self.location = Some(v); }
@@ -96,6 +148,85 @@ impl Event {
         if let Some(v) = p.signup_deadline { self.signup_deadline = Some(v); }
         if let Some(v) = p.capacity { self.capacity = Some(v); }
         if let Some(v) = p.is_published { self.is_published = v; }
+        if let Some(v) = p.publish_at { self.publish_at = v; }
+        if let Some(v) = p.unpublish_at { self.unpublish_at = v; }
         self.validate()
     }
-}
\ No newline at end of file
+}
+impl EventPatch {
+    // Патч для другого вхождения серии: сдвиг времени переносится, а не копируется,
+    // чтобы «все вхождения» не схлопнулись в одну дату.
+    pub fn relative_to(&self, anchor: &Event, target: &Event) -> EventPatch {
+        let anchor_start = self.starts_at.unwrap_or(anchor.starts_at);
+        let target_start = target.starts_at + (anchor_start - anchor.starts_at);
+        EventPatch {
+            title: self.title.clone(),
+            description: self.description.clone(),
+            location: self.location.clone(),
+            starts_at: self.starts_at.map(|_| target_start),
+            ends_at: self.ends_at.map(|v| target_start + (v - anchor_start)),
+            signup_deadline: self.signup_deadline.map(|v| target_start + (v - anchor_start)),
+            capacity: self.capacity,
+            is_published: self.is_published,
+            publish_at: self.publish_at.map(|o| o.map(|v| target_start + (v - anchor_start))),
+            unpublish_at: self.unpublish_at.map(|o| o.map(|v| target_start + (v - anchor_start))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn event(starts_at: OffsetDateTime) -> Event {
+        Event::new(
+            Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), "Club".into(), None, None,
+            starts_at, None, None, Some(10), true,
+        ).unwrap()
+    }
+
+    #[test]
+    fn relative_patch_shifts_times_per_occurrence() {
+        let anchor = event(datetime!(2025-09-01 18:00 UTC));
+        let target = event(datetime!(2025-09-08 18:00 UTC));
+        let p = EventPatch {
+            starts_at: Some(datetime!(2025-09-01 19:00 UTC)),
+            ends_at: Some(datetime!(2025-09-01 21:00 UTC)),
+            capacity: Some(20),
+            ..Default::default()
+        };
+
+        let r = p.relative_to(&anchor, &target);
+        assert_eq!(r.starts_at, Some(datetime!(2025-09-08 19:00 UTC)));
+        assert_eq!(r.ends_at, Some(datetime!(2025-09-08 21:00 UTC)));
+        assert_eq!(r.signup_deadline, None);
+        assert_eq!(r.capacity, Some(20));
+    }
+
+    #[test]
+    fn schedule_must_unpublish_after_publish() {
+        let e = event(datetime!(2025-09-10 18:00 UTC));
+        let on = datetime!(2025-09-05 09:00 UTC);
+        assert!(e.clone().with_schedule(Some(on), Some(on)).is_err());
+        assert!(e.clone().with_schedule(Some(on), None).is_ok());
+
+        let mut e = e.with_schedule(Some(on), Some(datetime!(2025-09-11 00:00 UTC))).unwrap();
+        e.apply(EventPatch { unpublish_at: Some(None), ..Default::default() }).unwrap();
+        assert_eq!(e.publish_at, Some(on));
+        assert_eq!(e.unpublish_at, None);
+    }
+
+    #[test]
+    fn validation_reports_every_field() {
+        let mut e = event(datetime!(2025-09-10 18:00 UTC));
+        let err = e.apply(EventPatch {
+            title: Some("  ".into()),
+            capacity: Some(-1),
+            signup_deadline: Some(datetime!(2025-09-11 00:00 UTC)),
+            ..Default::default()
+        }).unwrap_err();
+        let fields: Vec<_> = err.fields().iter().map(|f| (f.field, f.code)).collect();
+        assert_eq!(fields, [("title", "required"), ("signup_deadline", "after_start"), ("capacity", "negative")]);
+    }
+}
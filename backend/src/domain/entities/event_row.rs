@@ -2,7 +2,21 @@ use sqlx::FromRow;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::domain::entities::event::{Event, EventValidationError};
+use crate::domain::entities::event::Event;
+use crate::domain::entities::validation::ValidationErrors;
+
+// Жизненный цикл публикации; is_published == (status == Published)
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[sqlx(type_name = "event_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum EventStatus {
+    Draft,
+    PendingReview,
+    Published,
+    Rejected,
+    // отменено вместе с записями; обратно не возвращается
+    Canceled,
+}
 
 #[derive(Debug, Clone, FromRow)]
 pub struct EventRow {
@@ -17,15 +31,19 @@ pub struct EventRow {
     pub signup_deadline: Option<OffsetDateTime>,
     pub capacity: Option<i32>,
     pub is_published: bool,
+    pub series_id: Option<Uuid>,
+    pub series_index: Option<i32>,
+    pub publish_at: Option<OffsetDateTime>,
+    pub unpublish_at: Option<OffsetDateTime>,
 }
 
 impl TryFrom<EventRow> for Event {
-    type Error = EventValidationError;
+    type Error = ValidationErrors;
     fn try_from(r: EventRow) -> Result<Self, Self::Error> {
         Event::new(
             r.id, r.company_id, r.manager_id, r.title, r.description, r.location,
             r.starts_at, r.ends_at, r.signup_deadline, r.capacity, r.is_published,
-        )
+        )?.with_schedule(r.publish_at, r.unpublish_at)
     }
 }
 
@@ -43,6 +61,10 @@ impl From<Event> for EventRow {
             signup_deadline: d.signup_deadline,
             capacity: d.capacity,
             is_published: d.is_published,
+            series_id: None,
+            series_index: None,
+            publish_at: d.publish_at,
+            unpublish_at: d.unpublish_at,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+// верхняя граница на число вхождений в одной серии
+pub const MAX_OCCURRENCES: usize = 100;
+
+#[derive(
+    sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize
+)]
+#[sqlx(type_name = "series_frequency", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum SeriesFrequency {
+    Weekly,
+    Biweekly,
+    Custom,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RecurrenceError {
+    #[error("weekly/biweekly series require `until` or `count`")]
+    MissingBound,
+    #[error("custom series require at least one date")]
+    EmptyDates,
+    #[error("count must be between 1 and {MAX_OCCURRENCES}")]
+    CountOutOfRange,
+    #[error("until is before the first occurrence")]
+    UntilBeforeStart,
+    #[error("series must not exceed {MAX_OCCURRENCES} occurrences")]
+    TooManyOccurrences,
+}
+
+#[derive(Debug, Clone)]
+pub struct Recurrence {
+    pub frequency: SeriesFrequency,
+    pub until: Option<OffsetDateTime>,
+    pub count: Option<i32>,
+    pub dates: Vec<OffsetDateTime>,
+}
+
+impl Recurrence {
+    // Начала вхождений по порядку; first — начало шаблонного события.
+    pub fn expand(&self, first: OffsetDateTime) -> Result<Vec<OffsetDateTime>, RecurrenceError> {
+        if let Some(c) = self.count {
+            if c < 1 || c as usize > MAX_OCCURRENCES {
+                return Err(RecurrenceError::CountOutOfRange);
+            }
+        }
+        let limit = self.count.map(|c| c as usize).unwrap_or(MAX_OCCURRENCES);
+
+        let mut out = match self.frequency {
+            SeriesFrequency::Custom => {
+                if self.dates.is_empty() {
+                    return Err(RecurrenceError::EmptyDates);
+                }
+                let mut v: Vec<OffsetDateTime> = std::iter::once(first)
+                    .chain(self.dates.iter().copied())
+                    .filter(|d| self.until.is_none_or(|u| *d <= u))
+                    .collect();
+                v.sort();
+                v.dedup();
+                v
+            }
+            SeriesFrequency::Weekly | SeriesFrequency::Biweekly => {
+                if self.until.is_none() && self.count.is_none() {
+                    return Err(RecurrenceError::MissingBound);
+                }
+                let step = match self.frequency {
+                    SeriesFrequency::Biweekly => Duration::weeks(2),
+                    _ => Duration::weeks(1),
+                };
+                let mut v = Vec::new();
+                let mut at = first;
+                while v.len() < limit && self.until.is_none_or(|u| at <= u) {
+                    v.push(at);
+                    at += step;
+                }
+                // until без count, растянутый дальше предела
+                if self.count.is_none() && self.until.is_some_and(|u| at <= u) {
+                    return Err(RecurrenceError::TooManyOccurrences);
+                }
+                v
+            }
+        };
+
+        if out.is_empty() {
+            return Err(RecurrenceError::UntilBeforeStart);
+        }
+        if out.len() > limit {
+            if self.count.is_some() {
+                out.truncate(limit);
+            } else {
+                return Err(RecurrenceError::TooManyOccurrences);
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EventSeriesRow {
+    pub id: Uuid,
+    pub company_id: Uuid,
+    pub manager_id: Uuid,
+    pub frequency: SeriesFrequency,
+    pub until: Option<OffsetDateTime>,
+    pub occurrences: Option<i32>,
+    pub custom_dates: Vec<OffsetDateTime>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn rule(frequency: SeriesFrequency, until: Option<OffsetDateTime>, count: Option<i32>) -> Recurrence {
+        Recurrence { frequency, until, count, dates: Vec::new() }
+    }
+
+    #[test]
+    fn weekly_by_count_and_biweekly_by_until() {
+        let first = datetime!(2025-09-01 18:00 UTC);
+
+        let w = rule(SeriesFrequency::Weekly, None, Some(3)).expand(first).unwrap();
+        assert_eq!(w, vec![first, datetime!(2025-09-08 18:00 UTC), datetime!(2025-09-15 18:00 UTC)]);
+
+        let b = rule(SeriesFrequency::Biweekly, Some(datetime!(2025-09-29 18:00 UTC)), None)
+            .expand(first)
+            .unwrap();
+        assert_eq!(b.len(), 3);
+        assert_eq!(b[2], datetime!(2025-09-29 18:00 UTC));
+    }
+
+    #[test]
+    fn custom_dates_are_sorted_and_deduplicated() {
+        let first = datetime!(2025-09-10 10:00 UTC);
+        let r = Recurrence {
+            frequency: SeriesFrequency::Custom,
+            until: None,
+            count: None,
+            dates: vec![datetime!(2025-09-20 10:00 UTC), first, datetime!(2025-09-15 10:00 UTC)],
+        };
+        assert_eq!(
+            r.expand(first).unwrap(),
+            vec![first, datetime!(2025-09-15 10:00 UTC), datetime!(2025-09-20 10:00 UTC)]
+        );
+    }
+
+    #[test]
+    fn rejects_unbounded_and_oversized() {
+        let first = datetime!(2025-09-01 18:00 UTC);
+        assert_eq!(rule(SeriesFrequency::Weekly, None, None).expand(first), Err(RecurrenceError::MissingBound));
+        assert_eq!(rule(SeriesFrequency::Weekly, None, Some(0)).expand(first), Err(RecurrenceError::CountOutOfRange));
+        assert_eq!(
+            rule(SeriesFrequency::Weekly, Some(datetime!(2030-01-01 0:00 UTC)), None).expand(first),
+            Err(RecurrenceError::TooManyOccurrences)
+        );
+        assert_eq!(
+            rule(SeriesFrequency::Weekly, Some(datetime!(2025-08-01 0:00 UTC)), None).expand(first),
+            Err(RecurrenceError::UntilBeforeStart)
+        );
+    }
+}
@@ -1,7 +1,13 @@
 pub mod user;
+pub mod validation;
 pub mod company;
 pub mod event;
+pub mod event_series;
 pub mod registration;
+pub mod calendar;
+pub mod search;
+pub mod tag;
+pub mod notification;
 pub mod company_row;
 pub mod event_row;
 pub mod registration_row;
@@ -9,4 +15,3 @@ pub mod manager;
 pub mod manager_row;
 pub mod student_row;
 pub mod user_row;
-
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use time::macros::format_description;
+use time::{Duration, OffsetDateTime, UtcOffset};
+
+use crate::utils::template;
+
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[sqlx(type_name = "notification_channel", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    Telegram,
+    Email,
+}
+
+// Вид напоминания; ключ пишется в event_reminders.kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReminderKind {
+    BeforeStart(Duration),
+    // «завтра закрывается запись» для тех, кто смотрел событие, но не записался
+    Deadline,
+}
+
+impl ReminderKind {
+    pub fn key(&self) -> String {
+        match self {
+            ReminderKind::BeforeStart(lead) => format!("start-{}m", lead.whole_minutes()),
+            ReminderKind::Deadline => "deadline".into(),
+        }
+    }
+
+    pub fn parse(key: &str) -> Option<Self> {
+        if key == "deadline" {
+            return Some(ReminderKind::Deadline);
+        }
+        let minutes: i64 = key.strip_prefix("start-")?.strip_suffix('m')?.parse().ok()?;
+        Some(ReminderKind::BeforeStart(Duration::minutes(minutes)))
+    }
+}
+
+// Окна планирования: напоминание за lead уходит событиям с началом в (now + next, now + lead],
+// где next — следующий по убыванию lead. Кто записался позже, чем за lead, получит только
+// более позднее напоминание, а не все пропущенные разом
+pub fn lead_windows(leads: &[Duration]) -> Vec<(Duration, Duration)> {
+    let mut sorted: Vec<Duration> = leads.iter().copied().filter(|d| d.is_positive()).collect();
+    sorted.sort_by(|a, b| b.cmp(a));
+    sorted.dedup();
+    sorted
+        .iter()
+        .enumerate()
+        .map(|(i, &lead)| (lead, sorted.get(i + 1).copied().unwrap_or(Duration::ZERO)))
+        .collect()
+}
+
+// Что нужно, чтобы собрать текст напоминания
+#[derive(Debug, Clone)]
+pub struct ReminderEvent {
+    pub title: String,
+    pub location: Option<String>,
+    pub starts_at: OffsetDateTime,
+    pub signup_deadline: Option<OffsetDateTime>,
+}
+
+// Тексты в HTML-разметке Telegram; подставляемые значения экранируются
+const START_TEMPLATE: &str = "Напоминание: <b>«{title}»</b> начнётся {when}{place}.";
+const DEADLINE_TEMPLATE: &str = "Запись на <b>«{title}»</b> закрывается {when}. Успейте записаться!";
+
+pub fn render_reminder(kind: ReminderKind, e: &ReminderEvent, offset: UtcOffset) -> String {
+    let fmt = format_description!("[day].[month] в [hour]:[minute]");
+    let at = |t: OffsetDateTime| t.to_offset(offset).format(&fmt).expect("valid timestamp");
+    match kind {
+        ReminderKind::BeforeStart(_) => {
+            let place = e.location.as_deref().map(|l| format!(", {l}")).unwrap_or_default();
+            template::render(START_TEMPLATE, &[("title", &e.title), ("when", &at(e.starts_at)), ("place", &place)])
+        }
+        ReminderKind::Deadline => {
+            let dl = e.signup_deadline.unwrap_or(e.starts_at);
+            template::render(DEADLINE_TEMPLATE, &[("title", &e.title), ("when", &at(dl))])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::{datetime, offset};
+
+    #[test]
+    fn kind_keys_roundtrip() {
+        for k in [ReminderKind::BeforeStart(Duration::hours(24)), ReminderKind::BeforeStart(Duration::minutes(90)), ReminderKind::Deadline] {
+            assert_eq!(ReminderKind::parse(&k.key()), Some(k));
+        }
+        assert_eq!(ReminderKind::BeforeStart(Duration::hours(1)).key(), "start-60m");
+        assert_eq!(ReminderKind::parse("start-xm"), None);
+    }
+
+    #[test]
+    fn windows_do_not_overlap() {
+        let w = lead_windows(&[Duration::hours(1), Duration::hours(24), Duration::hours(1)]);
+        assert_eq!(w, vec![(Duration::hours(24), Duration::hours(1)), (Duration::hours(1), Duration::ZERO)]);
+        assert!(lead_windows(&[]).is_empty());
+    }
+
+    #[test]
+    fn renders_in_local_time() {
+        let e = ReminderEvent {
+            title: "Rust & Go".into(),
+            location: Some("ауд. 222".into()),
+            starts_at: datetime!(2025-10-01 11:00 UTC),
+            signup_deadline: Some(datetime!(2025-09-30 17:30 UTC)),
+        };
+        assert_eq!(
+            render_reminder(ReminderKind::BeforeStart(Duration::hours(1)), &e, offset!(+7)),
+            "Напоминание: <b>«Rust &amp; Go»</b> начнётся 01.10 в 18:00, ауд. 222."
+        );
+        assert_eq!(
+            render_reminder(ReminderKind::Deadline, &e, offset!(+7)),
+            "Запись на <b>«Rust &amp; Go»</b> закрывается 01.10 в 00:30. Успейте записаться!"
+        );
+    }
+}
@@ -1 +1,28 @@
-// domain registration entity
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(
+    sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize
+)]
+#[sqlx(type_name = "registration_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationStatus {
+    Registered,
+    Waitlisted,
+    Canceled,
+    Attended,
+    NoShow,
+}
+
+// waitlist_position считается с 1, заполнен только для Waitlisted
+#[derive(Debug, Clone, Copy)]
+pub struct RegistrationState {
+    pub status: RegistrationStatus,
+    pub waitlist_position: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StudentRegistration {
+    pub event_id: Uuid,
+    pub state: RegistrationState,
+}
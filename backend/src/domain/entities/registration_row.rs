@@ -3,6 +3,8 @@ use sqlx::FromRow;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+use crate::domain::entities::registration::RegistrationStatus;
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct RegistrationRow {
@@ -13,6 +15,9 @@ pub struct RegistrationRow {
     #[serde(with = "time::serde::rfc3339")]
     pub registered_at: OffsetDateTime,
     pub gcal_event_id: Option<String>,
+    pub status: RegistrationStatus,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub checked_in_at: Option<OffsetDateTime>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -0,0 +1,41 @@
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchKind {
+    Event,
+    Company,
+}
+
+impl SearchKind {
+    pub fn from_db(s: &str) -> Self {
+        match s {
+            "company" => Self::Company,
+            _ => Self::Event,
+        }
+    }
+}
+
+// Что пользователь вправе найти
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchScope {
+    pub events: bool,
+    pub companies: bool,
+    // декан видит всё, включая черновики и архивные компании
+    pub everything: bool,
+    // черновики своей компании видит её подтверждённый менеджер
+    pub own_company: Option<Uuid>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchHitRow {
+    pub kind: SearchKind,
+    pub id: Uuid,
+    pub title: String,
+    pub snippet: String,
+    pub rank: f32,
+    pub company_id: Option<Uuid>,
+    pub company_name: Option<String>,
+    pub starts_at: Option<OffsetDateTime>,
+    pub is_published: Option<bool>,
+}
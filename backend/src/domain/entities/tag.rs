@@ -0,0 +1,56 @@
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TagRow {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+    pub category: Option<String>,
+}
+
+// Сколько событий текущей выборки помечено тегом
+#[derive(Debug, Clone)]
+pub struct TagFacet {
+    pub slug: String,
+    pub name: String,
+    pub category: Option<String>,
+    pub count: i64,
+}
+
+#[derive(Debug, Error)]
+pub enum TagValidationError {
+    #[error("slug must be lowercase latin letters, digits and single dashes")]
+    BadSlug,
+    #[error("name must not be empty")]
+    EmptyName,
+}
+
+// slug попадает в query string фильтров, поэтому только [a-z0-9-]
+pub fn validate_slug(slug: &str) -> Result<(), TagValidationError> {
+    let ok = !slug.is_empty()
+        && slug.split('-').all(|part| {
+            !part.is_empty() && part.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+        });
+    if ok { Ok(()) } else { Err(TagValidationError::BadSlug) }
+}
+
+pub fn validate_name(name: &str) -> Result<(), TagValidationError> {
+    if name.trim().is_empty() { Err(TagValidationError::EmptyName) } else { Ok(()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slug_rules() {
+        assert!(validate_slug("career-fair").is_ok());
+        assert!(validate_slug("ml").is_ok());
+        assert!(validate_slug("").is_err());
+        assert!(validate_slug("ML").is_err());
+        assert!(validate_slug("-ml").is_err());
+        assert!(validate_slug("back--end").is_err());
+        assert!(validate_slug("бэкенд").is_err());
+    }
+}
@@ -1,4 +1,3 @@
-use time::OffsetDateTime;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use crate::auth::roles::ManagerStatus;
@@ -9,9 +8,6 @@ pub struct UserCommon {
     pub name: String,
     pub email: String,
     pub password_hash: String,
-    pub refresh_token_hash: Option<String>,
-    #[serde(with = "time::serde::rfc3339::option")]
-    pub refresh_token_expiration: Option<OffsetDateTime>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,9 +44,6 @@ pub struct UserUpdateCommon {
     pub name: Option<String>,
     pub email: Option<String>,
     pub password: Option<String>,
-    pub refresh_token_hash: Option<String>,
-    #[serde(with = "time::serde::rfc3339::option")]
-    pub refresh_token_expiration: Option<OffsetDateTime>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
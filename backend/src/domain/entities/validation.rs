@@ -0,0 +1,76 @@
+use serde::Serialize;
+use thiserror::Error;
+
+// Проблема в одном поле запроса: field — имя поля в JSON, code — стабильный код для клиентов,
+// message — текст для человека
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub code: &'static str,
+    pub message: String,
+}
+
+// Все проблемы запроса сразу, чтобы форма могла подсветить каждое поле
+#[derive(Debug, Default, Clone, Error)]
+#[error("{}", self.summary())]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn add(&mut self, field: &'static str, code: &'static str, message: impl Into<String>) {
+        self.0.push(FieldError { field, code, message: message.into() });
+    }
+
+    pub fn push(&mut self, e: impl Into<FieldError>) {
+        self.0.push(e.into());
+    }
+
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    pub fn fields(&self) -> &[FieldError] { &self.0 }
+
+    pub fn check(self) -> Result<(), Self> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+
+    // Объединяет уже найденные ошибки с результатом следующей проверки
+    pub fn and<T>(mut self, next: Result<T, ValidationErrors>) -> Result<T, ValidationErrors> {
+        match next {
+            Ok(v) if self.is_empty() => Ok(v),
+            Ok(_) => Err(self),
+            Err(e) => {
+                self.0.extend(e.0);
+                Err(self)
+            }
+        }
+    }
+
+    fn summary(&self) -> String {
+        self.0.iter().map(|e| e.message.as_str()).collect::<Vec<_>>().join("; ")
+    }
+}
+
+impl From<FieldError> for ValidationErrors {
+    fn from(e: FieldError) -> Self { Self(vec![e]) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn and_collects_errors_from_both_sides() {
+        let mut errs = ValidationErrors::new();
+        errs.add("tags", "unknown", "unknown tags: x");
+        let mut other = ValidationErrors::new();
+        other.add("title", "required", "title must not be empty");
+
+        let r: Result<(), _> = errs.and(Err(other));
+        let e = r.unwrap_err();
+        assert_eq!(e.fields().iter().map(|f| f.field).collect::<Vec<_>>(), ["tags", "title"]);
+        assert_eq!(e.to_string(), "unknown tags: x; title must not be empty");
+
+        assert_eq!(ValidationErrors::new().and(Ok(1)).unwrap(), 1);
+    }
+}
@@ -3,15 +3,16 @@ use time::OffsetDateTime;
 
 use crate::api::models::event::EventOut;
 use crate::api::requests::event::CreateEventIn;
-use crate::domain::entities::event::{Event, EventValidationError};
-use crate::domain::entities::event_row::EventRow;
+use crate::domain::entities::event::Event;
+use crate::domain::entities::validation::ValidationErrors;
+use crate::domain::entities::event_row::{EventRow, EventStatus};
 
 impl EventRow {
     pub fn from_manager_input(
         input: CreateEventIn,
         company_id: Uuid,
         manager_id: Uuid,
-    ) -> Result<Self, EventValidationError> {
+    ) -> Result<Self, ValidationErrors> {
         let e = Event::new(
             Uuid::new_v4(),
             company_id,
@@ -24,7 +25,7 @@ impl EventRow {
             input.signup_deadline,
             input.capacity,
             input.is_published.unwrap_or(false),
-        )?;
+        )?.with_schedule(input.publish_at, input.unpublish_at)?;
         Ok(e.into())
     }
 }
@@ -46,6 +47,15 @@ pub struct EventWithCount {
     pub capacity: Option<i32>,
     pub is_published: bool,
     pub registered_count: Option<i64>,
+    pub series_id: Option<Uuid>,
+    pub series_index: Option<i32>,
+    pub tags: Vec<String>,
+    pub status: EventStatus,
+    pub review_reason: Option<String>,
+    pub cancel_reason: Option<String>,
+    pub canceled_at: Option<OffsetDateTime>,
+    pub publish_at: Option<OffsetDateTime>,
+    pub unpublish_at: Option<OffsetDateTime>,
 }
 
 impl From<EventWithCount> for EventOut {
@@ -63,6 +73,17 @@ impl From<EventWithCount> for EventOut {
             registered_count: v.registered_count,
             capacity: v.capacity,
             is_published: v.is_published,
+            series_id: v.series_id,
+            series_index: v.series_index,
+            tags: v.tags,
+            status: v.status,
+            review_reason: v.review_reason,
+            cancel_reason: v.cancel_reason,
+            canceled_at: v.canceled_at,
+            publish_at: v.publish_at,
+            unpublish_at: v.unpublish_at,
+            registration_status: None,
+            waitlist_position: None,
         }
     }
 }
@@ -82,6 +103,17 @@ impl From<EventRow> for EventOut {
             registered_count: None,
             capacity: r.capacity,
             is_published: r.is_published,
+            series_id: r.series_id,
+            series_index: r.series_index,
+            tags: Vec::new(),
+            status: if r.is_published { EventStatus::Published } else { EventStatus::Draft },
+            review_reason: None,
+            cancel_reason: None,
+            canceled_at: None,
+            publish_at: r.publish_at,
+            unpublish_at: r.unpublish_at,
+            registration_status: None,
+            waitlist_position: None,
         }
     }
 }
\ No newline at end of file
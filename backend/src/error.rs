@@ -6,6 +6,9 @@ use axum::{
 use serde::Serialize;
 use crate::infra::errors::RepoError;
 use crate::domain::entities::company::CompanyValidationError;
+use crate::domain::entities::validation::{FieldError, ValidationErrors};
+use crate::infra::google::oauth::GoogleError;
+use crate::middleware::request_id::RequestId;
 
 pub type ApiResult<T> = Result<T, ApiError>;
 
@@ -29,6 +32,10 @@ pub enum ApiError {
     #[error("Unprocessable entity: {0}")]
     Unprocessable(String),
 
+    // ошибки по полям формы; отдаются списком в error.fields
+    #[error("Validation failed: {0}")]
+    Validation(ValidationErrors),
+
     #[error("Conflict: {0}")]
     Conflict(String),
 
@@ -42,17 +49,55 @@ pub enum ApiError {
 #[derive(Serialize)]
 struct ErrorBody<'a> {
     error: ErrorContent<'a>,
+    // чтобы по сообщению пользователя найти запрос в логах
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
 }
 
 #[derive(Serialize)]
 struct ErrorContent<'a> {
     code: &'a str,
     message: &'a str,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    fields: &'a [FieldError],
+}
+
+// Стабильный код ошибки по HTTP-статусу: для ответов, которые собрал не ApiError
+// (отказы экстракторов axum, 404/405 роутера)
+pub fn status_code_name(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST            => "BAD_REQUEST",
+        StatusCode::UNAUTHORIZED           => "UNAUTHORIZED",
+        StatusCode::FORBIDDEN              => "FORBIDDEN",
+        StatusCode::NOT_FOUND              => "NOT_FOUND",
+        StatusCode::METHOD_NOT_ALLOWED     => "METHOD_NOT_ALLOWED",
+        StatusCode::CONFLICT               => "CONFLICT",
+        StatusCode::PRECONDITION_FAILED    => "PRECONDITION_FAILED",
+        StatusCode::PAYLOAD_TOO_LARGE      => "PAYLOAD_TOO_LARGE",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "UNSUPPORTED_MEDIA_TYPE",
+        StatusCode::UNPROCESSABLE_ENTITY   => "UNPROCESSABLE_ENTITY",
+        StatusCode::TOO_MANY_REQUESTS      => "TOO_MANY_REQUESTS",
+        StatusCode::NOT_IMPLEMENTED        => "NOT_IMPLEMENTED",
+        s if s.is_server_error()           => "INTERNAL",
+        _                                  => "HTTP_ERROR",
+    }
+}
+
+// Единый формат ошибки {error:{code,message},request_id}
+pub fn error_response(http: StatusCode, code: &str, message: &str) -> Response {
+    render(http, code, message, &[])
+}
+
+fn render(http: StatusCode, code: &str, message: &str, fields: &[FieldError]) -> Response {
+    let body = ErrorBody { error: ErrorContent { code, message, fields }, request_id: RequestId::current() };
+    (http, Json(body)).into_response()
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         use ApiError::*;
+        let fields = match &self { Validation(errs) => errs.fields(), _ => &[] };
+        let summary;
         let (code, msg, http) = match &self {
             NotImplemented               => ("NOT_IMPLEMENTED",      "Not implemented",               StatusCode::NOT_IMPLEMENTED),
             Unauthorized                 => ("UNAUTHORIZED",         "Unauthorized",                  StatusCode::UNAUTHORIZED),
@@ -62,16 +107,29 @@ impl IntoResponse for ApiError {
             Unprocessable(m)             => ("UNPROCESSABLE_ENTITY", m.as_str(),                      StatusCode::UNPROCESSABLE_ENTITY),
             Conflict(m)                  => ("CONFLICT",             m.as_str(),                      StatusCode::CONFLICT),
             PreconditionFailed(m)        => ("PRECONDITION_FAILED",  m.as_str(),                      StatusCode::PRECONDITION_FAILED),
-            Internal(m)                  => ("INTERNAL",             m.as_str(),                      StatusCode::INTERNAL_SERVER_ERROR),
+            // подробности (текст ошибки sqlx и т.п.) только в лог, клиенту — общее сообщение
+            Internal(m)                  => {
+                tracing::error!(error = %m, "internal error");
+                ("INTERNAL",             "Internal server error",         StatusCode::INTERNAL_SERVER_ERROR)
+            }
+            Validation(errs)             => {
+                summary = errs.to_string();
+                ("VALIDATION_FAILED",    summary.as_str(),                StatusCode::UNPROCESSABLE_ENTITY)
+            }
         };
-        let body = ErrorBody { error: ErrorContent { code, message: msg } };
-        (http, Json(body)).into_response()
+        render(http, code, msg, fields)
     }
 }
 
 impl From<CompanyValidationError> for ApiError {
     fn from(e: CompanyValidationError) -> Self {
-        ApiError::Unprocessable(e.to_string())
+        ApiError::Validation(FieldError::from(e).into())
+    }
+}
+
+impl From<ValidationErrors> for ApiError {
+    fn from(e: ValidationErrors) -> Self {
+        ApiError::Validation(e)
     }
 }
 
@@ -84,4 +142,14 @@ impl From<RepoError> for ApiError {
             RepoError::Db(err) => ApiError::Internal(err.to_string()),
         }
     }
-}
\ No newline at end of file
+}
+
+impl From<GoogleError> for ApiError {
+    fn from(e: GoogleError) -> Self {
+        match e {
+            GoogleError::NotConfigured => ApiError::PreconditionFailed(e.to_string()),
+            GoogleError::NoRefreshToken => ApiError::Unprocessable(e.to_string()),
+            other => ApiError::Internal(other.to_string()),
+        }
+    }
+}
@@ -1 +1,110 @@
-// google calendar placeholder
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use super::oauth::{check, GoogleError};
+
+// у Google конец обязателен; для событий без ends_at ставим час
+const DEFAULT_LENGTH: Duration = Duration::hours(1);
+
+#[derive(Debug, Clone)]
+pub struct CalendarEventData {
+    pub event_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub starts_at: OffsetDateTime,
+    pub ends_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize)]
+struct EventTime {
+    #[serde(rename = "dateTime")]
+    date_time: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EventBody<'a> {
+    summary: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<&'a str>,
+    start: EventTime,
+    end: EventTime,
+    extended_properties: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct Created {
+    id: String,
+}
+
+impl CalendarEventData {
+    fn body(&self) -> EventBody<'_> {
+        let fmt = |t: OffsetDateTime| EventTime { date_time: t.format(&Rfc3339).expect("valid timestamp") };
+        EventBody {
+            summary: &self.title,
+            description: self.description.as_deref(),
+            location: self.location.as_deref(),
+            start: fmt(self.starts_at),
+            end: fmt(self.ends_at.unwrap_or(self.starts_at + DEFAULT_LENGTH)),
+            extended_properties: serde_json::json!({
+                "private": { "tsuEventId": self.event_id.to_string() }
+            }),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GoogleCalendarClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl GoogleCalendarClient {
+    pub fn new(http: reqwest::Client, base_url: String) -> Self {
+        Self { http, base_url }
+    }
+
+    fn events_url(&self) -> String {
+        format!("{}/calendars/primary/events", self.base_url)
+    }
+
+    pub async fn insert(&self, access_token: &str, ev: &CalendarEventData) -> Result<String, GoogleError> {
+        let resp = self.http
+            .post(self.events_url())
+            .bearer_auth(access_token)
+            .json(&ev.body())
+            .send()
+            .await?;
+        let created: Created = check(resp).await?.json().await?;
+        Ok(created.id)
+    }
+
+    pub async fn update(&self, access_token: &str, gcal_id: &str, ev: &CalendarEventData) -> Result<(), GoogleError> {
+        let resp = self.http
+            .put(format!("{}/{gcal_id}", self.events_url()))
+            .bearer_auth(access_token)
+            .json(&ev.body())
+            .send()
+            .await?;
+        check(resp).await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, access_token: &str, gcal_id: &str) -> Result<(), GoogleError> {
+        let resp = self.http
+            .delete(format!("{}/{gcal_id}", self.events_url()))
+            .bearer_auth(access_token)
+            .send()
+            .await?;
+        match check(resp).await {
+            Ok(_) => Ok(()),
+            Err(e) if e.is_gone() => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -1 +1,168 @@
-// google oauth placeholder
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as b64;
+use base64::Engine;
+use rand::RngCore;
+use reqwest::Url;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::config::Config;
+
+pub const CALENDAR_SCOPE: &str = "https://www.googleapis.com/auth/calendar.events";
+
+#[derive(Debug, Error)]
+pub enum GoogleError {
+    #[error("google integration is not configured")]
+    NotConfigured,
+    #[error("google did not return a refresh token")]
+    NoRefreshToken,
+    #[error("google api error {status}: {body}")]
+    Api { status: u16, body: String },
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+}
+
+impl GoogleError {
+    // 404/410 на копии события — её уже удалили руками в календаре
+    pub fn is_gone(&self) -> bool {
+        matches!(self, GoogleError::Api { status: 404 | 410, .. })
+    }
+
+    // invalid_grant: пользователь отозвал доступ в настройках Google
+    pub fn is_revoked(&self) -> bool {
+        matches!(self, GoogleError::Api { status: 400, body } if body.contains("invalid_grant"))
+    }
+}
+
+pub(crate) async fn check(resp: reqwest::Response) -> Result<reqwest::Response, GoogleError> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+    let body = resp.text().await.unwrap_or_default();
+    Err(GoogleError::Api { status: status.as_u16(), body })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+}
+
+// PKCE (RFC 7636, S256): verifier остаётся у нас, challenge уходит в authorization URL
+pub struct Pkce {
+    pub verifier: String,
+    pub challenge: String,
+}
+
+impl Pkce {
+    pub fn generate() -> Self {
+        let verifier = random_urlsafe(32);
+        let challenge = challenge_for(&verifier);
+        Self { verifier, challenge }
+    }
+}
+
+pub fn random_urlsafe(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    rand::thread_rng().fill_bytes(&mut buf);
+    b64.encode(buf)
+}
+
+fn challenge_for(verifier: &str) -> String {
+    b64.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+#[derive(Clone)]
+pub struct GoogleOAuthClient {
+    http: reqwest::Client,
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+    auth_url: String,
+    token_url: String,
+}
+
+impl GoogleOAuthClient {
+    pub fn new(http: reqwest::Client, cfg: &Config) -> Self {
+        Self {
+            http,
+            client_id: cfg.google_client_id.clone(),
+            client_secret: cfg.google_client_secret.clone(),
+            redirect_uri: cfg.google_redirect_uri.clone(),
+            auth_url: cfg.google_auth_url.clone(),
+            token_url: cfg.google_token_url.clone(),
+        }
+    }
+
+    pub fn is_configured(&self) -> bool {
+        !self.client_id.is_empty() && !self.client_secret.is_empty() && !self.redirect_uri.is_empty()
+    }
+
+    // access_type=offline + prompt=consent: иначе при повторном подключении refresh_token не выдаётся
+    pub fn authorize_url(&self, state: &str, code_challenge: &str) -> Result<String, GoogleError> {
+        if !self.is_configured() {
+            return Err(GoogleError::NotConfigured);
+        }
+        let url = Url::parse_with_params(
+            &self.auth_url,
+            &[
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", self.redirect_uri.as_str()),
+                ("response_type", "code"),
+                ("scope", CALENDAR_SCOPE),
+                ("access_type", "offline"),
+                ("prompt", "consent"),
+                ("state", state),
+                ("code_challenge", code_challenge),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+            .map_err(|_| GoogleError::NotConfigured)?;
+        Ok(url.into())
+    }
+
+    pub async fn exchange_code(&self, code: &str, code_verifier: &str) -> Result<TokenResponse, GoogleError> {
+        let resp = self.http
+            .post(&self.token_url)
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("code_verifier", code_verifier),
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+                ("redirect_uri", self.redirect_uri.as_str()),
+            ])
+            .send()
+            .await?;
+        Ok(check(resp).await?.json().await?)
+    }
+
+    pub async fn access_token(&self, refresh_token: &str) -> Result<String, GoogleError> {
+        let resp = self.http
+            .post(&self.token_url)
+            .form(&[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token),
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+            ])
+            .send()
+            .await?;
+        let t: TokenResponse = check(resp).await?.json().await?;
+        Ok(t.access_token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pkce_challenge_matches_rfc_example() {
+        // RFC 7636, Appendix B
+        let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+        assert_eq!(challenge_for(verifier), "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+    }
+}
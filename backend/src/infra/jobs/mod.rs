@@ -1 +1,117 @@
 pub mod worker;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use time::Duration;
+use uuid::Uuid;
+
+use crate::domain::entities::notification::NotificationChannel;
+
+// Типы задач в очереди; строка пишется в jobs.kind
+pub mod kinds {
+    // привести копии события в Google Calendar к текущему состоянию
+    pub const GCAL_SYNC_EVENT: &str = "gcal.sync_event";
+    // отправить одно запланированное напоминание о событии
+    pub const NOTIFY_REMINDER: &str = "notify.reminder";
+    // отправить одно письмо (infra::mail::templates::MailJob)
+    pub const MAIL_SEND: &str = "mail.send";
+    // отправить готовое сообщение в привязанный Telegram-чат пользователя (TelegramJob)
+    pub const TELEGRAM_SEND: &str = "telegram.send";
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventJob {
+    pub event_id: Uuid,
+}
+
+// kind — ключ ReminderKind, как в event_reminders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderJob {
+    pub event_id: Uuid,
+    pub user_id: Uuid,
+    pub kind: String,
+    pub channel: NotificationChannel,
+}
+
+// Текст в HTML-разметке Bot API; чат ищется при отправке, по активной привязке
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelegramJob {
+    pub user_id: Uuid,
+    pub html: String,
+}
+
+type HandlerFuture = Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>;
+type Handler = Arc<dyn Fn(serde_json::Value) -> HandlerFuture + Send + Sync>;
+
+// Обработчики по типам задач; собирается один раз при старте
+#[derive(Clone, Default)]
+pub struct JobRegistry {
+    handlers: HashMap<&'static str, Handler>,
+}
+
+impl JobRegistry {
+    pub fn new() -> Self { Self::default() }
+
+    // payload разбирается в P до вызова; не разобрался — это ошибка попытки
+    pub fn on<P, F, Fut>(mut self, kind: &'static str, f: F) -> Self
+    where
+        P: DeserializeOwned + Send + 'static,
+        F: Fn(P) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        let f = Arc::new(f);
+        let handler: Handler = Arc::new(move |payload| {
+            let f = f.clone();
+            Box::pin(async move { f(serde_json::from_value(payload)?).await })
+        });
+        self.handlers.insert(kind, handler);
+        self
+    }
+
+    pub async fn run(&self, kind: &str, payload: serde_json::Value) -> anyhow::Result<()> {
+        match self.handlers.get(kind) {
+            Some(h) => h(payload).await,
+            None => anyhow::bail!("no handler registered for job kind {kind}"),
+        }
+    }
+}
+
+// Пауза перед следующей попыткой: 30с, 1м, 2м, … но не больше часа
+pub fn backoff(attempts: i32) -> Duration {
+    const BASE: Duration = Duration::seconds(30);
+    const MAX: Duration = Duration::hours(1);
+    let exp = attempts.clamp(1, 16) - 1;
+    (BASE * (1i32 << exp)).min(MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_an_hour() {
+        assert_eq!(backoff(1), Duration::seconds(30));
+        assert_eq!(backoff(2), Duration::minutes(1));
+        assert_eq!(backoff(4), Duration::minutes(4));
+        assert_eq!(backoff(8), Duration::hours(1));
+        assert_eq!(backoff(100), Duration::hours(1));
+    }
+
+    #[tokio::test]
+    async fn registry_routes_by_kind_and_parses_payload() {
+        let reg = JobRegistry::new().on(kinds::GCAL_SYNC_EVENT, |job: EventJob| async move {
+            anyhow::ensure!(!job.event_id.is_nil(), "nil event");
+            Ok(())
+        });
+
+        let ok = serde_json::json!({ "event_id": Uuid::new_v4() });
+        assert!(reg.run(kinds::GCAL_SYNC_EVENT, ok).await.is_ok());
+        assert!(reg.run(kinds::GCAL_SYNC_EVENT, serde_json::json!({})).await.is_err());
+        assert!(reg.run("unknown", serde_json::json!({})).await.is_err());
+    }
+}
@@ -1 +1,143 @@
-// job worker placeholder
+use std::time::Duration;
+
+use time::OffsetDateTime;
+use tokio::task::JoinHandle;
+
+use crate::domain::entities::notification::{lead_windows, ReminderKind};
+use crate::infra::jobs::{backoff, JobRegistry};
+use crate::infra::repositories::event_repo::EventRepository;
+use crate::infra::repositories::idempotency_repo::IdempotencyRepository;
+use crate::infra::repositories::job_repo::{JobRepository, JobRow};
+use crate::infra::repositories::reminder_repo::ReminderRepository;
+use crate::infra::repositories::session_repo::SessionRepository;
+
+// за это время задача должна выполниться, иначе её сочтут брошенной и отдадут другому воркеру
+const LEASE: time::Duration = time::Duration::minutes(5);
+const BATCH: i64 = 16;
+// выполненные задачи храним неделю, чтобы было что смотреть при разборе
+const KEEP_DONE: time::Duration = time::Duration::days(7);
+// повтор с Idempotency-Key имеет смысл в пределах суток
+const KEEP_IDEMPOTENCY: time::Duration = time::Duration::days(1);
+// отозванные сессии ещё месяц видны при разборе инцидентов
+const KEEP_SESSIONS: time::Duration = time::Duration::days(30);
+// «запись закрывается завтра»
+const DEADLINE_NUDGE: time::Duration = time::Duration::days(1);
+
+// Периодические задачи по событиям: перевод неотмеченных в no_show,
+// публикация/снятие с публикации по расписанию, планирование напоминаний, чистка очереди, старых ключей идемпотентности и закончившихся сессий.
+// Отметки о выполнении лежат в самих событиях и в event_reminders, так что после рестарта
+// пропущенное доделывается на первом же тике, а сделанное не повторяется.
+// reminder_leads = None — напоминания выключены (бот не настроен)
+pub fn spawn<R, J, N, I, S>(events: R, jobs: J, reminders: N, idempotency: I, sessions: S, reminder_leads: Option<Vec<time::Duration>>, every: Duration) -> JoinHandle<()>
+where
+    R: EventRepository + Send + Sync + 'static,
+    J: JobRepository + Send + Sync + 'static,
+    N: ReminderRepository + Send + Sync + 'static,
+    I: IdempotencyRepository + Send + Sync + 'static,
+    S: SessionRepository + Send + Sync + 'static,
+{
+    let windows = reminder_leads.as_deref().map(lead_windows);
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(every);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tick.tick().await;
+            let now = OffsetDateTime::now_utc();
+            match events.publish_scheduled(now).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!(count = n, "scheduled events published"),
+                Err(e) => tracing::warn!(error = %e, "scheduled publish failed"),
+            }
+            match events.unpublish_scheduled(now).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!(count = n, "scheduled events unpublished"),
+                Err(e) => tracing::warn!(error = %e, "scheduled unpublish failed"),
+            }
+            match events.mark_no_shows(None, now).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!(count = n, "registrations marked as no_show"),
+                Err(e) => tracing::warn!(error = %e, "no_show sweep failed"),
+            }
+            if let Some(windows) = &windows {
+                schedule_reminders(&reminders, windows, now).await;
+            }
+            match jobs.purge_done(now - KEEP_DONE).await {
+                Ok(0) => {}
+                Ok(n) => tracing::debug!(count = n, "finished jobs purged"),
+                Err(e) => tracing::warn!(error = %e, "job purge failed"),
+            }
+            match idempotency.purge(now - KEEP_IDEMPOTENCY).await {
+                Ok(0) => {}
+                Ok(n) => tracing::debug!(count = n, "idempotency keys purged"),
+                Err(e) => tracing::warn!(error = %e, "idempotency purge failed"),
+            }
+            match sessions.purge(now - KEEP_SESSIONS).await {
+                Ok(0) => {}
+                Ok(n) => tracing::debug!(count = n, "ended sessions purged"),
+                Err(e) => tracing::warn!(error = %e, "session purge failed"),
+            }
+        }
+    })
+}
+
+async fn schedule_reminders<N: ReminderRepository>(reminders: &N, windows: &[(time::Duration, time::Duration)], now: OffsetDateTime) {
+    for &(lead, next) in windows {
+        let kind = ReminderKind::BeforeStart(lead).key();
+        match reminders.schedule_start(&kind, now + next, now + lead).await {
+            Ok(0) => {}
+            Ok(n) => tracing::info!(count = n, kind = %kind, "event reminders scheduled"),
+            Err(e) => tracing::warn!(error = %e, kind = %kind, "reminder scheduling failed"),
+        }
+    }
+    match reminders.schedule_deadline(now, now + DEADLINE_NUDGE).await {
+        Ok(0) => {}
+        Ok(n) => tracing::info!(count = n, "signup deadline reminders scheduled"),
+        Err(e) => tracing::warn!(error = %e, "deadline reminder scheduling failed"),
+    }
+}
+
+// Разбор очереди: пока забирается полная пачка — без пауз, иначе ждём poll
+pub fn spawn_queue<J>(jobs: J, registry: JobRegistry, poll: Duration) -> JoinHandle<()>
+where
+    J: JobRepository + Send + Sync + 'static,
+{
+    tokio::spawn(async move {
+        loop {
+            let now = OffsetDateTime::now_utc();
+            let batch = match jobs.claim(now, now + LEASE, BATCH).await {
+                Ok(b) => b,
+                Err(e) => {
+                    tracing::warn!(error = %e, "job claim failed");
+                    Vec::new()
+                }
+            };
+            let full = batch.len() as i64 == BATCH;
+            for job in batch {
+                run_one(&jobs, &registry, job).await;
+            }
+            if !full {
+                tokio::time::sleep(poll).await;
+            }
+        }
+    })
+}
+
+async fn run_one<J: JobRepository>(jobs: &J, registry: &JobRegistry, job: JobRow) {
+    let res = registry.run(&job.kind, job.payload).await;
+    let now = OffsetDateTime::now_utc();
+    let saved = match res {
+        Ok(()) => jobs.complete(job.id, now).await,
+        Err(e) => {
+            let error = format!("{e:#}");
+            let retry_at = (job.attempts < job.max_attempts).then(|| now + backoff(job.attempts));
+            match retry_at {
+                Some(at) => tracing::warn!(job_id = %job.id, kind = %job.kind, attempt = job.attempts, retry_at = %at, error = %error, "job failed"),
+                None => tracing::error!(job_id = %job.id, kind = %job.kind, attempts = job.attempts, error = %error, "job is dead"),
+            }
+            jobs.fail(job.id, &error, retry_at, now).await
+        }
+    };
+    if let Err(e) = saved {
+        tracing::warn!(job_id = %job.id, error = %e, "failed to record job outcome");
+    }
+}
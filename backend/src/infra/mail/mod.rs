@@ -0,0 +1,89 @@
+pub mod templates;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use lettre::message::{header::ContentType, Mailbox, MultiPart, SinglePart};
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use uuid::Uuid;
+
+use crate::config::{Config, MailTransportConfig};
+use templates::Rendered;
+
+// Куда физически уходит готовое письмо
+#[async_trait]
+pub trait MailTransport: Send + Sync {
+    async fn send(&self, message: Message) -> anyhow::Result<()>;
+}
+
+pub struct SmtpTransport {
+    inner: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpTransport {
+    pub fn from_url(url: &str) -> anyhow::Result<Self> {
+        Ok(Self { inner: AsyncSmtpTransport::<Tokio1Executor>::from_url(url)?.build() })
+    }
+}
+
+#[async_trait]
+impl MailTransport for SmtpTransport {
+    async fn send(&self, message: Message) -> anyhow::Result<()> {
+        self.inner.send(message).await?;
+        Ok(())
+    }
+}
+
+// Письма складываются файлами .eml: их открывает любой почтовый клиент,
+// а тесты читают каталог вместо настоящего ящика
+pub struct OutboxTransport {
+    dir: PathBuf,
+}
+
+impl OutboxTransport {
+    pub fn new(dir: PathBuf) -> Self { Self { dir } }
+}
+
+#[async_trait]
+impl MailTransport for OutboxTransport {
+    async fn send(&self, message: Message) -> anyhow::Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let stamp = time::OffsetDateTime::now_utc().unix_timestamp_nanos();
+        tokio::fs::write(self.dir.join(format!("{stamp}-{}.eml", Uuid::new_v4())), message.formatted()).await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct Mailer {
+    transport: Arc<dyn MailTransport>,
+    from: Mailbox,
+}
+
+impl Mailer {
+    pub fn new(transport: Arc<dyn MailTransport>, from: Mailbox) -> Self { Self { transport, from } }
+
+    pub fn from_config(cfg: &Config) -> anyhow::Result<Self> {
+        let transport: Arc<dyn MailTransport> = match &cfg.mail_transport {
+            MailTransportConfig::Smtp(url) => Arc::new(SmtpTransport::from_url(url)?),
+            MailTransportConfig::Outbox(dir) => Arc::new(OutboxTransport::new(dir.clone())),
+        };
+        Ok(Self::new(transport, cfg.mail_from.parse()?))
+    }
+
+    // text/plain и text/html в одном письме; клиент сам выберет, что показать
+    pub async fn send(&self, to: &str, name: &str, mail: Rendered) -> anyhow::Result<()> {
+        let to = Mailbox::new(Some(name.to_owned()), to.parse()?);
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(mail.subject)
+            .multipart(
+                MultiPart::alternative()
+                    .singlepart(SinglePart::builder().header(ContentType::TEXT_PLAIN).body(mail.text))
+                    .singlepart(SinglePart::builder().header(ContentType::TEXT_HTML).body(mail.html)),
+            )?;
+        self.transport.send(message).await
+    }
+}
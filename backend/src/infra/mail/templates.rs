@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+use time::macros::format_description;
+use time::{OffsetDateTime, UtcOffset};
+
+use crate::utils::template;
+
+// Снимок события на момент постановки письма: после удаления события
+// письмо об отмене всё равно должно знать, что именно отменили
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventInfo {
+    pub title: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub starts_at: OffsetDateTime,
+    pub location: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "template", rename_all = "snake_case")]
+pub enum Mail {
+    RegistrationConfirmed { event: EventInfo, waitlisted: bool },
+    RegistrationCanceled { event: EventInfo },
+    EventChanged { event: EventInfo },
+    EventCanceled { event: EventInfo },
+    StudentApproved,
+    StudentRejected,
+    ManagerApproved { company: String },
+    ManagerRejected { company: String },
+    // ссылка на страницу сброса пароля, действует minutes минут
+    PasswordReset { link: String, minutes: i64 },
+    // ссылка для подтверждения адреса после регистрации, действует hours часов
+    VerifyEmail { link: String, hours: i64 },
+}
+
+// Задача mail.send: адресат плюс данные шаблона; текст собирается при отправке
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailJob {
+    pub to: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub mail: Mail,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rendered {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+impl Mail {
+    // письма о событиях подчиняются отказу от почты; решения по аккаунту уходят всегда
+    pub fn is_optional(&self) -> bool {
+        !matches!(
+            self,
+            Mail::StudentApproved
+                | Mail::StudentRejected
+                | Mail::ManagerApproved { .. }
+                | Mail::ManagerRejected { .. }
+                | Mail::PasswordReset { .. }
+                | Mail::VerifyEmail { .. }
+        )
+    }
+
+    pub fn render(&self, name: &str, offset: UtcOffset) -> Rendered {
+        let fmt = format_description!("[day].[month].[year] в [hour]:[minute]");
+        let event_vars = |e: &EventInfo| {
+            let when = e.starts_at.to_offset(offset).format(&fmt).expect("valid timestamp");
+            let place = e.location.as_deref().map(|l| format!(", {l}")).unwrap_or_default();
+            [("title".to_owned(), e.title.clone()), ("when".to_owned(), when), ("place".to_owned(), place)]
+        };
+        let (subject, lines, vars): (&str, &[&str], Vec<(String, String)>) = match self {
+            Mail::RegistrationConfirmed { event, waitlisted: false } => (
+                "Вы записаны на «{title}»",
+                &["Вы записаны на событие «{title}».", "Начало: {when}{place}."],
+                event_vars(event).to_vec(),
+            ),
+            Mail::RegistrationConfirmed { event, waitlisted: true } => (
+                "Вы в листе ожидания на «{title}»",
+                &["Мест на «{title}» пока нет, вы в листе ожидания.", "Если место освободится, запись подтвердится автоматически."],
+                event_vars(event).to_vec(),
+            ),
+            Mail::RegistrationCanceled { event } => (
+                "Запись на «{title}» отменена",
+                &["Ваша запись на событие «{title}» ({when}) отменена."],
+                event_vars(event).to_vec(),
+            ),
+            Mail::EventChanged { event } => (
+                "Изменения в событии «{title}»",
+                &["В событии «{title}», на которое вы записаны, изменились время или место.", "Теперь: {when}{place}."],
+                event_vars(event).to_vec(),
+            ),
+            Mail::EventCanceled { event } => (
+                "Событие «{title}» отменено",
+                &["К сожалению, событие «{title}» ({when}) отменено."],
+                event_vars(event).to_vec(),
+            ),
+            Mail::StudentApproved => (
+                "Аккаунт подтверждён",
+                &["Деканат подтвердил ваш аккаунт студента. Теперь можно записываться на события."],
+                Vec::new(),
+            ),
+            Mail::StudentRejected => (
+                "Аккаунт не подтверждён",
+                &["Деканат отклонил заявку на подтверждение аккаунта студента. Если это ошибка, обратитесь в деканат."],
+                Vec::new(),
+            ),
+            Mail::ManagerApproved { company } => (
+                "Вы менеджер компании «{company}»",
+                &["Ваша заявка менеджера компании «{company}» подтверждена. Теперь можно создавать события."],
+                vec![("company".to_owned(), company.clone())],
+            ),
+            Mail::ManagerRejected { company } => (
+                "Заявка в «{company}» отклонена",
+                &["Ваша заявка менеджера компании «{company}» отклонена."],
+                vec![("company".to_owned(), company.clone())],
+            ),
+            Mail::PasswordReset { link, minutes } => (
+                "Сброс пароля",
+                &[
+                    "Чтобы задать новый пароль, откройте ссылку: {link}",
+                    "Ссылка одноразовая и действует {minutes} мин. Если вы не запрашивали сброс, просто проигнорируйте это письмо.",
+                ],
+                vec![("link".to_owned(), link.clone()), ("minutes".to_owned(), minutes.to_string())],
+            ),
+            Mail::VerifyEmail { link, hours } => (
+                "Подтвердите адрес почты",
+                &[
+                    "Чтобы подтвердить этот адрес, откройте ссылку: {link}",
+                    "Ссылка действует {hours} ч. Если вы не регистрировались, просто проигнорируйте это письмо.",
+                ],
+                vec![("link".to_owned(), link.clone()), ("hours".to_owned(), hours.to_string())],
+            ),
+        };
+
+        let mut vars: Vec<(&str, &str)> = vars.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        vars.push(("name", name));
+        let greeting = "Здравствуйте, {name}!";
+
+        let html: String = std::iter::once(greeting)
+            .chain(lines.iter().copied())
+            .map(|l| format!("<p>{}</p>", template::render(l, &vars)))
+            .collect();
+        let text: Vec<String> = std::iter::once(greeting)
+            .chain(lines.iter().copied())
+            .map(|l| template::render_plain(l, &vars))
+            .collect();
+        Rendered {
+            subject: template::render_plain(subject, &vars),
+            html: format!("<!DOCTYPE html><html><body>{html}</body></html>"),
+            text: text.join("\n\n"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::{datetime, offset};
+
+    fn event() -> EventInfo {
+        EventInfo { title: "R&D day".into(), starts_at: datetime!(2025-10-01 11:00 UTC), location: Some("ауд. 222".into()) }
+    }
+
+    #[test]
+    fn renders_html_and_text() {
+        let r = Mail::RegistrationConfirmed { event: event(), waitlisted: false }.render("Аня", offset!(+7));
+        assert_eq!(r.subject, "Вы записаны на «R&D day»");
+        assert_eq!(r.text, "Здравствуйте, Аня!\n\nВы записаны на событие «R&D day».\n\nНачало: 01.10.2025 в 18:00, ауд. 222.");
+        assert!(r.html.contains("<p>Вы записаны на событие «R&amp;D day».</p>"));
+    }
+
+    #[test]
+    fn job_payload_is_flat() {
+        let job = MailJob { to: "a@b.c".into(), name: "A".into(), mail: Mail::EventCanceled { event: event() } };
+        let v = serde_json::to_value(&job).unwrap();
+        assert_eq!(v["template"], "event_canceled");
+        assert_eq!(v["event"]["title"], "R&D day");
+        let back: MailJob = serde_json::from_value(v).unwrap();
+        assert_eq!(back.mail, job.mail);
+        assert!(!Mail::StudentApproved.is_optional());
+        assert!(!Mail::PasswordReset { link: "x".into(), minutes: 30 }.is_optional());
+    }
+}
@@ -4,4 +4,5 @@ pub mod security;
 pub mod google;
 pub mod telegram;
 pub mod jobs;
+pub mod mail;
 pub mod errors;
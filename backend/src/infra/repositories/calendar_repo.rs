@@ -0,0 +1,173 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::domain::entities::calendar::{CalendarEntryRow, CalendarEntryStatus};
+use crate::infra::errors::{RepoError, RepoResult};
+
+#[async_trait]
+pub trait CalendarRepository {
+    async fn event_entry(&self, event_id: Uuid) -> RepoResult<CalendarEntryRow>;
+    async fn company_name(&self, company_id: Uuid) -> RepoResult<String>;
+    async fn company_entries(&self, company_id: Uuid, since: OffsetDateTime) -> RepoResult<Vec<CalendarEntryRow>>;
+    async fn student_entries(&self, student_id: Uuid, since: OffsetDateTime) -> RepoResult<Vec<CalendarEntryRow>>;
+
+    async fn set_feed_token(&self, user_id: Uuid, token_hash: &str) -> RepoResult<()>;
+    async fn delete_feed_token(&self, user_id: Uuid) -> RepoResult<()>;
+    async fn user_by_feed_token(&self, token_hash: &str) -> RepoResult<Uuid>;
+}
+
+#[derive(Clone)]
+pub struct PgCalendarRepository { pool: Pool<Postgres> }
+impl PgCalendarRepository { pub fn new(pool: Pool<Postgres>) -> Self { Self { pool } } }
+
+// Общая форма строк ленты: события и надгробия приходят одним UNION
+struct EntryRow {
+    id: Uuid,
+    sequence: i32,
+    title: String,
+    description: Option<String>,
+    location: Option<String>,
+    starts_at: OffsetDateTime,
+    ends_at: Option<OffsetDateTime>,
+    updated_at: OffsetDateTime,
+    state: String,
+}
+
+impl From<EntryRow> for CalendarEntryRow {
+    fn from(r: EntryRow) -> Self {
+        Self {
+            id: r.id, sequence: r.sequence, title: r.title,
+            description: r.description, location: r.location,
+            starts_at: r.starts_at, ends_at: r.ends_at, updated_at: r.updated_at,
+            status: CalendarEntryStatus::from_db(&r.state),
+        }
+    }
+}
+
+#[async_trait]
+impl CalendarRepository for PgCalendarRepository {
+    async fn event_entry(&self, event_id: Uuid) -> RepoResult<CalendarEntryRow> {
+        let r = sqlx::query_as!(
+            EntryRow,
+            r#"
+            SELECT id, sequence, title, description, location, starts_at, ends_at, updated_at,
+                   CASE
+                       WHEN is_published THEN 'confirmed'
+                       WHEN was_published THEN 'cancelled'
+                       ELSE 'tentative'
+                   END AS "state!"
+            FROM events
+            WHERE id = $1
+            "#,
+            event_id
+        )
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+        Ok(r.into())
+    }
+
+    async fn company_name(&self, company_id: Uuid) -> RepoResult<String> {
+        let name = sqlx::query_scalar!("SELECT name FROM companies WHERE id = $1", company_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        name.ok_or(RepoError::NotFound)
+    }
+
+    async fn company_entries(&self, company_id: Uuid, since: OffsetDateTime) -> RepoResult<Vec<CalendarEntryRow>> {
+        // снятые с публикации и удалённые после since события отдаются как CANCELLED
+        let rows = sqlx::query_as!(
+            EntryRow,
+            r#"
+            SELECT e.id AS "id!", e.sequence AS "sequence!", e.title AS "title!",
+                   e.description, e.location, e.starts_at AS "starts_at!", e.ends_at,
+                   e.updated_at AS "updated_at!",
+                   CASE WHEN e.is_published THEN 'confirmed' ELSE 'cancelled' END AS "state!"
+            FROM events e
+            WHERE e.company_id = $1
+              AND (e.is_published OR (e.was_published AND e.updated_at >= $2))
+            UNION ALL
+            SELECT d.id, d.sequence, d.title, NULL, NULL, d.starts_at, d.ends_at, d.deleted_at, 'cancelled'
+            FROM deleted_events d
+            WHERE d.company_id = $1 AND d.deleted_at >= $2
+            ORDER BY 6
+            "#,
+            company_id,
+            since
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(CalendarEntryRow::from).collect())
+    }
+
+    async fn student_entries(&self, student_id: Uuid, since: OffsetDateTime) -> RepoResult<Vec<CalendarEntryRow>> {
+        // SEQUENCE личной ленты учитывает и ревизию записи (растёт при каждой смене статуса),
+        // чтобы календарь принял смену статуса без правок самого события
+        let rows = sqlx::query_as!(
+            EntryRow,
+            r#"
+            SELECT e.id AS "id!",
+                   (e.sequence + r.revision) AS "sequence!",
+                   e.title AS "title!", e.description, e.location,
+                   e.starts_at AS "starts_at!", e.ends_at,
+                   GREATEST(e.updated_at, r.registered_at, r.canceled_at) AS "updated_at!",
+                   CASE
+                       WHEN r.status = 'canceled' OR NOT e.is_published THEN 'cancelled'
+                       WHEN r.status = 'waitlisted' THEN 'tentative'
+                       ELSE 'confirmed'
+                   END AS "state!"
+            FROM registrations r
+            JOIN events e ON e.id = r.event_id
+            WHERE r.student_id = $1
+              AND (r.status <> 'canceled' OR r.canceled_at >= $2)
+            UNION ALL
+            SELECT d.id, d.sequence + d.revision, d.title, NULL, NULL, d.starts_at, d.ends_at, d.deleted_at, 'cancelled'
+            FROM deleted_events d
+            WHERE $1 = ANY (d.student_ids) AND d.deleted_at >= $2
+            ORDER BY 6
+            "#,
+            student_id,
+            since
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(CalendarEntryRow::from).collect())
+    }
+
+    async fn set_feed_token(&self, user_id: Uuid, token_hash: &str) -> RepoResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO calendar_feed_tokens (user_id, token_hash)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE
+                SET token_hash = EXCLUDED.token_hash,
+                    created_at = now()
+            "#,
+            user_id,
+            token_hash
+        )
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_feed_token(&self, user_id: Uuid) -> RepoResult<()> {
+        let res = sqlx::query!("DELETE FROM calendar_feed_tokens WHERE user_id = $1", user_id)
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 { return Err(RepoError::NotFound); }
+        Ok(())
+    }
+
+    async fn user_by_feed_token(&self, token_hash: &str) -> RepoResult<Uuid> {
+        let id = sqlx::query_scalar!(
+            "SELECT user_id FROM calendar_feed_tokens WHERE token_hash = $1",
+            token_hash
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+        id.ok_or(RepoError::NotFound)
+    }
+}
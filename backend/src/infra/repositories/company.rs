@@ -16,6 +16,7 @@ pub trait CompanyRepository {
     async fn create(&self, row: CompanyRow) -> RepoResult<CompanyRow>;
     async fn get(&self, id: Uuid) -> RepoResult<CompanyWithCounts>;
     async fn update_name(&self, id: Uuid, name: &str) -> RepoResult<CompanyWithCounts>;
+    async fn set_requires_review(&self, id: Uuid, flag: bool) -> RepoResult<CompanyWithCounts>;
 }
 
 #[derive(Clone)]
@@ -27,6 +28,7 @@ struct CompanyListRow {
     id: Uuid,
     name: String,
     status: CompanyStatus,
+    requires_event_review: bool,
     manager_count: Option<i64>,
     event_count: Option<i64>,
 }
@@ -37,6 +39,7 @@ impl From<CompanyListRow> for CompanyWithCounts {
             id: r.id,
             name: r.name,
             status: r.status,
+            requires_event_review: r.requires_event_review,
             manager_count: r.manager_count,
             event_count: r.event_count,
         }
@@ -50,21 +53,24 @@ impl CompanyRepository for PgCompanyRepository {
         let limit = limit.max(1);
         let offset_i64 = i64::from((page - 1) * limit);
         let limit_i64  = i64::from(limit);
-        let q_like: Option<String> = q.as_ref().map(|s| format!("{}%", s.to_lowercase()));
+        let q = q.as_deref().map(str::trim).filter(|s| !s.is_empty());
+        let q_like: Option<String> = q.map(|s| format!("{}%", s.to_lowercase()));
 
+        // префикс имени — для подсказок при наборе, полнотекст — по имени и описанию
         let rows = sqlx::query_as!(
             CompanyListRow,
             r#"
-            SELECT c.id, c.name, c.status as "status: CompanyStatus",
+            SELECT c.id, c.name, c.status as "status: CompanyStatus", c.requires_event_review,
                    (SELECT COUNT(*)::bigint FROM managers m WHERE m.company_id = c.id) AS "manager_count?",
                    (SELECT COUNT(*)::bigint FROM events   e WHERE e.company_id = c.id) AS "event_count?"
             FROM companies c
             WHERE c.status = 'active'
-              AND ($1::text IS NULL OR lower(c.name) LIKE $1)
+              AND ($1::text IS NULL OR lower(c.name) LIKE $1 OR c.search_tsv @@ search_query($2))
             ORDER BY lower(c.name)
-            LIMIT $2 OFFSET $3
+            LIMIT $3 OFFSET $4
             "#,
             q_like,
+            q,
             limit_i64,
             offset_i64
         )
@@ -81,22 +87,24 @@ impl CompanyRepository for PgCompanyRepository {
         let limit = limit.max(1);
         let offset_i64 = i64::from((page - 1) * limit);
         let limit_i64  = i64::from(limit);
-        let q_like: Option<String> = q.as_ref().map(|s| format!("{}%", s.to_lowercase()));
+        let q = q.as_deref().map(str::trim).filter(|s| !s.is_empty());
+        let q_like: Option<String> = q.map(|s| format!("{}%", s.to_lowercase()));
 
         let rows = sqlx::query_as!(
             CompanyListRow,
             r#"
-            SELECT c.id, c.name, c.status as "status: CompanyStatus",
+            SELECT c.id, c.name, c.status as "status: CompanyStatus", c.requires_event_review,
                    (SELECT COUNT(*)::bigint FROM managers m WHERE m.company_id = c.id) AS "manager_count?",
                    (SELECT COUNT(*)::bigint FROM events   e WHERE e.company_id = c.id) AS "event_count?"
             FROM companies c
             WHERE ($1::bool OR c.status = 'active')
-              AND ($2::text IS NULL OR lower(c.name) LIKE $2)
+              AND ($2::text IS NULL OR lower(c.name) LIKE $2 OR c.search_tsv @@ search_query($3))
             ORDER BY lower(c.name)
-            LIMIT $3 OFFSET $4
+            LIMIT $4 OFFSET $5
             "#,
             include_archived,
             q_like,
+            q,
             limit_i64,
             offset_i64
         )
@@ -134,7 +142,7 @@ impl CompanyRepository for PgCompanyRepository {
         let r = sqlx::query_as!(
             CompanyListRow,
             r#"
-            SELECT c.id, c.name, c.status as "status: CompanyStatus",
+            SELECT c.id, c.name, c.status as "status: CompanyStatus", c.requires_event_review,
                    (SELECT COUNT(*)::bigint FROM managers m WHERE m.company_id = c.id) AS "manager_count?",
                    (SELECT COUNT(*)::bigint FROM events   e WHERE e.company_id = c.id) AS "event_count?"
             FROM companies c
@@ -155,7 +163,7 @@ impl CompanyRepository for PgCompanyRepository {
             UPDATE companies
                SET name = $2, updated_at = now()
              WHERE id = $1
-         RETURNING id, name, status as "status: CompanyStatus",
+         RETURNING id, name, status as "status: CompanyStatus", requires_event_review,
                    (SELECT COUNT(*)::bigint FROM managers m WHERE m.company_id = companies.id) AS "manager_count?",
                    (SELECT COUNT(*)::bigint FROM events   e WHERE e.company_id = companies.id) AS "event_count?"
             "#,
@@ -174,7 +182,7 @@ impl CompanyRepository for PgCompanyRepository {
             UPDATE companies
                SET status = $2::company_status, updated_at = now()
              WHERE id = $1
-         RETURNING id, name, status as "status: CompanyStatus",
+         RETURNING id, name, status as "status: CompanyStatus", requires_event_review,
                    (SELECT COUNT(*)::bigint FROM managers m WHERE m.company_id = companies.id) AS "manager_count?",
                    (SELECT COUNT(*)::bigint FROM events   e WHERE e.company_id = companies.id) AS "event_count?"
             "#,
@@ -185,4 +193,23 @@ impl CompanyRepository for PgCompanyRepository {
 
         r.map(CompanyWithCounts::from).ok_or(RepoError::NotFound)
     }
+
+    async fn set_requires_review(&self, id: Uuid, flag: bool) -> RepoResult<CompanyWithCounts> {
+        let r = sqlx::query_as!(
+            CompanyListRow,
+            r#"
+            UPDATE companies
+               SET requires_event_review = $2, updated_at = now()
+             WHERE id = $1
+         RETURNING id, name, status as "status: CompanyStatus", requires_event_review,
+                   (SELECT COUNT(*)::bigint FROM managers m WHERE m.company_id = companies.id) AS "manager_count?",
+                   (SELECT COUNT(*)::bigint FROM events   e WHERE e.company_id = companies.id) AS "event_count?"
+            "#,
+            id, flag
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        r.map(CompanyWithCounts::from).ok_or(RepoError::NotFound)
+    }
 }
\ No newline at end of file
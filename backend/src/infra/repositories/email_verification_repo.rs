@@ -0,0 +1,176 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::infra::errors::{RepoError, RepoResult};
+use crate::infra::mail::templates::Mail;
+use crate::infra::repositories::job_repo::enqueue_mail_to_user;
+
+#[async_trait]
+pub trait EmailVerificationRepository {
+    // false — предыдущее письмо ушло меньше min_interval назад, новое не отправляется.
+    // Conflict — адрес уже подтверждён
+    async fn issue(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        now: OffsetDateTime,
+        expires_at: OffsetDateTime,
+        min_interval: Duration,
+        mail: &Mail,
+    ) -> RepoResult<bool>;
+    // Подтверждает адрес и гасит все токены пользователя; NotFound — токен неизвестен или истёк
+    async fn verify(&self, token_hash: &str, now: OffsetDateTime) -> RepoResult<Uuid>;
+}
+
+#[derive(Clone)]
+pub struct PgEmailVerificationRepository { pool: Pool<Postgres> }
+impl PgEmailVerificationRepository { pub fn new(pool: Pool<Postgres>) -> Self { Self { pool } } }
+
+#[async_trait]
+impl EmailVerificationRepository for PgEmailVerificationRepository {
+    async fn issue(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        now: OffsetDateTime,
+        expires_at: OffsetDateTime,
+        min_interval: Duration,
+        mail: &Mail,
+    ) -> RepoResult<bool> {
+        let mut tx = self.pool.begin().await?;
+        // строка пользователя под блокировкой: параллельные повторы не обходят интервал
+        let verified = sqlx::query_scalar!(
+            r#"SELECT email_verified_at IS NOT NULL AS "verified!" FROM users WHERE id = $1 FOR UPDATE"#,
+            user_id
+        )
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+        if verified {
+            return Err(RepoError::Conflict("email already verified".into()));
+        }
+        let recent = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(SELECT 1 FROM email_verifications
+                           WHERE user_id = $1 AND created_at > $2) AS "recent!"
+            "#,
+            user_id, now - min_interval
+        )
+            .fetch_one(&mut *tx)
+            .await?;
+        if recent {
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            r#"DELETE FROM email_verifications WHERE user_id = $1 OR expires_at < $2"#,
+            user_id, now - Duration::days(1)
+        )
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO email_verifications (token_hash, user_id, created_at, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            token_hash, user_id, now, expires_at
+        )
+            .execute(&mut *tx)
+            .await?;
+        enqueue_mail_to_user(&mut tx, user_id, mail).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn verify(&self, token_hash: &str, now: OffsetDateTime) -> RepoResult<Uuid> {
+        let mut tx = self.pool.begin().await?;
+        let user_id = sqlx::query_scalar!(
+            r#"
+            DELETE FROM email_verifications
+             WHERE token_hash = $1 AND expires_at > $2
+            RETURNING user_id
+            "#,
+            token_hash, now
+        )
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+
+        sqlx::query!(
+            r#"UPDATE users SET email_verified_at = COALESCE(email_verified_at, $2) WHERE id = $1"#,
+            user_id, now
+        )
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(r#"DELETE FROM email_verifications WHERE user_id = $1"#, user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(user_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::repositories::user_repo::{PgUserRepository, UserRepository};
+
+    const INTERVAL: Duration = Duration::minutes(1);
+
+    fn mail() -> Mail {
+        Mail::VerifyEmail { link: "http://localhost/verify.html?token=t".into(), hours: 48 }
+    }
+
+    async fn student(pool: &Pool<Postgres>, email: &str) -> Uuid {
+        PgUserRepository::new(pool.clone()).create_student("Аня", email, "x").await.unwrap().id
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn token_is_single_use(pool: Pool<Postgres>) {
+        let repo = PgEmailVerificationRepository::new(pool.clone());
+        let user = student(&pool, "a@test.io").await;
+        let now = OffsetDateTime::now_utc();
+
+        assert!(repo.issue(user, "h1", now, now + Duration::hours(1), INTERVAL, &mail()).await.unwrap());
+        assert_eq!(repo.verify("h1", now).await.unwrap(), user);
+        assert!(matches!(repo.verify("h1", now).await, Err(RepoError::NotFound)));
+        assert!(PgUserRepository::new(pool).email_verified(user).await.unwrap());
+        // адрес подтверждён — новое письмо не нужно
+        let later = now + Duration::hours(1);
+        assert!(matches!(
+            repo.issue(user, "h2", later, later + Duration::hours(1), INTERVAL, &mail()).await,
+            Err(RepoError::Conflict(_))
+        ));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn expired_token_is_rejected(pool: Pool<Postgres>) {
+        let repo = PgEmailVerificationRepository::new(pool.clone());
+        let user = student(&pool, "b@test.io").await;
+        let now = OffsetDateTime::now_utc();
+
+        assert!(repo.issue(user, "h1", now, now + Duration::hours(1), INTERVAL, &mail()).await.unwrap());
+        assert!(matches!(repo.verify("h1", now + Duration::hours(1)).await, Err(RepoError::NotFound)));
+        assert!(!PgUserRepository::new(pool).email_verified(user).await.unwrap());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn resend_is_throttled_and_replaces_token(pool: Pool<Postgres>) {
+        let repo = PgEmailVerificationRepository::new(pool.clone());
+        let user = student(&pool, "c@test.io").await;
+        let now = OffsetDateTime::now_utc();
+        let exp = now + Duration::hours(1);
+
+        assert!(repo.issue(user, "h1", now, exp, INTERVAL, &mail()).await.unwrap());
+        assert!(!repo.issue(user, "h2", now + Duration::seconds(30), exp, INTERVAL, &mail()).await.unwrap());
+        assert!(repo.issue(user, "h3", now + INTERVAL + Duration::seconds(1), exp, INTERVAL, &mail()).await.unwrap());
+
+        // действует только последняя ссылка
+        let at = now + Duration::minutes(2);
+        assert!(matches!(repo.verify("h1", at).await, Err(RepoError::NotFound)));
+        assert!(matches!(repo.verify("h2", at).await, Err(RepoError::NotFound)));
+        assert_eq!(repo.verify("h3", at).await.unwrap(), user);
+    }
+}
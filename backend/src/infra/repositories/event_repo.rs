@@ -1,12 +1,18 @@
 use async_trait::async_trait;
-use sqlx::{Pool, Postgres};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Pool, Postgres};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::domain::entities::event_row::EventRow;
+use crate::domain::entities::event_row::{EventRow, EventStatus};
+use crate::domain::entities::event_series::{EventSeriesRow, SeriesFrequency};
+use crate::domain::entities::registration::{RegistrationState, RegistrationStatus, StudentRegistration};
 use crate::domain::entities::registration_row::RegistrationRow;
+use crate::domain::entities::tag::TagFacet;
 use crate::domain::mappers::event::EventWithCount;
 use crate::infra::errors::{RepoError, RepoResult};
+use crate::infra::mail::templates::{EventInfo, Mail};
+use crate::infra::repositories::job_repo::{enqueue_gcal_sync, enqueue_mail_to_participants, enqueue_mail_to_user};
 
 #[derive(Debug, Default, Clone)]
 pub struct EventListFilter {
@@ -14,24 +20,99 @@ pub struct EventListFilter {
     pub manager_id: Option<Uuid>,
     pub published: Option<bool>,
     pub q: Option<String>,
-    // pub from: Option<OffsetDateTime>,
-    // pub to: Option<OffsetDateTime>,
+    // окно по началу события: from <= starts_at < to
+    pub from: Option<OffsetDateTime>,
+    pub to: Option<OffsetDateTime>,
+    // по окончанию (ends_at, а без него starts_at) — для upcoming/past
+    pub ends_after: Option<OffsetDateTime>,
+    pub ends_before: Option<OffsetDateTime>,
+    // slug'и тегов; событие должно иметь все
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventSort {
+    #[default]
+    StartsAt,
+    CreatedAt,
+    SignupDeadline,
+    RemainingSeats,
+}
+
+impl EventSort {
+    fn as_str(self) -> &'static str {
+        match self {
+            EventSort::StartsAt => "starts_at",
+            EventSort::CreatedAt => "created_at",
+            EventSort::SignupDeadline => "signup_deadline",
+            EventSort::RemainingSeats => "remaining_seats",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+// Keyset-позиция: ключ сортировки и id последней отданной строки.
+// Ключ — bigint (микросекунды или места), для desc — со знаком минус,
+// поэтому в SQL порядок всегда (sort_key, id) по возрастанию.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventCursor {
+    pub key: i64,
+    pub id: Uuid,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EventPageRequest {
+    pub sort: EventSort,
+    pub order: SortOrder,
+    pub after: Option<EventCursor>,
+    pub limit: i64,
+}
+
+pub struct EventPage {
+    pub items: Vec<EventWithCount>,
+    pub next: Option<EventCursor>,
+    pub total: i64,
+    pub facets: Vec<TagFacet>,
 }
 
 #[async_trait]
 pub trait EventRepository {
-    async fn list(&self, page: i32, limit: i32, f: EventListFilter) -> RepoResult<Vec<EventWithCount>>;
+    async fn list(&self, f: EventListFilter, page: EventPageRequest) -> RepoResult<EventPage>;
     async fn create(&self, row: EventRow) -> RepoResult<EventRow>;
     async fn get(&self, id: Uuid) -> RepoResult<EventWithCount>;
     async fn update_all(&self, row: EventRow) -> RepoResult<EventWithCount>;
+    async fn update_many(&self, rows: Vec<EventRow>) -> RepoResult<()>;
+    async fn set_tags(&self, event_ids: &[Uuid], tag_ids: &[Uuid]) -> RepoResult<()>;
+    async fn create_series(&self, series: EventSeriesRow, rows: Vec<EventRow>) -> RepoResult<()>;
+    async fn get_series(&self, id: Uuid) -> RepoResult<EventSeriesRow>;
+    async fn list_series_events(&self, series_id: Uuid) -> RepoResult<Vec<EventWithCount>>;
     async fn delete(&self, id: Uuid) -> RepoResult<()>;
-    async fn set_published(&self, id: Uuid, flag: bool) -> RepoResult<EventWithCount>;
+    async fn cancel(&self, id: Uuid, by: Uuid, reason: &str, now_utc: OffsetDateTime) -> RepoResult<EventWithCount>;
+    async fn set_status(&self, id: Uuid, status: EventStatus) -> RepoResult<EventWithCount>;
+    async fn review(&self, id: Uuid, status: EventStatus, reviewer: Uuid, reason: Option<&str>) -> RepoResult<EventWithCount>;
+    async fn review_queue(&self) -> RepoResult<Vec<EventWithCount>>;
+    async fn requires_review(&self, company_id: Uuid) -> RepoResult<bool>;
     async fn set_deadline(&self, id: Uuid, deadline: Option<OffsetDateTime>) -> RepoResult<EventWithCount>;
     async fn list_registrations(&self, event_id: Uuid) -> RepoResult<Vec<RegistrationRow>>;
     async fn count_registrations(&self, event_id: Uuid) -> RepoResult<i64>;
-    async fn register(&self, event_id: Uuid, student_id: Uuid, now_utc: OffsetDateTime) -> RepoResult<()>;
+    async fn registration_state(&self, event_id: Uuid, student_id: Uuid) -> RepoResult<Option<RegistrationState>>;
+    async fn record_view(&self, event_id: Uuid, student_id: Uuid, now_utc: OffsetDateTime) -> RepoResult<()>;
+    async fn register(&self, event_id: Uuid, student_id: Uuid, now_utc: OffsetDateTime) -> RepoResult<RegistrationState>;
     async fn cancel_registration(&self, event_id: Uuid, student_id: Uuid, now_utc: OffsetDateTime) -> RepoResult<()>;
-    async fn list_registrations_by_student(&self, student_id: Uuid) -> RepoResult<Vec<RegistrationRow>>;
+    async fn list_registrations_by_student(&self, student_id: Uuid) -> RepoResult<Vec<StudentRegistration>>;
+    async fn check_in(&self, event_id: Uuid, student_ids: &[Uuid], checked_by: Uuid, now_utc: OffsetDateTime) -> RepoResult<Vec<Uuid>>;
+    async fn mark_no_shows(&self, event_id: Option<Uuid>, now_utc: OffsetDateTime) -> RepoResult<u64>;
+    async fn publish_scheduled(&self, now_utc: OffsetDateTime) -> RepoResult<u64>;
+    async fn unpublish_scheduled(&self, now_utc: OffsetDateTime) -> RepoResult<u64>;
+    async fn redeem_ticket(&self, event_id: Uuid, student_id: Uuid, checked_by: Uuid, now_utc: OffsetDateTime) -> RepoResult<Option<RegistrationRow>>;
 }
 
 #[derive(Clone)]
@@ -51,7 +132,56 @@ struct EventListRow {
     signup_deadline: Option<OffsetDateTime>,
     capacity: Option<i32>,
     is_published: bool,
+    series_id: Option<Uuid>,
+    series_index: Option<i32>,
+    registered_count: Option<i64>,
+    tags: Vec<String>,
+    status: EventStatus,
+    review_reason: Option<String>,
+    cancel_reason: Option<String>,
+    canceled_at: Option<OffsetDateTime>,
+    publish_at: Option<OffsetDateTime>,
+    unpublish_at: Option<OffsetDateTime>,
+}
+
+struct EventKeyedRow {
+    id: Uuid,
+    company_id: Uuid,
+    manager_id: Uuid,
+    title: String,
+    description: Option<String>,
+    location: Option<String>,
+    starts_at: OffsetDateTime,
+    ends_at: Option<OffsetDateTime>,
+    signup_deadline: Option<OffsetDateTime>,
+    capacity: Option<i32>,
+    is_published: bool,
+    series_id: Option<Uuid>,
+    series_index: Option<i32>,
     registered_count: Option<i64>,
+    tags: Vec<String>,
+    status: EventStatus,
+    review_reason: Option<String>,
+    cancel_reason: Option<String>,
+    canceled_at: Option<OffsetDateTime>,
+    publish_at: Option<OffsetDateTime>,
+    unpublish_at: Option<OffsetDateTime>,
+    sort_key: i64,
+}
+
+impl From<EventKeyedRow> for EventWithCount {
+    fn from(r: EventKeyedRow) -> Self {
+        Self {
+            id: r.id, company_id: r.company_id, manager_id: r.manager_id,
+            title: r.title, description: r.description, location: r.location,
+            starts_at: r.starts_at, ends_at: r.ends_at, signup_deadline: r.signup_deadline,
+            capacity: r.capacity, is_published: r.is_published, registered_count: r.registered_count,
+            series_id: r.series_id, series_index: r.series_index, tags: r.tags,
+            status: r.status, review_reason: r.review_reason,
+            cancel_reason: r.cancel_reason, canceled_at: r.canceled_at,
+            publish_at: r.publish_at, unpublish_at: r.unpublish_at,
+        }
+    }
 }
 
 impl From<EventListRow> for EventWithCount {
@@ -61,43 +191,152 @@ impl From<EventListRow> for EventWithCount {
             title: r.title, description: r.description, location: r.location,
             starts_at: r.starts_at, ends_at: r.ends_at, signup_deadline: r.signup_deadline,
             capacity: r.capacity, is_published: r.is_published, registered_count: r.registered_count,
+            series_id: r.series_id, series_index: r.series_index, tags: r.tags,
+            status: r.status, review_reason: r.review_reason,
+            cancel_reason: r.cancel_reason, canceled_at: r.canceled_at,
+            publish_at: r.publish_at, unpublish_at: r.unpublish_at,
         }
     }
 }
 
 #[async_trait]
 impl EventRepository for PgEventRepository {
-    async fn list(&self, page: i32, limit: i32, f: EventListFilter) -> RepoResult<Vec<EventWithCount>> {
-        let page = page.max(1);
-        let limit = limit.max(1);
-        let offset_i64 = i64::from((page - 1) * limit);
-        let limit_i64  = i64::from(limit);
-
-        let q_like: Option<String> = f.q.as_ref().map(|s| format!("%{}%", s));
+    async fn list(&self, f: EventListFilter, page: EventPageRequest) -> RepoResult<EventPage> {
+        let limit = page.limit.clamp(1, 100);
+        let q: Option<&str> = f.q.as_deref().map(str::trim).filter(|s| !s.is_empty());
+        let desc = page.order == SortOrder::Desc;
+        let (after_key, after_id) = match page.after {
+            Some(c) => (Some(c.key), Some(c.id)),
+            None => (None, None),
+        };
 
+        // NULL (без дедлайна / без лимита мест) считаем «бесконечностью»
         let rows = sqlx::query_as!(
-            EventListRow,
+            EventKeyedRow,
             r#"
-            SELECT e.id, e.company_id, e.manager_id, e.title, e.description, e.location,
-                   e.starts_at, e.ends_at, e.signup_deadline, e.capacity, e.is_published,
-                   (SELECT COUNT(*)::bigint
-                      FROM registrations er
-                     WHERE er.event_id = e.id) AS "registered_count?"
-            FROM events e
-            WHERE ($1::uuid  IS NULL OR e.company_id   = $1)
-              AND ($2::uuid  IS NULL OR e.manager_id   = $2)
-              AND ($3::bool  IS NULL OR e.is_published = $3)
-              AND ($4::text  IS NULL OR e.title ILIKE  $4)
-            ORDER BY e.starts_at DESC
-            LIMIT $5 OFFSET $6
+            WITH filtered AS (
+                SELECT e.id, e.company_id, e.manager_id, e.title, e.description, e.location,
+                       e.starts_at, e.ends_at, e.signup_deadline, e.capacity, e.is_published,
+                       e.series_id, e.series_index, e.created_at, e.status, e.review_reason,
+                       e.cancel_reason, e.canceled_at,
+                       e.publish_at, e.unpublish_at,
+                       (SELECT COUNT(*)::bigint
+                          FROM registrations er
+                         WHERE er.event_id = e.id AND er.status = 'registered') AS registered_count,
+                       ARRAY(SELECT t.slug FROM event_tags et JOIN tags t ON t.id = et.tag_id
+                              WHERE et.event_id = e.id ORDER BY t.slug) AS tags
+                FROM events e
+                WHERE ($1::uuid        IS NULL OR e.company_id   = $1)
+                  AND ($2::uuid        IS NULL OR e.manager_id   = $2)
+                  AND ($3::bool        IS NULL OR e.is_published = $3)
+                  AND ($4::text        IS NULL OR e.search_tsv @@ search_query($4))
+                  AND ($5::timestamptz IS NULL OR e.starts_at >= $5)
+                  AND ($6::timestamptz IS NULL OR e.starts_at <  $6)
+                  AND ($7::timestamptz IS NULL OR COALESCE(e.ends_at, e.starts_at) >= $7)
+                  AND ($8::timestamptz IS NULL OR COALESCE(e.ends_at, e.starts_at) <  $8)
+                  AND (SELECT COUNT(*) FROM event_tags et JOIN tags t ON t.id = et.tag_id
+                        WHERE et.event_id = e.id AND t.slug = ANY($14)) = cardinality($14::text[])
+            ),
+            keyed AS (
+                SELECT f.*,
+                       (CASE WHEN $9::bool THEN -1 ELSE 1 END) * (CASE $10::text
+                           WHEN 'created_at'      THEN (extract(epoch FROM f.created_at) * 1000000)::bigint
+                           WHEN 'signup_deadline' THEN COALESCE((extract(epoch FROM f.signup_deadline) * 1000000)::bigint,
+                                                                4611686018427387903)
+                           WHEN 'remaining_seats' THEN COALESCE(f.capacity - f.registered_count, 4611686018427387903)
+                           ELSE (extract(epoch FROM f.starts_at) * 1000000)::bigint
+                       END) AS sort_key
+                FROM filtered f
+            )
+            SELECT k.id AS "id!", k.company_id AS "company_id!", k.manager_id AS "manager_id!",
+                   k.title AS "title!", k.description, k.location,
+                   k.starts_at AS "starts_at!", k.ends_at, k.signup_deadline, k.capacity,
+                   k.is_published AS "is_published!", k.series_id, k.series_index,
+                   k.registered_count, k.tags AS "tags!",
+                   k.status AS "status!: EventStatus", k.review_reason, k.cancel_reason, k.canceled_at,
+                   k.publish_at, k.unpublish_at, k.sort_key AS "sort_key!"
+            FROM keyed k
+            WHERE $11::bigint IS NULL OR (k.sort_key, k.id) > ($11, $12::uuid)
+            ORDER BY k.sort_key, k.id
+            LIMIT $13
             "#,
-            f.company_id, f.manager_id, f.published, q_like,
-            limit_i64, offset_i64
+            f.company_id, f.manager_id, f.published, q,
+            f.from, f.to, f.ends_after, f.ends_before,
+            desc, page.sort.as_str(),
+            after_key, after_id,
+            limit + 1,
+            &f.tags
         )
             .fetch_all(&self.pool)
             .await?;
 
-        Ok(rows.into_iter().map(EventWithCount::from).collect())
+        let total: i64 = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*)::bigint AS "count!"
+            FROM events e
+            WHERE ($1::uuid        IS NULL OR e.company_id   = $1)
+              AND ($2::uuid        IS NULL OR e.manager_id   = $2)
+              AND ($3::bool        IS NULL OR e.is_published = $3)
+              AND ($4::text        IS NULL OR e.search_tsv @@ search_query($4))
+              AND ($5::timestamptz IS NULL OR e.starts_at >= $5)
+              AND ($6::timestamptz IS NULL OR e.starts_at <  $6)
+              AND ($7::timestamptz IS NULL OR COALESCE(e.ends_at, e.starts_at) >= $7)
+              AND ($8::timestamptz IS NULL OR COALESCE(e.ends_at, e.starts_at) <  $8)
+              AND (SELECT COUNT(*) FROM event_tags et JOIN tags t ON t.id = et.tag_id
+                    WHERE et.event_id = e.id AND t.slug = ANY($9)) = cardinality($9::text[])
+            "#,
+            f.company_id, f.manager_id, f.published, q,
+            f.from, f.to, f.ends_after, f.ends_before,
+            &f.tags
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        // фасеты считаются по всей выборке, а не по странице
+        let facets = sqlx::query!(
+            r#"
+            SELECT t.slug, t.name, t.category, COUNT(*)::bigint AS "count!"
+            FROM events e
+            JOIN event_tags et ON et.event_id = e.id
+            JOIN tags t ON t.id = et.tag_id
+            WHERE ($1::uuid        IS NULL OR e.company_id   = $1)
+              AND ($2::uuid        IS NULL OR e.manager_id   = $2)
+              AND ($3::bool        IS NULL OR e.is_published = $3)
+              AND ($4::text        IS NULL OR e.search_tsv @@ search_query($4))
+              AND ($5::timestamptz IS NULL OR e.starts_at >= $5)
+              AND ($6::timestamptz IS NULL OR e.starts_at <  $6)
+              AND ($7::timestamptz IS NULL OR COALESCE(e.ends_at, e.starts_at) >= $7)
+              AND ($8::timestamptz IS NULL OR COALESCE(e.ends_at, e.starts_at) <  $8)
+              AND (SELECT COUNT(*) FROM event_tags et2 JOIN tags t2 ON t2.id = et2.tag_id
+                    WHERE et2.event_id = e.id AND t2.slug = ANY($9)) = cardinality($9::text[])
+            GROUP BY t.id
+            ORDER BY t.category NULLS LAST, t.name
+            "#,
+            f.company_id, f.manager_id, f.published, q,
+            f.from, f.to, f.ends_after, f.ends_before,
+            &f.tags
+        )
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|r| TagFacet { slug: r.slug, name: r.name, category: r.category, count: r.count })
+            .collect();
+
+        let has_more = rows.len() as i64 > limit;
+        let mut items: Vec<EventKeyedRow> = rows;
+        items.truncate(limit as usize);
+        let next = if has_more {
+            items.last().map(|r| EventCursor { key: r.sort_key, id: r.id })
+        } else {
+            None
+        };
+
+        Ok(EventPage {
+            items: items.into_iter().map(EventWithCount::from).collect(),
+            next,
+            total,
+            facets,
+        })
     }
 
     async fn create(&self, row: EventRow) -> RepoResult<EventRow> {
@@ -106,13 +345,16 @@ impl EventRepository for PgEventRepository {
             r#"
             INSERT INTO events
                 (id, company_id, manager_id, title, description, location,
-                 starts_at, ends_at, signup_deadline, capacity, is_published)
-            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)
+                 starts_at, ends_at, signup_deadline, capacity, is_published,
+                 series_id, series_index, publish_at, unpublish_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15)
             RETURNING id, company_id, manager_id, title, description, location,
-                      starts_at, ends_at, signup_deadline, capacity, is_published
+                      starts_at, ends_at, signup_deadline, capacity, is_published,
+                      series_id, series_index, publish_at, unpublish_at
             "#,
             row.id, row.company_id, row.manager_id, row.title, row.description, row.location,
-            row.starts_at, row.ends_at, row.signup_deadline, row.capacity, row.is_published
+            row.starts_at, row.ends_at, row.signup_deadline, row.capacity, row.is_published,
+            row.series_id, row.series_index, row.publish_at, row.unpublish_at
         )
             .fetch_one(&self.pool)
             .await?;
@@ -125,8 +367,11 @@ impl EventRepository for PgEventRepository {
             EventListRow,
             r#"
             SELECT e.id, e.company_id, e.manager_id, e.title, e.description, e.location,
-                   e.starts_at, e.ends_at, e.signup_deadline, e.capacity, e.is_published,
-                   (SELECT COUNT(*)::bigint FROM registrations er WHERE er.event_id = e.id) AS "registered_count?"
+                   e.starts_at, e.ends_at, e.signup_deadline, e.capacity, e.is_published, e.series_id, e.series_index,
+                   e.status AS "status: EventStatus", e.review_reason, e.cancel_reason, e.canceled_at, e.publish_at, e.unpublish_at,
+                   (SELECT COUNT(*)::bigint FROM registrations er WHERE er.event_id = e.id AND er.status = 'registered') AS "registered_count?",
+                   ARRAY(SELECT t.slug FROM event_tags et JOIN tags t ON t.id = et.tag_id
+                          WHERE et.event_id = e.id ORDER BY t.slug) AS "tags!"
             FROM events e
             WHERE e.id = $1
             "#,
@@ -139,50 +384,301 @@ impl EventRepository for PgEventRepository {
     }
 
     async fn update_all(&self, row: EventRow) -> RepoResult<EventWithCount> {
+        let mut tx = self.pool.begin().await?;
+        update_event(&mut tx, &row).await?;
+        enqueue_gcal_sync(&mut tx, &[row.id]).await?;
+
         let r = sqlx::query_as!(
             EventListRow,
             r#"
-            UPDATE events SET
-                title=$2, description=$3, location=$4,
-                starts_at=$5, ends_at=$6, signup_deadline=$7,
-                capacity=$8, is_published=$9, updated_at=now()
-            WHERE id=$1
-            RETURNING id, company_id, manager_id, title, description, location,
-                      starts_at, ends_at, signup_deadline, capacity, is_published,
-                      (SELECT COUNT(*)::bigint FROM registrations er WHERE er.event_id = events.id) AS "registered_count?"
+            SELECT e.id, e.company_id, e.manager_id, e.title, e.description, e.location,
+                   e.starts_at, e.ends_at, e.signup_deadline, e.capacity, e.is_published, e.series_id, e.series_index,
+                   e.status AS "status: EventStatus", e.review_reason, e.cancel_reason, e.canceled_at, e.publish_at, e.unpublish_at,
+                   (SELECT COUNT(*)::bigint FROM registrations er WHERE er.event_id = e.id AND er.status = 'registered') AS "registered_count?",
+                   ARRAY(SELECT t.slug FROM event_tags et JOIN tags t ON t.id = et.tag_id
+                          WHERE et.event_id = e.id ORDER BY t.slug) AS "tags!"
+            FROM events e
+            WHERE e.id = $1
+            "#,
+            row.id
+        )
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(r.into())
+    }
+
+    async fn update_many(&self, rows: Vec<EventRow>) -> RepoResult<()> {
+        let mut tx = self.pool.begin().await?;
+        for row in &rows {
+            update_event(&mut tx, row).await?;
+        }
+        let ids: Vec<Uuid> = rows.iter().map(|r| r.id).collect();
+        enqueue_gcal_sync(&mut tx, &ids).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Набор тегов заменяется целиком; для серии — сразу на всех вхождениях
+    async fn set_tags(&self, event_ids: &[Uuid], tag_ids: &[Uuid]) -> RepoResult<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!("DELETE FROM event_tags WHERE event_id = ANY($1)", event_ids)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO event_tags (event_id, tag_id)
+            SELECT e, t FROM unnest($1::uuid[]) AS e, unnest($2::uuid[]) AS t
             "#,
-            row.id, row.title, row.description, row.location,
-            row.starts_at, row.ends_at, row.signup_deadline,
-            row.capacity, row.is_published
+            event_ids, tag_ids
+        )
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn create_series(&self, series: EventSeriesRow, rows: Vec<EventRow>) -> RepoResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO event_series (id, company_id, manager_id, frequency, until, occurrences, custom_dates)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            series.id, series.company_id, series.manager_id, series.frequency as SeriesFrequency,
+            series.until, series.occurrences, &series.custom_dates
+        )
+            .execute(&mut *tx)
+            .await?;
+
+        for row in rows {
+            sqlx::query!(
+                r#"
+                INSERT INTO events
+                    (id, company_id, manager_id, title, description, location,
+                     starts_at, ends_at, signup_deadline, capacity, is_published,
+                     series_id, series_index, publish_at, unpublish_at)
+                VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15)
+                "#,
+                row.id, row.company_id, row.manager_id, row.title, row.description, row.location,
+                row.starts_at, row.ends_at, row.signup_deadline, row.capacity, row.is_published,
+                row.series_id, row.series_index, row.publish_at, row.unpublish_at
+            )
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_series(&self, id: Uuid) -> RepoResult<EventSeriesRow> {
+        let r = sqlx::query_as!(
+            EventSeriesRow,
+            r#"
+            SELECT id, company_id, manager_id,
+                   frequency AS "frequency: SeriesFrequency",
+                   until, occurrences, custom_dates
+            FROM event_series
+            WHERE id = $1
+            "#,
+            id
         )
             .fetch_optional(&self.pool)
             .await?;
+        r.ok_or(RepoError::NotFound)
+    }
 
-        r.map(EventWithCount::from).ok_or(RepoError::NotFound)
+    async fn list_series_events(&self, series_id: Uuid) -> RepoResult<Vec<EventWithCount>> {
+        let rows = sqlx::query_as!(
+            EventListRow,
+            r#"
+            SELECT e.id, e.company_id, e.manager_id, e.title, e.description, e.location,
+                   e.starts_at, e.ends_at, e.signup_deadline, e.capacity, e.is_published, e.series_id, e.series_index,
+                   e.status AS "status: EventStatus", e.review_reason, e.cancel_reason, e.canceled_at, e.publish_at, e.unpublish_at,
+                   (SELECT COUNT(*)::bigint FROM registrations er WHERE er.event_id = e.id AND er.status = 'registered') AS "registered_count?",
+                   ARRAY(SELECT t.slug FROM event_tags et JOIN tags t ON t.id = et.tag_id
+                          WHERE et.event_id = e.id ORDER BY t.slug) AS "tags!"
+            FROM events e
+            WHERE e.series_id = $1
+            ORDER BY e.starts_at
+            "#,
+            series_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(EventWithCount::from).collect())
     }
 
+    // Копии в календарях собираем до удаления: регистрации уйдут каскадом
+    // Удаляются только черновики без записей; всё остальное отменяют через cancel
     async fn delete(&self, id: Uuid) -> RepoResult<()> {
-        let res = sqlx::query!("DELETE FROM events WHERE id = $1", id)
-            .execute(&self.pool)
+        let mut tx = self.pool.begin().await?;
+        let ev = sqlx::query!(
+            r#"
+            SELECT e.status AS "status: EventStatus",
+                   EXISTS (SELECT 1 FROM registrations r WHERE r.event_id = e.id) AS "has_registrations!"
+              FROM events e
+             WHERE e.id = $1
+               FOR UPDATE
+            "#,
+            id
+        )
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+        if ev.status != EventStatus::Draft || ev.has_registrations {
+            return Err(RepoError::Precondition("only drafts without registrations can be deleted, cancel the event instead".into()));
+        }
+
+        sqlx::query!("DELETE FROM events WHERE id = $1", id)
+            .execute(&mut *tx)
             .await?;
-        if res.rows_affected() == 0 { return Err(RepoError::NotFound); }
+        tx.commit().await?;
         Ok(())
     }
-    async fn set_published(&self, id: Uuid, flag: bool) -> RepoResult<EventWithCount> {
+
+    // Записи остаются как есть: по ним студенты видят отменённое событие в истории.
+    // Всем записанным и ждущим в очереди уходит письмо, копии в календарях синхронизируются
+    async fn cancel(&self, id: Uuid, by: Uuid, reason: &str, now_utc: OffsetDateTime) -> RepoResult<EventWithCount> {
+        let mut tx = self.pool.begin().await?;
+        let ev = sqlx::query!(
+            r#"
+            UPDATE events
+               SET status = 'canceled', canceled_at = $3, canceled_by = $2, cancel_reason = $4, updated_at = $3
+             WHERE id = $1 AND status <> 'canceled'
+            RETURNING title, starts_at, location
+            "#,
+            id, by, now_utc, reason
+        )
+            .fetch_optional(&mut *tx)
+            .await?;
+        let Some(ev) = ev else {
+            let exists = sqlx::query_scalar!("SELECT 1 FROM events WHERE id = $1", id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            return match exists {
+                Some(_) => Err(RepoError::Precondition("event is already canceled".into())),
+                None => Err(RepoError::NotFound),
+            };
+        };
+
+        if ev.starts_at > now_utc {
+            let event = EventInfo { title: ev.title, starts_at: ev.starts_at, location: ev.location };
+            enqueue_mail_to_participants(&mut tx, id, &Mail::EventCanceled { event }).await?;
+        }
+        enqueue_gcal_sync(&mut tx, &[id]).await?;
+
+        let r = sqlx::query_as!(
+            EventListRow,
+            r#"
+            SELECT e.id, e.company_id, e.manager_id, e.title, e.description, e.location,
+                   e.starts_at, e.ends_at, e.signup_deadline, e.capacity, e.is_published, e.series_id, e.series_index,
+                   e.status AS "status: EventStatus", e.review_reason, e.cancel_reason, e.canceled_at, e.publish_at, e.unpublish_at,
+                   (SELECT COUNT(*)::bigint FROM registrations er WHERE er.event_id = e.id AND er.status = 'registered') AS "registered_count?",
+                   ARRAY(SELECT t.slug FROM event_tags et JOIN tags t ON t.id = et.tag_id
+                          WHERE et.event_id = e.id ORDER BY t.slug) AS "tags!"
+            FROM events e
+            WHERE e.id = $1
+            "#,
+            id
+        )
+            .fetch_one(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(r.into())
+    }
+    // Смена статуса самим менеджером (черновик / на модерацию / публикация);
+    // прошлое решение модератора остаётся видно, пока не отправят заново
+    async fn set_status(&self, id: Uuid, status: EventStatus) -> RepoResult<EventWithCount> {
+        let mut tx = self.pool.begin().await?;
         let r = sqlx::query_as!(
             EventListRow,
             r#"
             UPDATE events
-               SET is_published = $2, updated_at = now()
+               SET status        = $2::event_status,
+                   submitted_at  = CASE WHEN $2::event_status = 'pending_review' THEN now() ELSE submitted_at END,
+                   reviewed_by   = CASE WHEN $2::event_status = 'pending_review' THEN NULL ELSE reviewed_by END,
+                   reviewed_at   = CASE WHEN $2::event_status = 'pending_review' THEN NULL ELSE reviewed_at END,
+                   review_reason = CASE WHEN $2::event_status = 'pending_review' THEN NULL ELSE review_reason END,
+                   updated_at    = now()
              WHERE id = $1
             RETURNING id, company_id, manager_id, title, description, location,
-                      starts_at, ends_at, signup_deadline, capacity, is_published,
-                      (SELECT COUNT(*)::bigint FROM registrations er WHERE er.event_id = events.id) AS "registered_count?"
+                      starts_at, ends_at, signup_deadline, capacity, is_published, series_id, series_index,
+                      status AS "status: EventStatus", review_reason, cancel_reason, canceled_at, publish_at, unpublish_at,
+                      (SELECT COUNT(*)::bigint FROM registrations er WHERE er.event_id = events.id AND er.status = 'registered') AS "registered_count?",
+                      ARRAY(SELECT t.slug FROM event_tags et JOIN tags t ON t.id = et.tag_id
+                             WHERE et.event_id = events.id ORDER BY t.slug) AS "tags!"
             "#,
-            id, flag
+            id, status as EventStatus
         )
-            .fetch_optional(&self.pool).await?;
-        r.map(EventWithCount::from).ok_or(RepoError::NotFound)
+            .fetch_optional(&mut *tx).await?
+            .ok_or(RepoError::NotFound)?;
+        enqueue_gcal_sync(&mut tx, &[id]).await?;
+        tx.commit().await?;
+        Ok(r.into())
+    }
+
+    // Решение модератора принимается только по событию, которое ждёт проверки
+    async fn review(&self, id: Uuid, status: EventStatus, reviewer: Uuid, reason: Option<&str>) -> RepoResult<EventWithCount> {
+        let mut tx = self.pool.begin().await?;
+        let r = sqlx::query_as!(
+            EventListRow,
+            r#"
+            UPDATE events
+               SET status = $2, reviewed_by = $3, reviewed_at = now(), review_reason = $4, updated_at = now()
+             WHERE id = $1 AND status = 'pending_review'
+            RETURNING id, company_id, manager_id, title, description, location,
+                      starts_at, ends_at, signup_deadline, capacity, is_published, series_id, series_index,
+                      status AS "status: EventStatus", review_reason, cancel_reason, canceled_at, publish_at, unpublish_at,
+                      (SELECT COUNT(*)::bigint FROM registrations er WHERE er.event_id = events.id AND er.status = 'registered') AS "registered_count?",
+                      ARRAY(SELECT t.slug FROM event_tags et JOIN tags t ON t.id = et.tag_id
+                             WHERE et.event_id = events.id ORDER BY t.slug) AS "tags!"
+            "#,
+            id, status as EventStatus, reviewer, reason
+        )
+            .fetch_optional(&mut *tx).await?;
+        match r {
+            Some(r) => {
+                enqueue_gcal_sync(&mut tx, &[id]).await?;
+                tx.commit().await?;
+                Ok(r.into())
+            }
+            None => {
+                tx.rollback().await?;
+                self.get(id).await?;
+                Err(RepoError::Precondition("event is not pending review".into()))
+            }
+        }
+    }
+
+    async fn review_queue(&self) -> RepoResult<Vec<EventWithCount>> {
+        let rows = sqlx::query_as!(
+            EventListRow,
+            r#"
+            SELECT e.id, e.company_id, e.manager_id, e.title, e.description, e.location,
+                   e.starts_at, e.ends_at, e.signup_deadline, e.capacity, e.is_published, e.series_id, e.series_index,
+                   e.status AS "status: EventStatus", e.review_reason, e.cancel_reason, e.canceled_at, e.publish_at, e.unpublish_at,
+                   (SELECT COUNT(*)::bigint FROM registrations er WHERE er.event_id = e.id AND er.status = 'registered') AS "registered_count?",
+                   ARRAY(SELECT t.slug FROM event_tags et JOIN tags t ON t.id = et.tag_id
+                          WHERE et.event_id = e.id ORDER BY t.slug) AS "tags!"
+            FROM events e
+            WHERE e.status = 'pending_review'
+            ORDER BY e.submitted_at, e.id
+            "#
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(EventWithCount::from).collect())
+    }
+
+    async fn requires_review(&self, company_id: Uuid) -> RepoResult<bool> {
+        sqlx::query_scalar!("SELECT requires_event_review FROM companies WHERE id = $1", company_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(RepoError::NotFound)
     }
 
     async fn set_deadline(&self, id: Uuid, deadline: Option<OffsetDateTime>) -> RepoResult<EventWithCount> {
@@ -193,8 +689,11 @@ impl EventRepository for PgEventRepository {
                SET signup_deadline = $2, updated_at = now()
              WHERE id = $1
             RETURNING id, company_id, manager_id, title, description, location,
-                      starts_at, ends_at, signup_deadline, capacity, is_published,
-                      (SELECT COUNT(*)::bigint FROM registrations er WHERE er.event_id = events.id) AS "registered_count?"
+                      starts_at, ends_at, signup_deadline, capacity, is_published, series_id, series_index,
+                      status AS "status: EventStatus", review_reason, cancel_reason, canceled_at, publish_at, unpublish_at,
+                      (SELECT COUNT(*)::bigint FROM registrations er WHERE er.event_id = events.id AND er.status = 'registered') AS "registered_count?",
+                      ARRAY(SELECT t.slug FROM event_tags et JOIN tags t ON t.id = et.tag_id
+                             WHERE et.event_id = events.id ORDER BY t.slug) AS "tags!"
             "#,
             id, deadline
         )
@@ -212,11 +711,13 @@ impl EventRepository for PgEventRepository {
             u.name  AS student_name,
             u.email AS student_email,
             r.registered_at,
-            r.gcal_event_id
+            r.gcal_event_id,
+            r.status AS "status: RegistrationStatus",
+            r.checked_in_at
         FROM registrations r
         JOIN users u ON u.id = r.student_id
         WHERE r.event_id = $1
-          AND r.status = 'registered'
+          AND r.status IN ('registered', 'attended', 'no_show')
         ORDER BY r.registered_at DESC
         "#,
         event_id
@@ -230,7 +731,7 @@ impl EventRepository for PgEventRepository {
 
     async fn count_registrations(&self, event_id: Uuid) -> RepoResult<i64> {
         let n = sqlx::query_scalar!(
-            r#"SELECT COUNT(*)::bigint FROM registrations WHERE event_id = $1"#,
+            r#"SELECT COUNT(*)::bigint FROM registrations WHERE event_id = $1 AND status = 'registered'"#,
             event_id
         )
             .fetch_one(&self.pool)
@@ -239,54 +740,485 @@ impl EventRepository for PgEventRepository {
         Ok(n)
     }
 
-    async fn register(&self, event_id: Uuid, student_id: Uuid, now_utc: OffsetDateTime) -> RepoResult<()> {
-        let _ = sqlx::query!(
+    async fn registration_state(&self, event_id: Uuid, student_id: Uuid) -> RepoResult<Option<RegistrationState>> {
+        let mut conn = self.pool.acquire().await?;
+        fetch_registration_state(&mut conn, event_id, student_id).await
+    }
+
+    // по просмотрам решаем, кому напомнить о закрытии записи
+    async fn record_view(&self, event_id: Uuid, student_id: Uuid, now_utc: OffsetDateTime) -> RepoResult<()> {
+        sqlx::query!(
             r#"
-            INSERT INTO registrations (event_id, student_id, registered_at)
-            VALUES ($1, $2, $3)
-            ON CONFLICT (event_id, student_id) DO NOTHING
+            INSERT INTO event_views (event_id, student_id, first_viewed_at, last_viewed_at)
+            VALUES ($1, $2, $3, $3)
+            ON CONFLICT (event_id, student_id) DO UPDATE SET last_viewed_at = EXCLUDED.last_viewed_at
             "#,
             event_id, student_id, now_utc
         )
-            .execute(&self.pool).await?;
+            .execute(&self.pool)
+            .await?;
         Ok(())
     }
 
-    async fn cancel_registration(&self, event_id: Uuid, student_id: Uuid, _now_utc: OffsetDateTime) -> RepoResult<()> {
-        let res = sqlx::query!(
-            r#"DELETE FROM registrations WHERE event_id = $1 AND student_id = $2"#,
+    async fn register(&self, event_id: Uuid, student_id: Uuid, now_utc: OffsetDateTime) -> RepoResult<RegistrationState> {
+        let mut tx = self.pool.begin().await?;
+
+        // блокируем событие: решение «место или очередь» принимается под этим локом
+        let ev = sqlx::query!(
+            r#"SELECT capacity, title, starts_at, location FROM events WHERE id = $1 FOR UPDATE"#,
+            event_id
+        )
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+
+        if let Some(state) = fetch_registration_state(&mut tx, event_id, student_id).await? {
+            if state.status != RegistrationStatus::Canceled {
+                tx.commit().await?;
+                return Ok(state);
+            }
+        }
+
+        let used = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*)::bigint AS "count!"
+            FROM registrations
+            WHERE event_id = $1 AND status = 'registered'
+            "#,
+            event_id
+        )
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let (status, waitlisted_at) = match ev.capacity {
+            Some(cap) if used >= i64::from(cap) => (RegistrationStatus::Waitlisted, Some(now_utc)),
+            _ => (RegistrationStatus::Registered, None),
+        };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO registrations (event_id, student_id, status, registered_at, waitlisted_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (event_id, student_id) DO UPDATE
+              SET status        = EXCLUDED.status,
+                  registered_at = EXCLUDED.registered_at,
+                  waitlisted_at = EXCLUDED.waitlisted_at,
+                  canceled_at   = NULL
+            "#,
+            event_id, student_id, status as RegistrationStatus, now_utc, waitlisted_at
+        )
+            .execute(&mut *tx)
+            .await?;
+
+        let state = fetch_registration_state(&mut tx, event_id, student_id)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+
+        let event = EventInfo { title: ev.title, starts_at: ev.starts_at, location: ev.location };
+        let waitlisted = status == RegistrationStatus::Waitlisted;
+        enqueue_mail_to_user(&mut tx, student_id, &Mail::RegistrationConfirmed { event, waitlisted }).await?;
+        enqueue_gcal_sync(&mut tx, &[event_id]).await?;
+        tx.commit().await?;
+        Ok(state)
+    }
+
+    async fn cancel_registration(&self, event_id: Uuid, student_id: Uuid, now_utc: OffsetDateTime) -> RepoResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let ev = sqlx::query!(
+            r#"SELECT title, starts_at, location, status AS "status: EventStatus" FROM events WHERE id = $1 FOR UPDATE"#,
+            event_id
+        )
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+
+        let prev = sqlx::query_scalar!(
+            r#"
+            SELECT status AS "status: RegistrationStatus"
+            FROM registrations
+            WHERE event_id = $1 AND student_id = $2
+              AND status IN ('registered', 'waitlisted')
+            FOR UPDATE
+            "#,
             event_id, student_id
         )
-            .execute(&self.pool).await?;
-        if res.rows_affected() == 0 { return Err(RepoError::NotFound); }
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+
+        sqlx::query!(
+            r#"
+            UPDATE registrations
+               SET status = 'canceled', canceled_at = $3, waitlisted_at = NULL
+             WHERE event_id = $1 AND student_id = $2
+            "#,
+            event_id, student_id, now_utc
+        )
+            .execute(&mut *tx)
+            .await?;
+
+        let event = EventInfo { title: ev.title, starts_at: ev.starts_at, location: ev.location };
+        // у отменённого события очередь не двигается: места там больше никому не нужны
+        if prev == RegistrationStatus::Registered && ev.status != EventStatus::Canceled {
+            let promoted = promote_waitlist(&mut tx, event_id, now_utc).await?;
+            notify_promoted(&mut tx, &promoted, &event).await?;
+        }
+        enqueue_mail_to_user(&mut tx, student_id, &Mail::RegistrationCanceled { event }).await?;
+        enqueue_gcal_sync(&mut tx, &[event_id]).await?;
+        tx.commit().await?;
         Ok(())
     }
-    async fn list_registrations_by_student(
+
+    async fn list_registrations_by_student(&self, student_id: Uuid) -> RepoResult<Vec<StudentRegistration>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+              r.event_id,
+              r.status AS "status: RegistrationStatus",
+              CASE WHEN r.status = 'waitlisted' THEN (
+                SELECT COUNT(*)::bigint
+                  FROM registrations w
+                 WHERE w.event_id = r.event_id
+                   AND w.status = 'waitlisted'
+                   AND (w.waitlisted_at, w.student_id) <= (r.waitlisted_at, r.student_id)
+              ) END AS waitlist_position
+            FROM registrations r
+            WHERE r.student_id = $1
+              AND r.status <> 'canceled'
+            ORDER BY r.registered_at DESC
+            "#,
+            student_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| StudentRegistration {
+                event_id: r.event_id,
+                state: RegistrationState { status: r.status, waitlist_position: r.waitlist_position },
+            })
+            .collect())
+    }
+
+    async fn check_in(
         &self,
         event_id: Uuid,
-    ) -> RepoResult<Vec<RegistrationRow>> {
-        let rows = sqlx::query_as!(
+        student_ids: &[Uuid],
+        checked_by: Uuid,
+        now_utc: OffsetDateTime,
+    ) -> RepoResult<Vec<Uuid>> {
+        // no_show тоже можно отметить задним числом — опоздавших вносят после окончания
+        let ids = sqlx::query_scalar!(
+            r#"
+            UPDATE registrations
+               SET status        = 'attended',
+                   checked_in_at = COALESCE(checked_in_at, $3),
+                   checked_in_by = COALESCE(checked_in_by, $4)
+             WHERE event_id = $1
+               AND student_id = ANY($2)
+               AND status IN ('registered', 'attended', 'no_show')
+            RETURNING student_id
+            "#,
+            event_id, student_ids, now_utc, checked_by
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(ids)
+    }
+
+    async fn mark_no_shows(&self, event_id: Option<Uuid>, now_utc: OffsetDateTime) -> RepoResult<u64> {
+        // у события без ends_at окончанием считается starts_at
+        let res = sqlx::query!(
+            r#"
+            UPDATE registrations r
+               SET status = 'no_show'
+              FROM events e
+             WHERE e.id = r.event_id
+               AND ($1::uuid IS NULL OR r.event_id = $1)
+               AND r.status = 'registered'
+               AND e.status <> 'canceled'
+               AND COALESCE(e.ends_at, e.starts_at) < $2
+            "#,
+            event_id, now_utc
+        )
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    // Черновик по расписанию публикуется или, если компании нужна модерация,
+    // уходит на проверку; остальные статусы не меняются, но отметка ставится.
+    // Событие, которое уже пора снимать, оставляем unpublish_scheduled.
+    // Менеджер должен быть подтверждён — иначе событие не даст изменить events_manager_guard
+    async fn publish_scheduled(&self, now_utc: OffsetDateTime) -> RepoResult<u64> {
+        let mut tx = self.pool.begin().await?;
+        let ids = sqlx::query_scalar!(
+            r#"
+            UPDATE events e
+               SET status = CASE
+                                WHEN e.status <> 'draft' THEN e.status
+                                WHEN c.requires_event_review THEN 'pending_review'
+                                ELSE 'published'
+                            END::event_status,
+                   submitted_at = CASE WHEN e.status = 'draft' AND c.requires_event_review
+                                       THEN $1 ELSE e.submitted_at END,
+                   auto_published_at = $1,
+                   updated_at = now()
+              FROM companies c
+             WHERE c.id = e.company_id
+               AND e.publish_at <= $1
+               AND e.auto_published_at IS NULL
+               AND (e.unpublish_at IS NULL OR e.unpublish_at > $1)
+               AND EXISTS (SELECT 1 FROM managers m WHERE m.user_id = e.manager_id AND m.status = 'confirmed')
+            RETURNING e.id
+            "#,
+            now_utc
+        )
+            .fetch_all(&mut *tx)
+            .await?;
+        enqueue_gcal_sync(&mut tx, &ids).await?;
+        tx.commit().await?;
+        Ok(ids.len() as u64)
+    }
+
+    // Снятие возвращает в черновики и опубликованное, и ждущее модерации
+    async fn unpublish_scheduled(&self, now_utc: OffsetDateTime) -> RepoResult<u64> {
+        let mut tx = self.pool.begin().await?;
+        let ids = sqlx::query_scalar!(
+            r#"
+            UPDATE events e
+               SET status = CASE WHEN e.status IN ('published', 'pending_review')
+                                 THEN 'draft' ELSE e.status END::event_status,
+                   auto_unpublished_at = $1,
+                   updated_at = now()
+             WHERE e.unpublish_at <= $1
+               AND e.auto_unpublished_at IS NULL
+               AND EXISTS (SELECT 1 FROM managers m WHERE m.user_id = e.manager_id AND m.status = 'confirmed')
+            RETURNING e.id
+            "#,
+            now_utc
+        )
+            .fetch_all(&mut *tx)
+            .await?;
+        enqueue_gcal_sync(&mut tx, &ids).await?;
+        tx.commit().await?;
+        Ok(ids.len() as u64)
+    }
+
+    async fn redeem_ticket(
+        &self,
+        event_id: Uuid,
+        student_id: Uuid,
+        checked_by: Uuid,
+        now_utc: OffsetDateTime,
+    ) -> RepoResult<Option<RegistrationRow>> {
+        // в отличие от check_in, уже отмеченный билет не проходит повторно
+        let row = sqlx::query_as!(
             RegistrationRow,
             r#"
+            WITH upd AS (
+              UPDATE registrations
+                 SET status = 'attended', checked_in_at = $3, checked_in_by = $4
+               WHERE event_id = $1 AND student_id = $2
+                 AND status IN ('registered', 'no_show')
+              RETURNING event_id, student_id, registered_at, gcal_event_id, status, checked_in_at
+            )
             SELECT
-              r.event_id,
-              r.student_id,
+              upd.event_id,
+              upd.student_id,
               u.name  AS student_name,
               u.email AS student_email,
-              r.registered_at,
-              NULL::text AS gcal_event_id
-            FROM registrations r
-            JOIN users u ON u.id = r.student_id
-            WHERE r.event_id = $1
-            ORDER BY r.registered_at DESC
+              upd.registered_at,
+              upd.gcal_event_id,
+              upd.status AS "status: RegistrationStatus",
+              upd.checked_in_at
+            FROM upd
+            JOIN users u ON u.id = upd.student_id
             "#,
-            event_id
+            event_id, student_id, now_utc, checked_by
         )
-            .fetch_all(&self.pool)
-            .await
-            .map_err(RepoError::Db)?;
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row)
+    }
+}
 
-        Ok(rows)
+async fn update_event(conn: &mut PgConnection, row: &EventRow) -> RepoResult<()> {
+    // old — снимок до изменения: по нему видно, сдвинулись ли время или место
+    let updated = sqlx::query_scalar!(
+        r#"
+        WITH old AS (SELECT starts_at, location FROM events WHERE id = $1 FOR UPDATE)
+        UPDATE events SET
+            title=$2, description=$3, location=$4,
+            starts_at=$5, ends_at=$6, signup_deadline=$7,
+            capacity=$8, is_published=$9,
+            publish_at=$10, unpublish_at=$11,
+            -- новое время в расписании воркер должен отработать заново
+            auto_published_at   = CASE WHEN publish_at   IS DISTINCT FROM $10 THEN NULL ELSE auto_published_at END,
+            auto_unpublished_at = CASE WHEN unpublish_at IS DISTINCT FROM $11 THEN NULL ELSE auto_unpublished_at END,
+            updated_at=now()
+        FROM old
+        WHERE id=$1
+        RETURNING (old.starts_at <> $5 OR old.location IS DISTINCT FROM $4) AS "rescheduled!"
+        "#,
+        row.id, row.title, row.description, row.location,
+        row.starts_at, row.ends_at, row.signup_deadline,
+        row.capacity, row.is_published, row.publish_at, row.unpublish_at
+    )
+        .fetch_optional(&mut *conn)
+        .await?;
+    let Some(rescheduled) = updated else { return Err(RepoError::NotFound) };
+    let event = EventInfo { title: row.title.clone(), starts_at: row.starts_at, location: row.location.clone() };
+    if rescheduled {
+        enqueue_mail_to_participants(conn, row.id, &Mail::EventChanged { event: event.clone() }).await?;
+    }
+
+    // если вместимость выросла — освободившиеся места уходят очереди
+    let promoted = promote_waitlist(conn, row.id, OffsetDateTime::now_utc()).await?;
+    notify_promoted(conn, &promoted, &event).await?;
+    Ok(())
+}
+
+// Письма тем, кого promote_waitlist перевёл из очереди в registered; в той же транзакции
+async fn notify_promoted(conn: &mut PgConnection, promoted: &[Uuid], event: &EventInfo) -> RepoResult<()> {
+    for &student_id in promoted {
+        let mail = Mail::RegistrationConfirmed { event: event.clone(), waitlisted: false };
+        enqueue_mail_to_user(conn, student_id, &mail).await?;
     }
+    Ok(())
+}
+
+async fn fetch_registration_state(
+    conn: &mut PgConnection,
+    event_id: Uuid,
+    student_id: Uuid,
+) -> RepoResult<Option<RegistrationState>> {
+    let row = sqlx::query!(
+        r#"
+        SELECT
+          r.status AS "status: RegistrationStatus",
+          CASE WHEN r.status = 'waitlisted' THEN (
+            SELECT COUNT(*)::bigint
+              FROM registrations w
+             WHERE w.event_id = r.event_id
+               AND w.status = 'waitlisted'
+               AND (w.waitlisted_at, w.student_id) <= (r.waitlisted_at, r.student_id)
+          ) END AS waitlist_position
+        FROM registrations r
+        WHERE r.event_id = $1 AND r.student_id = $2
+        "#,
+        event_id, student_id
+    )
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row.map(|r| RegistrationState { status: r.status, waitlist_position: r.waitlist_position }))
+}
+
+// Переводит голову очереди в registered, пока есть свободные места.
+// Вызывать внутри транзакции, держащей лок на строке события.
+// Отменённое событие никого из очереди не переводит.
+async fn promote_waitlist(
+    conn: &mut PgConnection,
+    event_id: Uuid,
+    now_utc: OffsetDateTime,
+) -> RepoResult<Vec<Uuid>> {
+    // free: NULL — вместимость не ограничена, забираем всю очередь
+    let ev = sqlx::query!(
+        r#"
+        SELECT e.status AS "status: EventStatus",
+               e.capacity::bigint - (
+                 SELECT COUNT(*)::bigint FROM registrations r
+                  WHERE r.event_id = e.id AND r.status = 'registered'
+               ) AS free
+        FROM events e
+        WHERE e.id = $1
+        "#,
+        event_id
+    )
+        .fetch_one(&mut *conn)
+        .await?;
+    let free = ev.free;
 
-}
\ No newline at end of file
+    if ev.status == EventStatus::Canceled || matches!(free, Some(n) if n <= 0) {
+        return Ok(Vec::new());
+    }
+
+    let promoted = sqlx::query_scalar!(
+        r#"
+        WITH head AS (
+          SELECT student_id
+            FROM registrations
+           WHERE event_id = $1 AND status = 'waitlisted'
+           ORDER BY waitlisted_at, student_id
+           LIMIT $2
+           FOR UPDATE
+        )
+        UPDATE registrations r
+           SET status = 'registered', registered_at = $3, waitlisted_at = NULL
+          FROM head
+         WHERE r.event_id = $1 AND r.student_id = head.student_id
+        RETURNING r.student_id
+        "#,
+        event_id, free, now_utc
+    )
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(promoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Duration;
+
+    use super::*;
+    use crate::infra::repositories::user_repo::{PgUserRepository, UserRepository};
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn canceled_event_does_not_promote_waitlist(pool: Pool<Postgres>) {
+        let users = PgUserRepository::new(pool.clone());
+        let repo = PgEventRepository::new(pool.clone());
+        let company = Uuid::new_v4();
+        sqlx::query!("INSERT INTO companies (id, name, status) VALUES ($1, 'ТГУ', 'active')", company)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let manager = users.create_manager("Менеджер", "m@test.io", "x", company).await.unwrap().id;
+        sqlx::query!("UPDATE managers SET status = 'confirmed' WHERE user_id = $1", manager)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let a = users.create_student("Аня", "a@test.io", "x").await.unwrap().id;
+        let b = users.create_student("Боря", "b@test.io", "x").await.unwrap().id;
+
+        let now = OffsetDateTime::now_utc();
+        let event = repo.create(EventRow {
+            id: Uuid::new_v4(),
+            company_id: company,
+            manager_id: manager,
+            title: "Лекция".into(),
+            description: None,
+            location: None,
+            starts_at: now + Duration::days(1),
+            ends_at: None,
+            signup_deadline: None,
+            capacity: Some(1),
+            is_published: true,
+            series_id: None,
+            series_index: None,
+            publish_at: None,
+            unpublish_at: None,
+        }).await.unwrap().id;
+        assert_eq!(repo.register(event, a, now).await.unwrap().status, RegistrationStatus::Registered);
+        assert_eq!(repo.register(event, b, now).await.unwrap().status, RegistrationStatus::Waitlisted);
+
+        repo.cancel(event, manager, "перенос", now).await.unwrap();
+        repo.cancel_registration(event, a, now).await.unwrap();
+
+        let b_state = repo.registration_state(event, b).await.unwrap().unwrap();
+        assert_eq!(b_state.status, RegistrationStatus::Waitlisted);
+    }
+}
@@ -0,0 +1,197 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::infra::errors::{RepoError, RepoResult};
+use crate::infra::google::calendar::CalendarEventData;
+use crate::infra::repositories::job_repo::enqueue_gcal_sync;
+
+// Регистрация, у которой есть (или должна быть) копия в Google Calendar
+#[derive(Debug, Clone)]
+pub struct GcalSyncTarget {
+    pub student_id: Uuid,
+    pub refresh_token: Option<String>,
+    pub gcal_event_id: Option<String>,
+    // копия нужна: студент записан и событие опубликовано
+    pub active: bool,
+}
+
+#[async_trait]
+pub trait GoogleAccountRepository {
+    async fn save_oauth_state(&self, state: &str, user_id: Uuid, code_verifier: &str, expires_at: OffsetDateTime) -> RepoResult<()>;
+    async fn take_oauth_state(&self, state: &str, now: OffsetDateTime) -> RepoResult<(Uuid, String)>;
+
+    async fn upsert_account(&self, user_id: Uuid, refresh_token: &str, scope: Option<&str>, now: OffsetDateTime) -> RepoResult<()>;
+    async fn delete_account(&self, user_id: Uuid) -> RepoResult<()>;
+    async fn refresh_token(&self, user_id: Uuid) -> RepoResult<Option<String>>;
+
+    async fn event_data(&self, event_id: Uuid) -> RepoResult<CalendarEventData>;
+    async fn sync_targets(&self, event_id: Uuid) -> RepoResult<Vec<GcalSyncTarget>>;
+    async fn set_gcal_event_id(&self, event_id: Uuid, student_id: Uuid, gcal_event_id: Option<&str>) -> RepoResult<()>;
+}
+
+#[derive(Clone)]
+pub struct PgGoogleAccountRepository { pool: Pool<Postgres> }
+impl PgGoogleAccountRepository { pub fn new(pool: Pool<Postgres>) -> Self { Self { pool } } }
+
+#[async_trait]
+impl GoogleAccountRepository for PgGoogleAccountRepository {
+    async fn save_oauth_state(&self, state: &str, user_id: Uuid, code_verifier: &str, expires_at: OffsetDateTime) -> RepoResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO google_oauth_states (state, user_id, code_verifier, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            state, user_id, code_verifier, expires_at
+        )
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // state одноразовый: удаляем при любом исходе, заодно чистим просроченные
+    async fn take_oauth_state(&self, state: &str, now: OffsetDateTime) -> RepoResult<(Uuid, String)> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query!(
+            r#"
+            DELETE FROM google_oauth_states
+            WHERE state = $1
+            RETURNING user_id, code_verifier, expires_at
+            "#,
+            state
+        )
+            .fetch_optional(&mut *tx)
+            .await?;
+        sqlx::query!("DELETE FROM google_oauth_states WHERE expires_at <= $1", now)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        match row {
+            Some(r) if r.expires_at > now => Ok((r.user_id, r.code_verifier)),
+            _ => Err(RepoError::NotFound),
+        }
+    }
+
+    // предстоящие записи, сделанные до подключения, тоже уходят в календарь
+    async fn upsert_account(&self, user_id: Uuid, refresh_token: &str, scope: Option<&str>, now: OffsetDateTime) -> RepoResult<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO google_accounts (user_id, refresh_token, scope)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id) DO UPDATE
+                SET refresh_token = EXCLUDED.refresh_token,
+                    scope         = EXCLUDED.scope
+            "#,
+            user_id, refresh_token, scope
+        )
+            .execute(&mut *tx)
+            .await?;
+
+        let upcoming = sqlx::query_scalar!(
+            r#"
+            SELECT r.event_id
+            FROM registrations r
+            JOIN events e ON e.id = r.event_id
+            WHERE r.student_id = $1
+              AND r.status = 'registered'
+              AND e.starts_at > $2
+            "#,
+            user_id, now
+        )
+            .fetch_all(&mut *tx)
+            .await?;
+        enqueue_gcal_sync(&mut tx, &upcoming).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // без аккаунта управлять копиями уже нечем — забываем их id
+    async fn delete_account(&self, user_id: Uuid) -> RepoResult<()> {
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query!("DELETE FROM google_accounts WHERE user_id = $1", user_id)
+            .execute(&mut *tx)
+            .await?;
+        if res.rows_affected() == 0 {
+            tx.rollback().await.ok();
+            return Err(RepoError::NotFound);
+        }
+        sqlx::query!(
+            "UPDATE registrations SET gcal_event_id = NULL WHERE student_id = $1 AND gcal_event_id IS NOT NULL",
+            user_id
+        )
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn refresh_token(&self, user_id: Uuid) -> RepoResult<Option<String>> {
+        let token = sqlx::query_scalar!("SELECT refresh_token FROM google_accounts WHERE user_id = $1", user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    async fn event_data(&self, event_id: Uuid) -> RepoResult<CalendarEventData> {
+        let r = sqlx::query!(
+            r#"
+            SELECT id, title, description, location, starts_at, ends_at
+            FROM events
+            WHERE id = $1
+            "#,
+            event_id
+        )
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+        Ok(CalendarEventData {
+            event_id: r.id,
+            title: r.title,
+            description: r.description,
+            location: r.location,
+            starts_at: r.starts_at,
+            ends_at: r.ends_at,
+        })
+    }
+
+    async fn sync_targets(&self, event_id: Uuid) -> RepoResult<Vec<GcalSyncTarget>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT r.student_id,
+                   g.refresh_token AS "refresh_token?",
+                   r.gcal_event_id,
+                   (e.is_published AND r.status IN ('registered', 'attended', 'no_show')) AS "active!"
+            FROM registrations r
+            JOIN events e ON e.id = r.event_id
+            LEFT JOIN google_accounts g ON g.user_id = r.student_id
+            WHERE r.event_id = $1
+              AND (g.user_id IS NOT NULL OR r.gcal_event_id IS NOT NULL)
+            "#,
+            event_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|r| GcalSyncTarget {
+                student_id: r.student_id,
+                refresh_token: r.refresh_token,
+                gcal_event_id: r.gcal_event_id,
+                active: r.active,
+            })
+            .collect())
+    }
+
+    async fn set_gcal_event_id(&self, event_id: Uuid, student_id: Uuid, gcal_event_id: Option<&str>) -> RepoResult<()> {
+        sqlx::query!(
+            "UPDATE registrations SET gcal_event_id = $3 WHERE event_id = $1 AND student_id = $2",
+            event_id, student_id, gcal_event_id
+        )
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::infra::errors::RepoResult;
+
+// Область действия ключа: один и тот же Idempotency-Key у разных пользователей
+// или на разных эндпоинтах — разные ключи
+#[derive(Debug, Clone)]
+pub struct IdempotencyKey {
+    pub key: String,
+    pub user_id: Option<Uuid>,
+    pub method: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdempotencyClaim {
+    // ключ новый (или брошенный упавшим запросом) — выполняем
+    Started,
+    // исходный запрос ещё выполняется
+    InFlight,
+    // тот же ключ, но другое тело запроса
+    Mismatch,
+    Replay { status: i32, body: Option<serde_json::Value> },
+}
+
+#[async_trait]
+pub trait IdempotencyRepository {
+    async fn claim(&self, k: &IdempotencyKey, request_hash: &[u8], now: OffsetDateTime, lock_until: OffsetDateTime) -> RepoResult<IdempotencyClaim>;
+    async fn complete(&self, k: &IdempotencyKey, status: i32, body: Option<serde_json::Value>, now: OffsetDateTime) -> RepoResult<()>;
+    async fn release(&self, k: &IdempotencyKey) -> RepoResult<()>;
+    async fn purge(&self, before: OffsetDateTime) -> RepoResult<u64>;
+}
+
+#[derive(Clone)]
+pub struct PgIdempotencyRepository { pool: Pool<Postgres> }
+impl PgIdempotencyRepository { pub fn new(pool: Pool<Postgres>) -> Self { Self { pool } } }
+
+#[async_trait]
+impl IdempotencyRepository for PgIdempotencyRepository {
+    // Вставка занимает ключ атомарно. Существующую строку перехватываем, только если
+    // её запрос так и не завершился (истёк locked_until) и тело то же самое
+    async fn claim(&self, k: &IdempotencyKey, request_hash: &[u8], now: OffsetDateTime, lock_until: OffsetDateTime) -> RepoResult<IdempotencyClaim> {
+        let started = sqlx::query_scalar!(
+            r#"
+            INSERT INTO idempotency_keys (key, user_id, method, path, request_hash, locked_until, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (key, (COALESCE(user_id, '00000000-0000-0000-0000-000000000000'::uuid)), method, path)
+            DO UPDATE SET locked_until = EXCLUDED.locked_until
+             WHERE idempotency_keys.processed_at IS NULL
+               AND idempotency_keys.locked_until < $7
+               AND idempotency_keys.request_hash = EXCLUDED.request_hash
+            RETURNING id
+            "#,
+            k.key, k.user_id, k.method, k.path, request_hash, lock_until, now
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+        if started.is_some() {
+            return Ok(IdempotencyClaim::Started);
+        }
+
+        let existing = sqlx::query!(
+            r#"
+            SELECT request_hash, response_status, response_body, processed_at
+              FROM idempotency_keys
+             WHERE key = $1 AND user_id IS NOT DISTINCT FROM $2 AND method = $3 AND path = $4
+            "#,
+            k.key, k.user_id, k.method, k.path
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+        // строку успели удалить между запросами — пусть клиент повторит
+        let Some(r) = existing else { return Ok(IdempotencyClaim::InFlight) };
+        Ok(match (r.request_hash.as_deref() == Some(request_hash), r.processed_at, r.response_status) {
+            (false, _, _) => IdempotencyClaim::Mismatch,
+            (true, Some(_), Some(status)) => IdempotencyClaim::Replay { status, body: r.response_body },
+            (true, _, _) => IdempotencyClaim::InFlight,
+        })
+    }
+
+    async fn complete(&self, k: &IdempotencyKey, status: i32, body: Option<serde_json::Value>, now: OffsetDateTime) -> RepoResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE idempotency_keys
+               SET response_status = $5, response_body = $6, processed_at = $7, locked_until = NULL
+             WHERE key = $1 AND user_id IS NOT DISTINCT FROM $2 AND method = $3 AND path = $4
+            "#,
+            k.key, k.user_id, k.method, k.path, status, body, now
+        )
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // ответ не сохраняем (5xx, не-JSON): ключ освобождается, повтор выполнится заново
+    async fn release(&self, k: &IdempotencyKey) -> RepoResult<()> {
+        sqlx::query!(
+            r#"
+            DELETE FROM idempotency_keys
+             WHERE key = $1 AND user_id IS NOT DISTINCT FROM $2 AND method = $3 AND path = $4
+               AND processed_at IS NULL
+            "#,
+            k.key, k.user_id, k.method, k.path
+        )
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn purge(&self, before: OffsetDateTime) -> RepoResult<u64> {
+        let res = sqlx::query!(
+            r#"
+            DELETE FROM idempotency_keys
+             WHERE created_at < $1 AND (processed_at IS NOT NULL OR locked_until < $1)
+            "#,
+            before
+        )
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+}
@@ -0,0 +1,267 @@
+use async_trait::async_trait;
+use serde::Serialize;
+use sqlx::{PgConnection, Pool, Postgres};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::infra::errors::{is_unique_violation, RepoError, RepoResult};
+use crate::infra::jobs::kinds;
+use crate::infra::mail::templates::Mail;
+
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[sqlx(type_name = "job_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    // попытки кончились; ждёт разбора и ручного перезапуска
+    Dead,
+}
+
+#[derive(Debug, Clone)]
+pub struct JobRow {
+    pub id: Uuid,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub status: JobStatus,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: OffsetDateTime,
+    pub last_error: Option<String>,
+    pub finished_at: Option<OffsetDateTime>,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+#[async_trait]
+pub trait JobRepository {
+    async fn claim(&self, now: OffsetDateTime, lease_until: OffsetDateTime, limit: i64) -> RepoResult<Vec<JobRow>>;
+    async fn complete(&self, id: Uuid, now: OffsetDateTime) -> RepoResult<()>;
+    async fn fail(&self, id: Uuid, error: &str, retry_at: Option<OffsetDateTime>, now: OffsetDateTime) -> RepoResult<()>;
+    async fn list(&self, status: Option<JobStatus>, kind: Option<&str>, limit: i64) -> RepoResult<Vec<JobRow>>;
+    async fn retry(&self, id: Uuid, now: OffsetDateTime) -> RepoResult<JobRow>;
+    async fn purge_done(&self, before: OffsetDateTime) -> RepoResult<u64>;
+}
+
+#[derive(Clone)]
+pub struct PgJobRepository { pool: Pool<Postgres> }
+impl PgJobRepository { pub fn new(pool: Pool<Postgres>) -> Self { Self { pool } } }
+
+#[async_trait]
+impl JobRepository for PgJobRepository {
+    // Забирает созревшие задачи и задачи с истёкшей арендой (воркер упал посреди работы).
+    // SKIP LOCKED даёт нескольким экземплярам разбирать очередь, не мешая друг другу
+    async fn claim(&self, now: OffsetDateTime, lease_until: OffsetDateTime, limit: i64) -> RepoResult<Vec<JobRow>> {
+        let rows = sqlx::query_as!(
+            JobRow,
+            r#"
+            WITH due AS (
+              SELECT id
+                FROM jobs
+               WHERE (status = 'queued' AND run_at <= $1)
+                  OR (status = 'running' AND locked_until <= $1)
+               ORDER BY run_at
+               LIMIT $3
+               FOR UPDATE SKIP LOCKED
+            )
+            UPDATE jobs j
+               SET status = 'running',
+                   attempts = j.attempts + 1,
+                   locked_until = $2
+              FROM due
+             WHERE j.id = due.id
+            RETURNING j.id, j.kind, j.payload, j.status AS "status: JobStatus",
+                      j.attempts, j.max_attempts, j.run_at, j.last_error,
+                      j.finished_at, j.created_at, j.updated_at
+            "#,
+            now, lease_until, limit
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows)
+    }
+
+    async fn complete(&self, id: Uuid, now: OffsetDateTime) -> RepoResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE jobs
+               SET status = 'done', locked_until = NULL, finished_at = $2
+             WHERE id = $1 AND status = 'running'
+            "#,
+            id, now
+        )
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // retry_at = None — попытки исчерпаны, задача уходит в dead
+    async fn fail(&self, id: Uuid, error: &str, retry_at: Option<OffsetDateTime>, now: OffsetDateTime) -> RepoResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE jobs
+               SET status       = CASE WHEN $3::timestamptz IS NULL THEN 'dead' ELSE 'queued' END::job_status,
+                   run_at       = COALESCE($3, run_at),
+                   finished_at  = CASE WHEN $3::timestamptz IS NULL THEN $4::timestamptz END,
+                   locked_until = NULL,
+                   last_error   = $2
+             WHERE id = $1 AND status = 'running'
+            "#,
+            id, error, retry_at, now
+        )
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn list(&self, status: Option<JobStatus>, kind: Option<&str>, limit: i64) -> RepoResult<Vec<JobRow>> {
+        let rows = sqlx::query_as!(
+            JobRow,
+            r#"
+            SELECT id, kind, payload, status AS "status: JobStatus",
+                   attempts, max_attempts, run_at, last_error,
+                   finished_at, created_at, updated_at
+            FROM jobs
+            WHERE ($1::job_status IS NULL OR status = $1)
+              AND ($2::text IS NULL OR kind = $2)
+            ORDER BY updated_at DESC, id
+            LIMIT $3
+            "#,
+            status as Option<JobStatus>, kind, limit
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows)
+    }
+
+    // Перезапуск вручную — с чистым счётчиком попыток; ошибка остаётся до следующей
+    async fn retry(&self, id: Uuid, now: OffsetDateTime) -> RepoResult<JobRow> {
+        let r = sqlx::query_as!(
+            JobRow,
+            r#"
+            UPDATE jobs
+               SET status = 'queued', attempts = 0, run_at = $2, finished_at = NULL
+             WHERE id = $1 AND status = 'dead'
+            RETURNING id, kind, payload, status AS "status: JobStatus",
+                      attempts, max_attempts, run_at, last_error,
+                      finished_at, created_at, updated_at
+            "#,
+            id, now
+        )
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| match is_unique_violation(&e) {
+                Some(_) => RepoError::Conflict("the same job is already queued".into()),
+                None => e.into(),
+            })?;
+        match r {
+            Some(r) => Ok(r),
+            None => {
+                let exists = sqlx::query_scalar!("SELECT 1 FROM jobs WHERE id = $1", id)
+                    .fetch_optional(&self.pool)
+                    .await?;
+                match exists {
+                    Some(_) => Err(RepoError::Precondition("only dead jobs can be retried".into())),
+                    None => Err(RepoError::NotFound),
+                }
+            }
+        }
+    }
+
+    // dead не трогаем: их разбирают руками
+    async fn purge_done(&self, before: OffsetDateTime) -> RepoResult<u64> {
+        let res = sqlx::query!("DELETE FROM jobs WHERE status = 'done' AND finished_at < $1", before)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+}
+
+// Кладёт задачу в очередь на том соединении, где идёт транзакция изменения.
+// С dedupe_key повтор уже ждущей задачи молча пропускается
+pub async fn enqueue<P: Serialize>(
+    conn: &mut PgConnection,
+    kind: &str,
+    payload: &P,
+    dedupe_key: Option<&str>,
+    run_at: OffsetDateTime,
+) -> RepoResult<()> {
+    let payload = serde_json::to_value(payload)
+        .map_err(|e| RepoError::Db(sqlx::Error::Encode(e.into())))?;
+    sqlx::query!(
+        r#"
+        INSERT INTO jobs (id, kind, payload, dedupe_key, run_at)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (kind, dedupe_key) WHERE status = 'queued' AND dedupe_key IS NOT NULL DO NOTHING
+        "#,
+        Uuid::new_v4(), kind, payload, dedupe_key, run_at
+    )
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+// Синхронизация копий в Google Calendar после любого изменения события или записей;
+// событиям, у участников которых нет ни аккаунта Google, ни копий, задача не нужна
+pub async fn enqueue_gcal_sync(conn: &mut PgConnection, event_ids: &[Uuid]) -> RepoResult<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO jobs (id, kind, payload, dedupe_key)
+        SELECT gen_random_uuid(), $1, jsonb_build_object('event_id', e), e::text
+          FROM unnest($2::uuid[]) AS e
+         WHERE EXISTS (SELECT 1
+                         FROM registrations r
+                         LEFT JOIN google_accounts g ON g.user_id = r.student_id
+                        WHERE r.event_id = e
+                          AND (g.user_id IS NOT NULL OR r.gcal_event_id IS NOT NULL))
+        ON CONFLICT (kind, dedupe_key) WHERE status = 'queued' AND dedupe_key IS NOT NULL DO NOTHING
+        "#,
+        kinds::GCAL_SYNC_EVENT, event_ids
+    )
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+fn mail_payload(mail: &Mail) -> RepoResult<serde_json::Value> {
+    serde_json::to_value(mail).map_err(|e| RepoError::Db(sqlx::Error::Encode(e.into())))
+}
+
+// Письмо пользователю; адрес и имя подставляются из users в момент постановки
+pub async fn enqueue_mail_to_user(conn: &mut PgConnection, user_id: Uuid, mail: &Mail) -> RepoResult<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO jobs (id, kind, payload)
+        SELECT gen_random_uuid(), $1, $2::jsonb || jsonb_build_object('to', u.email::text, 'name', u.name)
+          FROM users u
+         WHERE u.id = $3
+           AND (NOT $4 OR NOT EXISTS (SELECT 1 FROM notification_opt_outs o
+                                       WHERE o.user_id = u.id AND o.channel = 'email'))
+        "#,
+        kinds::MAIL_SEND, mail_payload(mail)?, user_id, mail.is_optional()
+    )
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+// По письму каждому, кто записан на событие или стоит в очереди
+pub async fn enqueue_mail_to_participants(conn: &mut PgConnection, event_id: Uuid, mail: &Mail) -> RepoResult<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO jobs (id, kind, payload)
+        SELECT gen_random_uuid(), $1, $2::jsonb || jsonb_build_object('to', u.email::text, 'name', u.name)
+          FROM registrations r
+          JOIN users u ON u.id = r.student_id
+         WHERE r.event_id = $3
+           AND r.status IN ('registered', 'waitlisted')
+           AND (NOT $4 OR NOT EXISTS (SELECT 1 FROM notification_opt_outs o
+                                       WHERE o.user_id = u.id AND o.channel = 'email'))
+        "#,
+        kinds::MAIL_SEND, mail_payload(mail)?, event_id, mail.is_optional()
+    )
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
@@ -4,6 +4,8 @@ use uuid::Uuid;
 use crate::auth::roles::ManagerStatus;
 use crate::infra::errors::{RepoError, RepoResult};
 use crate::domain::entities::manager_row::ManagerRow;
+use crate::infra::mail::templates::Mail;
+use crate::infra::repositories::job_repo::enqueue_mail_to_user;
 
 #[async_trait]
 pub trait ManagerRepository {
@@ -40,23 +42,37 @@ impl ManagerRepository for PgManagerRepository {
         Ok(rows)
     }
     async fn set_status(&self, company_id: Uuid, user_id: Uuid, status: ManagerStatus) -> RepoResult<()> {
-        let res = sqlx::query!(
+        let mut tx = self.pool.begin().await?;
+        let r = sqlx::query!(
             r#"
-            UPDATE managers
+            WITH old AS (SELECT status FROM managers WHERE company_id = $1 AND user_id = $2 FOR UPDATE)
+            UPDATE managers m
                SET status = $3
-             WHERE company_id = $1
-               AND user_id    = $2
+              FROM old, companies c
+             WHERE m.company_id = $1
+               AND m.user_id    = $2
+               AND c.id = m.company_id
+            RETURNING c.name AS company, old.status <> m.status AS "changed!"
             "#,
             company_id,
             user_id,
             status as _
         )
-            .execute(&self.pool)
-            .await?;
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(RepoError::NotFound)?;
 
-        if res.rows_affected() == 0 {
-            return Err(RepoError::NotFound);
+        // решение по заявке менеджеру сообщаем письмом
+        let mail = match status {
+            _ if !r.changed => None,
+            ManagerStatus::Confirmed => Some(Mail::ManagerApproved { company: r.company }),
+            ManagerStatus::Rejected => Some(Mail::ManagerRejected { company: r.company }),
+            ManagerStatus::Pending => None,
+        };
+        if let Some(mail) = mail {
+            enqueue_mail_to_user(&mut tx, user_id, &mail).await?;
         }
+        tx.commit().await?;
         Ok(())
     }
     async fn request_join(&self, company_id: Uuid, user_id: Uuid) -> RepoResult<()> {
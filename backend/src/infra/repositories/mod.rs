@@ -6,3 +6,14 @@ pub mod registration_repo;
 pub mod manager_repo;
 pub mod telegram_repo;
 pub mod telegram_code_repo;
+pub mod calendar_repo;
+pub mod google_repo;
+pub mod search_repo;
+pub mod tag_repo;
+pub mod job_repo;
+pub mod reminder_repo;
+pub mod idempotency_repo;
+pub mod session_repo;
+pub mod password_reset_repo;
+pub mod email_verification_repo;
+pub mod settings_repo;
@@ -0,0 +1,121 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::infra::errors::{RepoError, RepoResult};
+use crate::infra::jobs::{kinds, TelegramJob};
+use crate::infra::mail::templates::Mail;
+use crate::infra::repositories::job_repo::{enqueue, enqueue_mail_to_user};
+use crate::infra::repositories::session_repo::revoke_user_sessions;
+
+// Куда уходит ссылка для сброса; задача ставится в той же транзакции, что и токен
+#[derive(Debug, Clone)]
+pub enum ResetDelivery {
+    Email(Mail),
+    Telegram(TelegramJob),
+}
+
+#[async_trait]
+pub trait PasswordResetRepository {
+    // false — предыдущий токен выпущен меньше min_interval назад, новый не выпускается
+    async fn issue(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        now: OffsetDateTime,
+        expires_at: OffsetDateTime,
+        min_interval: Duration,
+        delivery: &ResetDelivery,
+    ) -> RepoResult<bool>;
+    // Гасит токен, меняет пароль и отзывает все сессии; NotFound — токен неизвестен, использован или истёк
+    async fn reset(&self, token_hash: &str, password_hash: &str, now: OffsetDateTime) -> RepoResult<Uuid>;
+}
+
+#[derive(Clone)]
+pub struct PgPasswordResetRepository { pool: Pool<Postgres> }
+impl PgPasswordResetRepository { pub fn new(pool: Pool<Postgres>) -> Self { Self { pool } } }
+
+#[async_trait]
+impl PasswordResetRepository for PgPasswordResetRepository {
+    async fn issue(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        now: OffsetDateTime,
+        expires_at: OffsetDateTime,
+        min_interval: Duration,
+        delivery: &ResetDelivery,
+    ) -> RepoResult<bool> {
+        let mut tx = self.pool.begin().await?;
+        let recent = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(SELECT 1 FROM password_resets
+                           WHERE user_id = $1 AND used_at IS NULL AND created_at > $2) AS "recent!"
+            "#,
+            user_id, now - min_interval
+        )
+            .fetch_one(&mut *tx)
+            .await?;
+        if recent {
+            return Ok(false);
+        }
+
+        // действует только последняя ссылка; заодно чистим давно истёкшие чужие
+        sqlx::query!(
+            r#"DELETE FROM password_resets WHERE user_id = $1 OR expires_at < $2"#,
+            user_id, now - Duration::days(1)
+        )
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO password_resets (token_hash, user_id, created_at, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            token_hash, user_id, now, expires_at
+        )
+            .execute(&mut *tx)
+            .await?;
+
+        match delivery {
+            ResetDelivery::Email(mail) => enqueue_mail_to_user(&mut tx, user_id, mail).await?,
+            ResetDelivery::Telegram(job) => enqueue(&mut tx, kinds::TELEGRAM_SEND, job, None, now).await?,
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn reset(&self, token_hash: &str, password_hash: &str, now: OffsetDateTime) -> RepoResult<Uuid> {
+        let mut tx = self.pool.begin().await?;
+        let user_id = sqlx::query_scalar!(
+            r#"
+            UPDATE password_resets
+               SET used_at = $2
+             WHERE token_hash = $1 AND used_at IS NULL AND expires_at > $2
+            RETURNING user_id
+            "#,
+            token_hash, now
+        )
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(RepoError::NotFound)?;
+
+        // смена хеша поднимает users.token_version, выданные access-токены перестают приниматься
+        sqlx::query!(
+            r#"UPDATE users SET password_hash = $2 WHERE id = $1"#,
+            user_id, password_hash
+        )
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"DELETE FROM password_resets WHERE user_id = $1 AND token_hash <> $2"#,
+            user_id, token_hash
+        )
+            .execute(&mut *tx)
+            .await?;
+        revoke_user_sessions(&mut tx, user_id, None, "password_reset", now).await?;
+        tx.commit().await?;
+        Ok(user_id)
+    }
+}
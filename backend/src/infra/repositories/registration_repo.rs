@@ -4,6 +4,7 @@ use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::infra::errors::{RepoError, RepoResult};
+use crate::domain::entities::registration::RegistrationStatus;
 use crate::domain::entities::registration_row::RegistrationRow;
 
 #[async_trait]
@@ -93,7 +94,9 @@ impl RegistrationRepository for PgRegistrationRepository {
               u.name  AS student_name,
               u.email AS student_email,
               r.registered_at,
-              r.gcal_event_id
+              r.gcal_event_id,
+              r.status AS "status: RegistrationStatus",
+              r.checked_in_at
             FROM registrations r
             JOIN users u ON u.id = r.student_id
             WHERE r.event_id = $1 AND r.student_id = $2 AND r.status = 'registered'
@@ -133,7 +136,7 @@ impl RegistrationRepository for PgRegistrationRepository {
               ON CONFLICT (event_id, student_id) DO UPDATE
                 SET status = 'registered',
                     canceled_at = NULL
-              RETURNING event_id, student_id, registered_at, gcal_event_id
+              RETURNING event_id, student_id, registered_at, gcal_event_id, status, checked_in_at
             )
             SELECT
               upsert.event_id,
@@ -141,7 +144,9 @@ impl RegistrationRepository for PgRegistrationRepository {
               u.name  AS student_name,
               u.email AS student_email,
               upsert.registered_at,
-              upsert.gcal_event_id
+              upsert.gcal_event_id,
+              upsert.status AS "status: RegistrationStatus",
+              upsert.checked_in_at
             FROM upsert
             JOIN users u ON u.id = upsert.student_id
             "#,
@@ -182,7 +187,9 @@ impl RegistrationRepository for PgRegistrationRepository {
               u.name  AS student_name,
               u.email AS student_email,
               r.registered_at,
-              r.gcal_event_id
+              r.gcal_event_id,
+              r.status AS "status: RegistrationStatus",
+              r.checked_in_at
             FROM registrations r
             JOIN users u ON u.id = r.student_id
             WHERE r.event_id = $1 AND r.status = 'registered'
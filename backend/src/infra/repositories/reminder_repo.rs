@@ -0,0 +1,190 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::domain::entities::notification::NotificationChannel;
+use crate::domain::entities::registration::RegistrationStatus;
+use crate::infra::errors::{RepoError, RepoResult};
+use crate::infra::jobs::kinds;
+
+// Всё, что нужно в момент отправки: условия перепроверяются, т.к. между
+// планированием и отправкой студент мог отписаться, отвязать Telegram или отменить запись
+#[derive(Debug, Clone)]
+pub struct ReminderTarget {
+    pub title: String,
+    pub location: Option<String>,
+    pub starts_at: OffsetDateTime,
+    pub signup_deadline: Option<OffsetDateTime>,
+    pub published: bool,
+    pub telegram_user_id: Option<i64>,
+    pub registration: Option<RegistrationStatus>,
+    pub opted_out: bool,
+}
+
+#[async_trait]
+pub trait ReminderRepository {
+    async fn schedule_start(&self, kind: &str, from: OffsetDateTime, to: OffsetDateTime) -> RepoResult<u64>;
+    async fn schedule_deadline(&self, from: OffsetDateTime, to: OffsetDateTime) -> RepoResult<u64>;
+    async fn target(&self, event_id: Uuid, user_id: Uuid, channel: NotificationChannel) -> RepoResult<ReminderTarget>;
+    // Помечает напоминание отправленным до отправки; false — его уже забрал другой запуск задачи
+    async fn claim(&self, event_id: Uuid, user_id: Uuid, kind: &str, channel: NotificationChannel, now: OffsetDateTime) -> RepoResult<bool>;
+    // Снимает отметку, если отправить не удалось и задача будет повторена
+    async fn release(&self, event_id: Uuid, user_id: Uuid, kind: &str, channel: NotificationChannel) -> RepoResult<()>;
+    async fn opted_out(&self, user_id: Uuid) -> RepoResult<Vec<NotificationChannel>>;
+    async fn set_opt_out(&self, user_id: Uuid, channel: NotificationChannel, opted_out: bool) -> RepoResult<()>;
+}
+
+#[derive(Clone)]
+pub struct PgReminderRepository { pool: Pool<Postgres> }
+impl PgReminderRepository { pub fn new(pool: Pool<Postgres>) -> Self { Self { pool } } }
+
+#[async_trait]
+impl ReminderRepository for PgReminderRepository {
+    // Напоминание о начале всем записанным на события с началом в (from, to].
+    // Строка в event_reminders и задача на отправку появляются одним запросом:
+    // уже запланированное ON CONFLICT отсекает, так что повторный тик ничего не дублирует
+    async fn schedule_start(&self, kind: &str, from: OffsetDateTime, to: OffsetDateTime) -> RepoResult<u64> {
+        let res = sqlx::query!(
+            r#"
+            WITH planned AS (
+              INSERT INTO event_reminders (event_id, user_id, kind, channel)
+              SELECT r.event_id, r.student_id, $1, 'telegram'
+                FROM registrations r
+                JOIN events e ON e.id = r.event_id
+                JOIN telegram_links t ON t.user_id = r.student_id AND t.is_active
+               WHERE r.status = 'registered'
+                 AND e.status = 'published'
+                 AND e.starts_at > $2 AND e.starts_at <= $3
+                 AND NOT EXISTS (SELECT 1 FROM notification_opt_outs o
+                                  WHERE o.user_id = r.student_id AND o.channel = 'telegram')
+              ON CONFLICT DO NOTHING
+              RETURNING event_id, user_id, kind, channel
+            )
+            INSERT INTO jobs (id, kind, payload)
+            SELECT gen_random_uuid(), $4,
+                   jsonb_build_object('event_id', event_id, 'user_id', user_id, 'kind', kind, 'channel', channel)
+              FROM planned
+            "#,
+            kind, from, to, kinds::NOTIFY_REMINDER
+        )
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    // Тем, кто открывал событие, но так и не записался, — запись закрывается в (from, to]
+    async fn schedule_deadline(&self, from: OffsetDateTime, to: OffsetDateTime) -> RepoResult<u64> {
+        let res = sqlx::query!(
+            r#"
+            WITH planned AS (
+              INSERT INTO event_reminders (event_id, user_id, kind, channel)
+              SELECT v.event_id, v.student_id, 'deadline', 'telegram'
+                FROM event_views v
+                JOIN events e ON e.id = v.event_id
+                JOIN telegram_links t ON t.user_id = v.student_id AND t.is_active
+               WHERE e.status = 'published'
+                 AND e.signup_deadline > $1 AND e.signup_deadline <= $2
+                 AND NOT EXISTS (SELECT 1 FROM registrations r
+                                  WHERE r.event_id = v.event_id AND r.student_id = v.student_id)
+                 AND NOT EXISTS (SELECT 1 FROM notification_opt_outs o
+                                  WHERE o.user_id = v.student_id AND o.channel = 'telegram')
+              ON CONFLICT DO NOTHING
+              RETURNING event_id, user_id, kind, channel
+            )
+            INSERT INTO jobs (id, kind, payload)
+            SELECT gen_random_uuid(), $3,
+                   jsonb_build_object('event_id', event_id, 'user_id', user_id, 'kind', kind, 'channel', channel)
+              FROM planned
+            "#,
+            from, to, kinds::NOTIFY_REMINDER
+        )
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    async fn target(&self, event_id: Uuid, user_id: Uuid, channel: NotificationChannel) -> RepoResult<ReminderTarget> {
+        let row = sqlx::query_as!(
+            ReminderTarget,
+            r#"
+            SELECT e.title, e.location, e.starts_at, e.signup_deadline,
+                   e.status = 'published' AS "published!",
+                   (SELECT t.telegram_user_id FROM telegram_links t WHERE t.user_id = $2 AND t.is_active) AS telegram_user_id,
+                   (SELECT r.status FROM registrations r
+                     WHERE r.event_id = e.id AND r.student_id = $2) AS "registration: RegistrationStatus",
+                   EXISTS (SELECT 1 FROM notification_opt_outs o
+                            WHERE o.user_id = $2 AND o.channel = $3) AS "opted_out!"
+              FROM events e
+             WHERE e.id = $1
+            "#,
+            event_id, user_id, channel as NotificationChannel
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+        row.ok_or(RepoError::NotFound)
+    }
+
+    async fn claim(&self, event_id: Uuid, user_id: Uuid, kind: &str, channel: NotificationChannel, now: OffsetDateTime) -> RepoResult<bool> {
+        let claimed = sqlx::query_scalar!(
+            r#"
+            UPDATE event_reminders
+               SET sent_at = $5
+             WHERE event_id = $1 AND user_id = $2 AND kind = $3 AND channel = $4
+               AND sent_at IS NULL
+            RETURNING event_id
+            "#,
+            event_id, user_id, kind, channel as NotificationChannel, now
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(claimed.is_some())
+    }
+
+    async fn release(&self, event_id: Uuid, user_id: Uuid, kind: &str, channel: NotificationChannel) -> RepoResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE event_reminders
+               SET sent_at = NULL
+             WHERE event_id = $1 AND user_id = $2 AND kind = $3 AND channel = $4
+            "#,
+            event_id, user_id, kind, channel as NotificationChannel
+        )
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn opted_out(&self, user_id: Uuid) -> RepoResult<Vec<NotificationChannel>> {
+        let rows = sqlx::query_scalar!(
+            r#"SELECT channel AS "channel: NotificationChannel" FROM notification_opt_outs WHERE user_id = $1"#,
+            user_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows)
+    }
+
+    async fn set_opt_out(&self, user_id: Uuid, channel: NotificationChannel, opted_out: bool) -> RepoResult<()> {
+        if opted_out {
+            sqlx::query!(
+                r#"
+                INSERT INTO notification_opt_outs (user_id, channel)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING
+                "#,
+                user_id, channel as NotificationChannel
+            )
+                .execute(&self.pool)
+                .await?;
+        } else {
+            sqlx::query!(
+                "DELETE FROM notification_opt_outs WHERE user_id = $1 AND channel = $2",
+                user_id, channel as NotificationChannel
+            )
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+}
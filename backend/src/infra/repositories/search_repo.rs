@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::domain::entities::search::{SearchHitRow, SearchKind, SearchScope};
+use crate::infra::errors::RepoResult;
+
+#[async_trait]
+pub trait SearchRepository {
+    async fn search(&self, q: &str, scope: SearchScope, limit: i64) -> RepoResult<Vec<SearchHitRow>>;
+}
+
+#[derive(Clone)]
+pub struct PgSearchRepository { pool: Pool<Postgres> }
+impl PgSearchRepository { pub fn new(pool: Pool<Postgres>) -> Self { Self { pool } } }
+
+struct HitRow {
+    kind: String,
+    id: Uuid,
+    title: String,
+    snippet: String,
+    rank: f32,
+    company_id: Option<Uuid>,
+    company_name: Option<String>,
+    starts_at: Option<OffsetDateTime>,
+    is_published: Option<bool>,
+}
+
+impl From<HitRow> for SearchHitRow {
+    fn from(r: HitRow) -> Self {
+        Self {
+            kind: SearchKind::from_db(&r.kind),
+            id: r.id, title: r.title, snippet: r.snippet, rank: r.rank,
+            company_id: r.company_id, company_name: r.company_name,
+            starts_at: r.starts_at, is_published: r.is_published,
+        }
+    }
+}
+
+#[async_trait]
+impl SearchRepository for PgSearchRepository {
+    // События и компании ранжируются вместе: веса в обоих tsvector согласованы
+    // (A — название, B — компания/место, C — описание)
+    async fn search(&self, q: &str, scope: SearchScope, limit: i64) -> RepoResult<Vec<SearchHitRow>> {
+        let rows = sqlx::query_as!(
+            HitRow,
+            r#"
+            WITH query AS (SELECT search_query($1) AS tsq)
+            SELECT 'event' AS "kind!", e.id AS "id!", e.title AS "title!",
+                   search_snippet(concat_ws(' · ', e.title, e.location, e.description), query.tsq) AS "snippet!",
+                   ts_rank_cd(e.search_tsv, query.tsq) AS "rank!",
+                   e.company_id AS "company_id?", c.name AS "company_name?",
+                   e.starts_at AS "starts_at?", e.is_published AS "is_published?"
+            FROM events e
+            JOIN companies c ON c.id = e.company_id, query
+            WHERE $2::bool
+              AND e.search_tsv @@ query.tsq
+              AND (e.is_published OR $4::bool OR e.company_id = $5)
+            UNION ALL
+            SELECT 'company', c.id, c.name,
+                   search_snippet(concat_ws(' · ', c.name, c.description), query.tsq),
+                   ts_rank_cd(c.search_tsv, query.tsq),
+                   NULL, NULL, NULL, NULL
+            FROM companies c, query
+            WHERE $3::bool
+              AND c.search_tsv @@ query.tsq
+              AND (c.status = 'active' OR $4::bool)
+            ORDER BY 5 DESC, 2
+            LIMIT $6
+            "#,
+            q, scope.events, scope.companies, scope.everything, scope.own_company, limit
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(SearchHitRow::from).collect())
+    }
+}
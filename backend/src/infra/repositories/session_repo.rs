@@ -0,0 +1,217 @@
+use async_trait::async_trait;
+use sqlx::{PgConnection, Pool, Postgres};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::infra::errors::{RepoError, RepoResult};
+use crate::infra::security::session::ClientInfo;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SessionRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub device_label: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: OffsetDateTime,
+    pub last_used_at: OffsetDateTime,
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rotation {
+    Rotated { session_id: Uuid, user_id: Uuid },
+    // предъявлен уже ротированный токен: семейство скомпрометировано и отозвано
+    Reused { session_id: Uuid, user_id: Uuid },
+    // неизвестный токен, отозванная или истёкшая сессия
+    Invalid,
+}
+
+#[async_trait]
+pub trait SessionRepository {
+    async fn create(&self, user_id: Uuid, client: &ClientInfo, token_hash: &str, now: OffsetDateTime, expires_at: OffsetDateTime) -> RepoResult<Uuid>;
+    async fn rotate(&self, old_hash: &str, new_hash: &str, user_agent: Option<&str>, now: OffsetDateTime, expires_at: OffsetDateTime) -> RepoResult<Rotation>;
+    async fn list_active(&self, user_id: Uuid, now: OffsetDateTime) -> RepoResult<Vec<SessionRow>>;
+    // сессия принадлежит пользователю, не отозвана и не истекла
+    async fn is_active(&self, user_id: Uuid, session_id: Uuid, now: OffsetDateTime) -> RepoResult<bool>;
+    async fn revoke(&self, user_id: Uuid, session_id: Uuid, reason: &str, now: OffsetDateTime) -> RepoResult<()>;
+    async fn revoke_all(&self, user_id: Uuid, except: Option<Uuid>, reason: &str, now: OffsetDateTime) -> RepoResult<u64>;
+    async fn purge(&self, before: OffsetDateTime) -> RepoResult<u64>;
+}
+
+#[derive(Clone)]
+pub struct PgSessionRepository { pool: Pool<Postgres> }
+impl PgSessionRepository { pub fn new(pool: Pool<Postgres>) -> Self { Self { pool } } }
+
+#[async_trait]
+impl SessionRepository for PgSessionRepository {
+    async fn create(&self, user_id: Uuid, client: &ClientInfo, token_hash: &str, now: OffsetDateTime, expires_at: OffsetDateTime) -> RepoResult<Uuid> {
+        let mut tx = self.pool.begin().await?;
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO auth_sessions (user_id, device_label, user_agent, created_at, last_used_at, expires_at)
+            VALUES ($1, $2, $3, $4, $4, $5)
+            RETURNING id
+            "#,
+            user_id, client.device_label, client.user_agent, now, expires_at
+        )
+            .fetch_one(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"INSERT INTO refresh_tokens (token_hash, session_id, created_at) VALUES ($1, $2, $3)"#,
+            token_hash, id, now
+        )
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    // Строки токена и сессии блокируются: из двух одновременных refresh одним токеном
+    // второй увидит rotated_at и будет считаться повторным использованием
+    async fn rotate(&self, old_hash: &str, new_hash: &str, user_agent: Option<&str>, now: OffsetDateTime, expires_at: OffsetDateTime) -> RepoResult<Rotation> {
+        let mut tx = self.pool.begin().await?;
+        let found = sqlx::query!(
+            r#"
+            SELECT rt.session_id, rt.rotated_at, s.user_id, s.revoked_at, s.expires_at
+              FROM refresh_tokens rt
+              JOIN auth_sessions s ON s.id = rt.session_id
+             WHERE rt.token_hash = $1
+             FOR UPDATE OF rt, s
+            "#,
+            old_hash
+        )
+            .fetch_optional(&mut *tx)
+            .await?;
+        let Some(t) = found else { return Ok(Rotation::Invalid) };
+        if t.revoked_at.is_some() || t.expires_at <= now {
+            return Ok(Rotation::Invalid);
+        }
+
+        if t.rotated_at.is_some() {
+            sqlx::query!(
+                r#"UPDATE auth_sessions SET revoked_at = $2, revoke_reason = 'reuse' WHERE id = $1"#,
+                t.session_id, now
+            )
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            return Ok(Rotation::Reused { session_id: t.session_id, user_id: t.user_id });
+        }
+
+        sqlx::query!(
+            r#"UPDATE refresh_tokens SET rotated_at = $2 WHERE token_hash = $1"#,
+            old_hash, now
+        )
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"INSERT INTO refresh_tokens (token_hash, session_id, created_at) VALUES ($1, $2, $3)"#,
+            new_hash, t.session_id, now
+        )
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"
+            UPDATE auth_sessions
+               SET last_used_at = $2,
+                   expires_at   = $3,
+                   user_agent   = COALESCE($4, user_agent)
+             WHERE id = $1
+            "#,
+            t.session_id, now, expires_at, user_agent
+        )
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(Rotation::Rotated { session_id: t.session_id, user_id: t.user_id })
+    }
+
+    async fn list_active(&self, user_id: Uuid, now: OffsetDateTime) -> RepoResult<Vec<SessionRow>> {
+        let rows = sqlx::query_as!(
+            SessionRow,
+            r#"
+            SELECT id, user_id, device_label, user_agent, created_at, last_used_at, expires_at
+              FROM auth_sessions
+             WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > $2
+             ORDER BY last_used_at DESC
+            "#,
+            user_id, now
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows)
+    }
+
+    async fn is_active(&self, user_id: Uuid, session_id: Uuid, now: OffsetDateTime) -> RepoResult<bool> {
+        let active = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(SELECT 1 FROM auth_sessions
+                           WHERE id = $2 AND user_id = $1
+                             AND revoked_at IS NULL AND expires_at > $3) AS "active!"
+            "#,
+            user_id, session_id, now
+        )
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(active)
+    }
+
+    async fn revoke(&self, user_id: Uuid, session_id: Uuid, reason: &str, now: OffsetDateTime) -> RepoResult<()> {
+        let res = sqlx::query!(
+            r#"
+            UPDATE auth_sessions
+               SET revoked_at = $3, revoke_reason = $4
+             WHERE id = $2 AND user_id = $1 AND revoked_at IS NULL
+            "#,
+            user_id, session_id, now, reason
+        )
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn revoke_all(&self, user_id: Uuid, except: Option<Uuid>, reason: &str, now: OffsetDateTime) -> RepoResult<u64> {
+        let mut conn = self.pool.acquire().await?;
+        revoke_user_sessions(&mut conn, user_id, except, reason, now).await
+    }
+
+    // отозванные и истёкшие сессии вместе с их токенами (ON DELETE CASCADE)
+    async fn purge(&self, before: OffsetDateTime) -> RepoResult<u64> {
+        let res = sqlx::query!(
+            r#"
+            DELETE FROM auth_sessions
+             WHERE expires_at < $1 OR revoked_at < $1
+            "#,
+            before
+        )
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+}
+
+// Отзывает все сессии пользователя, кроме except; для транзакций других репозиториев
+// (смена и сброс пароля), где это должно случиться вместе с записью нового хеша
+pub async fn revoke_user_sessions(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    except: Option<Uuid>,
+    reason: &str,
+    now: OffsetDateTime,
+) -> RepoResult<u64> {
+    let res = sqlx::query!(
+        r#"
+        UPDATE auth_sessions
+           SET revoked_at = $3, revoke_reason = $4
+         WHERE user_id = $1 AND revoked_at IS NULL
+           AND id IS DISTINCT FROM $2
+        "#,
+        user_id, except, now, reason
+    )
+        .execute(&mut *conn)
+        .await?;
+    Ok(res.rows_affected())
+}
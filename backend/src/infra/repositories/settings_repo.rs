@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::infra::errors::RepoResult;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppSettings {
+    pub require_verified_email: bool,
+    pub updated_at: OffsetDateTime,
+    pub updated_by: Option<Uuid>,
+}
+
+#[async_trait]
+pub trait SettingsRepository {
+    async fn get(&self) -> RepoResult<AppSettings>;
+    // None — значение не меняется
+    async fn update(&self, require_verified_email: Option<bool>, by: Uuid, now: OffsetDateTime) -> RepoResult<AppSettings>;
+}
+
+#[derive(Clone)]
+pub struct PgSettingsRepository { pool: Pool<Postgres> }
+impl PgSettingsRepository { pub fn new(pool: Pool<Postgres>) -> Self { Self { pool } } }
+
+#[async_trait]
+impl SettingsRepository for PgSettingsRepository {
+    async fn get(&self) -> RepoResult<AppSettings> {
+        let row = sqlx::query_as!(
+            AppSettings,
+            r#"SELECT require_verified_email, updated_at, updated_by FROM app_settings"#
+        )
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row)
+    }
+
+    async fn update(&self, require_verified_email: Option<bool>, by: Uuid, now: OffsetDateTime) -> RepoResult<AppSettings> {
+        let row = sqlx::query_as!(
+            AppSettings,
+            r#"
+            UPDATE app_settings
+               SET require_verified_email = COALESCE($1, require_verified_email),
+                   updated_at = $3,
+                   updated_by = $2
+            RETURNING require_verified_email, updated_at, updated_by
+            "#,
+            require_verified_email, by, now
+        )
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row)
+    }
+}
@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::domain::entities::tag::TagRow;
+use crate::infra::errors::{is_unique_violation, RepoError, RepoResult};
+
+#[async_trait]
+pub trait TagRepository {
+    async fn list(&self) -> RepoResult<Vec<TagRow>>;
+    async fn by_slugs(&self, slugs: &[String]) -> RepoResult<Vec<TagRow>>;
+    async fn create(&self, row: TagRow) -> RepoResult<TagRow>;
+    async fn update(&self, row: TagRow) -> RepoResult<TagRow>;
+    async fn get(&self, id: Uuid) -> RepoResult<TagRow>;
+    async fn delete(&self, id: Uuid) -> RepoResult<()>;
+}
+
+#[derive(Clone)]
+pub struct PgTagRepository { pool: Pool<Postgres> }
+impl PgTagRepository { pub fn new(pool: Pool<Postgres>) -> Self { Self { pool } } }
+
+fn slug_conflict(e: sqlx::Error) -> RepoError {
+    match is_unique_violation(&e) {
+        Some(_) => RepoError::Conflict("tag slug already exists".into()),
+        None => e.into(),
+    }
+}
+
+#[async_trait]
+impl TagRepository for PgTagRepository {
+    async fn list(&self) -> RepoResult<Vec<TagRow>> {
+        let rows = sqlx::query_as!(
+            TagRow,
+            r#"
+            SELECT id, slug, name, category
+            FROM tags
+            ORDER BY category NULLS LAST, name
+            "#
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows)
+    }
+
+    async fn by_slugs(&self, slugs: &[String]) -> RepoResult<Vec<TagRow>> {
+        let rows = sqlx::query_as!(
+            TagRow,
+            "SELECT id, slug, name, category FROM tags WHERE slug = ANY($1)",
+            slugs
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows)
+    }
+
+    async fn create(&self, row: TagRow) -> RepoResult<TagRow> {
+        sqlx::query_as!(
+            TagRow,
+            r#"
+            INSERT INTO tags (id, slug, name, category)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, slug, name, category
+            "#,
+            row.id, row.slug, row.name, row.category
+        )
+            .fetch_one(&self.pool)
+            .await
+            .map_err(slug_conflict)
+    }
+
+    async fn update(&self, row: TagRow) -> RepoResult<TagRow> {
+        sqlx::query_as!(
+            TagRow,
+            r#"
+            UPDATE tags
+               SET slug = $2, name = $3, category = $4
+             WHERE id = $1
+            RETURNING id, slug, name, category
+            "#,
+            row.id, row.slug, row.name, row.category
+        )
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(slug_conflict)?
+            .ok_or(RepoError::NotFound)
+    }
+
+    async fn get(&self, id: Uuid) -> RepoResult<TagRow> {
+        sqlx::query_as!(TagRow, "SELECT id, slug, name, category FROM tags WHERE id = $1", id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(RepoError::NotFound)
+    }
+
+    // с событий тег снимается каскадом
+    async fn delete(&self, id: Uuid) -> RepoResult<()> {
+        let res = sqlx::query!("DELETE FROM tags WHERE id = $1", id)
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 { return Err(RepoError::NotFound); }
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 // infra/repositories/telegram_repo.rs
 use async_trait::async_trait;
 use sqlx::{Pool, Postgres};
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::infra::errors::{RepoError, RepoResult};
@@ -10,8 +11,12 @@ pub trait TelegramLinkRepository {
     async fn link(&self, user_id: Uuid, telegram_user_id: i64) -> RepoResult<()>;
     async fn unlink_by_user(&self, user_id: Uuid) -> RepoResult<()>;
     async fn get_user_by_telegram(&self, telegram_user_id: i64) -> RepoResult<Uuid>;
+    // только активная привязка: после блокировки бота студент привязывается заново
     async fn exists_for_user(&self, user_id: Uuid) -> RepoResult<bool>;
     async fn is_student(&self, user_id: Uuid) -> RepoResult<bool>;
+    async fn deactivate(&self, user_id: Uuid, now: OffsetDateTime) -> RepoResult<()>;
+    // чат для личных сообщений; None, если привязки нет или бот заблокирован
+    async fn active_chat(&self, user_id: Uuid) -> RepoResult<Option<i64>>;
 }
 
 #[derive(Clone)]
@@ -26,7 +31,9 @@ impl TelegramLinkRepository for PgTelegramLinkRepository {
             INSERT INTO telegram_links (user_id, telegram_user_id, created_at)
             VALUES ($1, $2, now())
             ON CONFLICT (user_id) DO UPDATE
-              SET telegram_user_id = EXCLUDED.telegram_user_id
+              SET telegram_user_id = EXCLUDED.telegram_user_id,
+                  is_active        = true,
+                  deactivated_at   = NULL
             "#,
             user_id, telegram_user_id
         )
@@ -61,7 +68,7 @@ impl TelegramLinkRepository for PgTelegramLinkRepository {
 
     async fn exists_for_user(&self, user_id: Uuid) -> RepoResult<bool> {
         let exists: bool = sqlx::query_scalar!(
-            r#"SELECT EXISTS(SELECT 1 FROM telegram_links WHERE user_id = $1) AS "exists!""#,
+            r#"SELECT EXISTS(SELECT 1 FROM telegram_links WHERE user_id = $1 AND is_active) AS "exists!""#,
             user_id
         )
             .fetch_one(&self.pool)
@@ -78,4 +85,29 @@ impl TelegramLinkRepository for PgTelegramLinkRepository {
             .await?;
         Ok(exists)
     }
-}
\ No newline at end of file
+
+    // бот заблокирован: связь не удаляем, но и писать больше не пытаемся
+    async fn deactivate(&self, user_id: Uuid, now: OffsetDateTime) -> RepoResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE telegram_links
+               SET is_active = false, deactivated_at = $2
+             WHERE user_id = $1 AND is_active
+            "#,
+            user_id, now
+        )
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn active_chat(&self, user_id: Uuid) -> RepoResult<Option<i64>> {
+        let chat = sqlx::query_scalar!(
+            r#"SELECT telegram_user_id FROM telegram_links WHERE user_id = $1 AND is_active"#,
+            user_id
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(chat)
+    }
+}
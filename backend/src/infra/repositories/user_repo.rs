@@ -5,6 +5,9 @@ use uuid::Uuid;
 use crate::auth::roles::{ManagerStatus, UserRole, StudentStatus};
 use crate::infra::errors::{RepoError, RepoResult};
 use crate::domain::entities::user_row::UserRow;
+use crate::infra::mail::templates::Mail;
+use crate::infra::repositories::job_repo::enqueue_mail_to_user;
+use crate::infra::repositories::session_repo::revoke_user_sessions;
 
 #[async_trait]
 pub trait UserRepository {
@@ -18,22 +21,32 @@ pub trait UserRepository {
     ) -> RepoResult<UserRow>;
 
     async fn find_by_email(&self, email: &str) -> RepoResult<UserRow>;
-    async fn find_by_refresh_token(&self, refresh_hash: &str, now: OffsetDateTime)
-        -> RepoResult<UserRow>;
     async fn approve_user(&self, user_id: Uuid, approver_id: Uuid) -> RepoResult<()>;
     async fn create_student(&self, name: &str, email: &str, password_hash: &str)
         -> RepoResult<UserRow>;
     async fn create_manager(&self, name: &str, email: &str, password_hash: &str, company_id: Uuid)
         -> RepoResult<UserRow>;
-    async fn set_refresh_token(&self, user_id: Uuid, refresh_hash: &str, expires_at: OffsetDateTime)
-        -> RepoResult<()>;
     async fn find_by_id(&self, id: Uuid) -> RepoResult<UserRow>;
     async fn student_status(&self, user_id: Uuid) -> RepoResult<Option<StudentStatus>>;
     async fn manager_info(&self, user_id: Uuid) -> RepoResult<Option<(ManagerStatus, Uuid)>>;
-    async fn list_students_by_status(&self, statuses: &[StudentStatus], page: i32, limit: i32, search: &str)
-        -> RepoResult<Vec<(UserRow, Option<StudentStatus>)>>;
+    // версия прав, которую должен нести access-токен (растёт триггерами при смене статуса, роли, пароля)
+    async fn token_version(&self, user_id: Uuid) -> RepoResult<i32>;
+    // verified: Some(true/false) — только с подтверждённым / неподтверждённым email
+    async fn list_students_by_status(
+        &self,
+        statuses: &[StudentStatus],
+        verified: Option<bool>,
+        page: i32,
+        limit: i32,
+        search: &str,
+    ) -> RepoResult<Vec<(UserRow, Option<StudentStatus>, bool)>>;
+    async fn email_verified(&self, user_id: Uuid) -> RepoResult<bool>;
 
     async fn set_student_status(&self, user_id: Uuid, status: StudentStatus) -> RepoResult<()>;
+    // Новый пароль и отзыв остальных сессий (кроме keep_session) одной транзакцией
+    async fn set_password(&self, user_id: Uuid, password_hash: &str, keep_session: Option<Uuid>, now: OffsetDateTime) -> RepoResult<()>;
+    // новый хеш того же пароля; false — хеш успели сменить, и пересчёт не нужен
+    async fn upgrade_password_hash(&self, user_id: Uuid, old_hash: &str, new_hash: &str) -> RepoResult<bool>;
 }
 
 #[derive(Clone)]
@@ -102,33 +115,6 @@ impl UserRepository for PgUserRepository {
         u.ok_or(RepoError::NotFound)
     }
 
-    async fn find_by_refresh_token(
-        &self,
-        refresh_hash: &str,
-        now: OffsetDateTime,
-    ) -> RepoResult<UserRow> {
-        let u = sqlx::query_as!(
-            UserRow,
-            r#"
-            SELECT
-                id,
-                name,
-                email::text as "email!",
-                password_hash,
-                role as "role: UserRole"
-            FROM users
-            WHERE refresh_token_hash = $1
-              AND refresh_token_expiration > $2
-            "#,
-            refresh_hash,
-            now
-        )
-            .fetch_optional(&self.pool)
-            .await?;
-
-        u.ok_or(RepoError::NotFound)
-    }
-
     async fn approve_user(&self, user_id: Uuid, _approver_id: Uuid) -> RepoResult<()> {
         let res = sqlx::query!(
             r#"
@@ -239,33 +225,6 @@ impl UserRepository for PgUserRepository {
         Ok(user)
     }
 
-    async fn set_refresh_token(
-        &self,
-        user_id: Uuid,
-        refresh_hash: &str,
-        expires_at: OffsetDateTime,
-    ) -> RepoResult<()> {
-        let res = sqlx::query!(
-            r#"
-            UPDATE users
-               SET refresh_token_hash = $2,
-                   refresh_token_expiration = $3,
-                   updated_at = now()
-             WHERE id = $1
-            "#,
-            user_id,
-            refresh_hash,
-            expires_at
-        )
-            .execute(&self.pool)
-            .await?;
-
-        if res.rows_affected() == 0 {
-            return Err(RepoError::NotFound);
-        }
-        Ok(())
-    }
-
     async fn find_by_id(&self, id: Uuid) -> RepoResult<UserRow> {
         let u = sqlx::query_as!(
             UserRow,
@@ -287,6 +246,55 @@ impl UserRepository for PgUserRepository {
         u.ok_or(RepoError::NotFound)
     }
 
+    async fn email_verified(&self, user_id: Uuid) -> RepoResult<bool> {
+        sqlx::query_scalar!(
+            r#"SELECT email_verified_at IS NOT NULL AS "verified!" FROM users WHERE id = $1"#,
+            user_id
+        )
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(RepoError::NotFound)
+    }
+
+    async fn set_password(&self, user_id: Uuid, password_hash: &str, keep_session: Option<Uuid>, now: OffsetDateTime) -> RepoResult<()> {
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query!(
+            r#"UPDATE users SET password_hash = $2 WHERE id = $1"#,
+            user_id, password_hash
+        )
+            .execute(&mut *tx)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(RepoError::NotFound);
+        }
+        revoke_user_sessions(&mut tx, user_id, keep_session, "password_change", now).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn upgrade_password_hash(&self, user_id: Uuid, old_hash: &str, new_hash: &str) -> RepoResult<bool> {
+        let mut tx = self.pool.begin().await?;
+        // пароль прежний — выданные токены остаются в силе (см. trg_users_bump_token_version)
+        sqlx::query_scalar!("SELECT set_config('app.password_rehash', 'on', true)")
+            .fetch_one(&mut *tx)
+            .await?;
+        let res = sqlx::query!(
+            r#"UPDATE users SET password_hash = $3 WHERE id = $1 AND password_hash = $2"#,
+            user_id, old_hash, new_hash
+        )
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(res.rows_affected() == 1)
+    }
+
+    async fn token_version(&self, user_id: Uuid) -> RepoResult<i32> {
+        sqlx::query_scalar!("SELECT token_version FROM users WHERE id = $1", user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(RepoError::NotFound)
+    }
+
     async fn student_status(&self, user_id: Uuid) -> RepoResult<Option<StudentStatus>> {
 
         let row = sqlx::query!(
@@ -321,8 +329,14 @@ impl UserRepository for PgUserRepository {
         Ok(row.map(|r| (r.status, r.company_id)))
     }
 
-    async fn list_students_by_status(&self, statuses: &[StudentStatus], page: i32, limit: i32, search: &str)
-        -> RepoResult<Vec<(UserRow, Option<StudentStatus>)>> {
+    async fn list_students_by_status(
+        &self,
+        statuses: &[StudentStatus],
+        verified: Option<bool>,
+        page: i32,
+        limit: i32,
+        search: &str,
+    ) -> RepoResult<Vec<(UserRow, Option<StudentStatus>, bool)>> {
 
         let page = page.max(1);
         let limit = limit.max(1);
@@ -350,21 +364,24 @@ impl UserRepository for PgUserRepository {
           u.email::text as "email!",
           u.password_hash,
           u.role as "role: crate::auth::roles::UserRole",
-          s.status as "status: crate::auth::roles::StudentStatus"
+          s.status as "status: crate::auth::roles::StudentStatus",
+          u.email_verified_at IS NOT NULL AS "email_verified!"
         FROM users u
         JOIN students s ON s.user_id = u.id
         WHERE u.role = 'student'
           AND ( $1 = '' OR u.name ILIKE $2 OR u.email ILIKE $2 )
           AND ( s.status::text = ANY($3::text[]) )
+          AND ( $6::bool IS NULL OR (u.email_verified_at IS NOT NULL) = $6 )
         ORDER BY u.created_at DESC NULLS LAST, u.id
         OFFSET $4 LIMIT $5
         "#,
-        // $1..$5
+        // $1..$6
         search,
         pattern,
         &status_texts[..],
         offset_i64,
         limit_i64,
+        verified,
     )
             .fetch_all(&self.pool)
             .await
@@ -380,9 +397,9 @@ impl UserRepository for PgUserRepository {
                     password_hash: r.password_hash,
                     role: r.role,
                 };
-                (u, Some(r.status))
+                (u, Some(r.status), r.email_verified)
             })
-            .collect::<Vec<(UserRow, Option<StudentStatus>)>>();
+            .collect::<Vec<(UserRow, Option<StudentStatus>, bool)>>();
 
         Ok(list)
     }
@@ -399,22 +416,65 @@ impl UserRepository for PgUserRepository {
             StudentStatus::Rejected  => "rejected",
         };
 
-        let res = sqlx::query!(
+        let mut tx = self.pool.begin().await?;
+        let changed = sqlx::query_scalar!(
         r#"
+        WITH old AS (SELECT status FROM students WHERE user_id = $1 FOR UPDATE)
         UPDATE students
            SET status = ($2)::text::student_status
+          FROM old
          WHERE user_id = $1
+        RETURNING old.status <> students.status AS "changed!"
         "#,
         user_id,
         status_text,
     )
-            .execute(&self.pool)
+            .fetch_optional(&mut *tx)
             .await
-            .map_err(RepoError::Db)?;
-
-        if res.rows_affected() == 0 {
-            return Err(RepoError::NotFound);
+            .map_err(RepoError::Db)?
+            .ok_or(RepoError::NotFound)?;
+
+        // о решении деканата студенту пишем на почту, повторное решение не дублируем
+        let mail = match status {
+            _ if !changed => None,
+            StudentStatus::Confirmed => Some(Mail::StudentApproved),
+            StudentStatus::Rejected => Some(Mail::StudentRejected),
+            StudentStatus::Created | StudentStatus::Linked => None,
+        };
+        if let Some(mail) = mail {
+            enqueue_mail_to_user(&mut tx, user_id, &mail).await?;
         }
+        tx.commit().await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn students_can_be_filtered_by_verified_email(pool: Pool<Postgres>) {
+        let repo = PgUserRepository::new(pool.clone());
+        let verified = repo.create_student("Аня", "a@test.io", "x").await.unwrap().id;
+        let unverified = repo.create_student("Боря", "b@test.io", "x").await.unwrap().id;
+        sqlx::query!("UPDATE users SET email_verified_at = now() WHERE id = $1", verified)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let ids = |verified: Option<bool>| {
+            let repo = repo.clone();
+            async move {
+                let mut rows = repo.list_students_by_status(&[StudentStatus::Created], verified, 1, 50, "")
+                    .await
+                    .unwrap();
+                rows.sort_by_key(|(u, _, _)| u.email.clone());
+                rows.into_iter().map(|(u, _, v)| (u.id, v)).collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(ids(Some(true)).await, vec![(verified, true)]);
+        assert_eq!(ids(Some(false)).await, vec![(unverified, false)]);
+        assert_eq!(ids(None).await, vec![(verified, true), (unverified, false)]);
+    }
+}
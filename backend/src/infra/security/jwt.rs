@@ -1,4 +1,4 @@
-use base64::{engine::general_purpose::STANDARD as b64, Engine};
+use base64::{engine::general_purpose::{STANDARD as b64, URL_SAFE_NO_PAD}, Engine};
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
@@ -44,6 +44,12 @@ pub struct Claims {
     pub student_status: Option<String>,   // "created" | "linked" | "confirmed" | "rejected"
     pub manager_status: Option<String>,   // "pending" | "confirmed" | "rejected"
     pub company_id: Option<Uuid>,
+    // сессия (устройство), которой выдан токен; у токенов до появления сессий отсутствует
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
+    // версия прав пользователя на момент выпуска (users.token_version)
+    #[serde(default)]
+    pub ver: i32,
 }
 
 #[derive(Debug, Error)]
@@ -74,11 +80,24 @@ impl TokenService {
         manager_status: Option<String>,
         company_id: Option<Uuid>,
     ) -> Result<String, TokenError> {
+        self.sign(&self.new_claims(sub_email, user_id, role, student_status, manager_status, company_id))
+    }
+
+    // Claims нового токена без сессии; вызывающий может дописать sid/ver перед sign
+    pub fn new_claims(
+        &self,
+        sub_email: &str,
+        user_id: Uuid,
+        role: &str,
+        student_status: Option<String>,
+        manager_status: Option<String>,
+        company_id: Option<Uuid>,
+    ) -> Claims {
         let now = OffsetDateTime::now_utc();
         let iat = now.unix_timestamp();
         let exp = (now + Duration::minutes(self.cfg.lifetime_minutes)).unix_timestamp();
 
-        let claims = Claims {
+        Claims {
             iss: self.cfg.issuer.clone(),
             aud: self.cfg.audience.clone(),
             sub: sub_email.to_string(),
@@ -90,11 +109,15 @@ impl TokenService {
             student_status,
             manager_status,
             company_id,
-        };
+            sid: None,
+            ver: 0,
+        }
+    }
 
+    pub fn sign(&self, claims: &Claims) -> Result<String, TokenError> {
         let mut header = Header::new(Algorithm::HS256);
         header.kid = None;
-        encode(&header, &claims, &self.cfg.enc()).map_err(|e| TokenError::Jwt(e.to_string()))
+        encode(&header, claims, &self.cfg.enc()).map_err(|e| TokenError::Jwt(e.to_string()))
     }
 
     pub fn generate_student_token(
@@ -133,10 +156,7 @@ impl TokenService {
         claims.iat = now.unix_timestamp();
         claims.exp = (now + Duration::minutes(self.cfg.lifetime_minutes)).unix_timestamp();
         claims.student_status = Some(status.to_string());
-
-        let mut header = Header::new(Algorithm::HS256);
-        header.kid = None;
-        encode(&header, &claims, &self.cfg.enc()).map_err(|e| TokenError::Jwt(e.to_string()))
+        self.sign(&claims)
     }
 
     pub fn validate_token(&self, token: &str) -> Result<Claims, TokenError> {
@@ -154,6 +174,13 @@ impl TokenService {
         b64.encode(buf)
     }
 
+    // одноразовый токен для ссылок (сброс пароля): безопасен в URL без экранирования
+    pub fn generate_link_token(&self) -> String {
+        let mut buf = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut buf);
+        URL_SAFE_NO_PAD.encode(buf)
+    }
+
     pub fn hash_refresh_token(&self, refresh: &str) -> String {
         use sha2::{Digest, Sha256};
         let mut h = Sha256::new();
@@ -194,4 +221,20 @@ mod tests {
         let c2 = svc.validate_token(&t2).unwrap();
         assert_eq!(c2.student_status.as_deref(), Some("confirmed"));
     }
+
+    #[test]
+    fn session_and_version_survive_roundtrip() {
+        std::env::set_var("JWT_HS256_SECRET", "test_secret");
+        let svc = TokenService::new(TokenConfig::from_env());
+        let t = svc.generate_dean_token("d@e.com", Uuid::new_v4()).unwrap();
+        let c = svc.validate_token(&t).unwrap();
+        assert_eq!((c.sid, c.ver), (None, 0));
+
+        let sid = Uuid::new_v4();
+        let mut claims = svc.new_claims("d@e.com", Uuid::new_v4(), "dean", None, None, None);
+        claims.sid = Some(sid);
+        claims.ver = 3;
+        let c = svc.validate_token(&svc.sign(&claims).unwrap()).unwrap();
+        assert_eq!((c.sid, c.ver), (Some(sid), 3));
+    }
 }
\ No newline at end of file
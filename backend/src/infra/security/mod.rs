@@ -1,5 +1,8 @@
 pub mod password;
 pub mod jwt;
 pub mod session;
+pub mod token_version;
+pub mod session_state;
 pub mod password_policy;
 pub mod rbac;
+pub mod ticket;
@@ -1,38 +1,181 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
 use anyhow::Result;
+use argon2::{Algorithm, Argon2, Params, Version};
 use pbkdf2::{
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Pbkdf2,
 };
 use rand_core::OsRng;
 
-pub fn hash_password(password: &str) -> Result<String> {
-    let salt = SaltString::generate(&mut OsRng);
+// Стоимость Argon2id. По умолчанию — минимум из рекомендаций OWASP (19 MiB, 2 прохода);
+// под конкретный сервер подбирается командой `backend argon2-tune`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
 
+impl Default for HashParams {
+    fn default() -> Self {
+        Self { memory_kib: 19 * 1024, iterations: 2, parallelism: 1 }
+    }
+}
 
-    let phc = Pbkdf2
+impl HashParams {
+    pub fn from_env() -> Self {
+        let d = Self::default();
+        let var = |name: &str, default: u32| {
+            std::env::var(name).ok().and_then(|s| s.parse::<u32>().ok()).unwrap_or(default)
+        };
+        Self {
+            memory_kib: var("ARGON2_MEMORY_KIB", d.memory_kib),
+            iterations: var("ARGON2_ITERATIONS", d.iterations),
+            parallelism: var("ARGON2_PARALLELISM", d.parallelism),
+        }
+    }
+
+    fn hasher(&self) -> Result<Argon2<'static>> {
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, None)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+}
+
+// параметры читаются из окружения один раз за процесс
+fn current() -> HashParams {
+    static PARAMS: OnceLock<HashParams> = OnceLock::new();
+    *PARAMS.get_or_init(HashParams::from_env)
+}
+
+// Один хеш — десятки миллисекунд процессора и мегабайты памяти: считаем в пуле блокирующих
+// задач, чтобы вход и регистрация не останавливали воркеры tokio с остальными запросами
+pub async fn hash(password: &str) -> Result<String> {
+    let password = password.to_owned();
+    tokio::task::spawn_blocking(move || hash_password(&password)).await?
+}
+
+pub async fn verify(password: &str, phc_hash: &str) -> Result<bool> {
+    let (password, phc_hash) = (password.to_owned(), phc_hash.to_owned());
+    Ok(tokio::task::spawn_blocking(move || verify_password(&password, &phc_hash)).await?)
+}
+
+fn hash_password(password: &str) -> Result<String> {
+    hash_with(password, current())
+}
+
+fn hash_with(password: &str, params: HashParams) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let phc = params.hasher()?
         .hash_password(password.as_bytes(), &salt)
         .map_err(|e| anyhow::anyhow!(e))?
         .to_string();
-
     Ok(phc)
 }
 
-pub fn verify_password(password: &str, phc_hash: &str) -> bool {
+// Проверяет Argon2 и старые PBKDF2-хеши; схема и параметры берутся из самой PHC-строки
+fn verify_password(password: &str, phc_hash: &str) -> bool {
     let parsed = match PasswordHash::new(phc_hash) {
         Ok(p) => p,
         Err(_) => return false,
     };
-    Pbkdf2.verify_password(password.as_bytes(), &parsed).is_ok()
+    let argon2 = Argon2::default();
+    let verifiers: [&dyn PasswordVerifier; 2] = [&argon2, &Pbkdf2];
+    parsed.verify_password(&verifiers, password).is_ok()
+}
+
+// Хеш сделан не Argon2id или с другими параметрами — после успешного входа его стоит пересчитать
+pub fn needs_rehash(phc_hash: &str) -> bool {
+    needs_rehash_for(phc_hash, current())
+}
+
+fn needs_rehash_for(phc_hash: &str, want: HashParams) -> bool {
+    let Ok(parsed) = PasswordHash::new(phc_hash) else { return true };
+    if parsed.algorithm != argon2::ARGON2ID_IDENT || parsed.version != Some(Version::V0x13.into()) {
+        return true;
+    }
+    match Params::try_from(&parsed) {
+        Ok(p) => (p.m_cost(), p.t_cost(), p.p_cost()) != (want.memory_kib, want.iterations, want.parallelism),
+        Err(_) => true,
+    }
+}
+
+// `backend argon2-tune [целевое время, мс; по умолчанию 100]`: подбирает память и число
+// проходов так, чтобы один хеш на этой машине считался не дольше целевого времени
+pub fn tune(target: Duration) -> Result<()> {
+    const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+    if cfg!(debug_assertions) {
+        println!("warning: debug build, timings are not representative (use --release)");
+    }
+    let measure = |p: HashParams| -> Result<Duration> {
+        let hasher = p.hasher()?;
+        let salt = SaltString::generate(&mut OsRng);
+        let started = Instant::now();
+        for _ in 0..3 {
+            hasher.hash_password(b"benchmark password", &salt).map_err(|e| anyhow::anyhow!(e))?;
+        }
+        Ok(started.elapsed() / 3)
+    };
+
+    let mut best = None;
+    let mut p = HashParams::default();
+    loop {
+        let took = measure(p)?;
+        println!("m={} KiB t={} p={}: {:?}", p.memory_kib, p.iterations, p.parallelism, took);
+        if took > target {
+            break;
+        }
+        best = Some(p);
+        // сначала растим память (главная защита от GPU), упёршись в потолок — число проходов
+        if p.memory_kib * 2 <= MAX_MEMORY_KIB {
+            p.memory_kib *= 2;
+        } else {
+            p.iterations += 1;
+        }
+    }
+
+    let best = best.unwrap_or_else(|| {
+        println!("even the minimum recommended parameters exceed {target:?}; keeping them");
+        HashParams::default()
+    });
+    println!();
+    println!("ARGON2_MEMORY_KIB={}", best.memory_kib);
+    println!("ARGON2_ITERATIONS={}", best.iterations);
+    println!("ARGON2_PARALLELISM={}", best.parallelism);
+    println!("# each concurrent login holds {} MiB while hashing", best.memory_kib / 1024);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // дешёвые параметры, чтобы тесты не тратили секунды на хеширование
+    const CHEAP: HashParams = HashParams { memory_kib: 64, iterations: 1, parallelism: 1 };
+
     #[test]
     fn roundtrip() {
-        let phc = hash_password("Abcd1234!").expect("hash");
+        let phc = hash_with("Abcd1234!", CHEAP).expect("hash");
+        assert!(phc.starts_with("$argon2id$"));
         assert!(verify_password("Abcd1234!", &phc));
         assert!(!verify_password("wrong", &phc));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn legacy_pbkdf2_verifies_and_needs_rehash() {
+        let salt = SaltString::generate(&mut OsRng);
+        let legacy = Pbkdf2.hash_password(b"Abcd1234!", &salt).unwrap().to_string();
+        assert!(verify_password("Abcd1234!", &legacy));
+        assert!(!verify_password("wrong", &legacy));
+        assert!(needs_rehash_for(&legacy, CHEAP));
+    }
+
+    #[test]
+    fn changed_parameters_need_rehash() {
+        let phc = hash_with("Abcd1234!", CHEAP).unwrap();
+        assert!(!needs_rehash_for(&phc, CHEAP));
+        assert!(needs_rehash_for(&phc, HashParams { iterations: 2, ..CHEAP }));
+    }
+}
@@ -1,24 +1,38 @@
 use regex::Regex;
 
-pub fn validate(password: &str) -> Result<(), &'static str> {
+use crate::domain::entities::validation::ValidationErrors;
+
+// Все нарушенные правила сразу: (код, сообщение)
+pub fn violations(password: &str) -> Vec<(&'static str, &'static str)> {
+    let mut out = Vec::new();
     if password.len() < 8 {
-        return Err("Пароль должен быть не менее 8 символов.");
+        out.push(("too_short", "Пароль должен быть не менее 8 символов."));
     }
     let has_upper = Regex::new(r"[A-Z]").unwrap();
     let has_lower = Regex::new(r"[a-z]").unwrap();
     let has_digit = Regex::new(r"[0-9]").unwrap();
     let has_spec  = Regex::new(r"[!@#$%^&*(),.?{}|<>]").unwrap();
 
-    if !has_upper.is_match(password) { return Err("Пароль должен содержать хотя бы одну заглавную букву."); }
-    if !has_lower.is_match(password) { return Err("Пароль должен содержать хотя бы одну строчную букву."); }
-    if !has_digit.is_match(password) { return Err("Пароль должен содержать хотя бы одну цифру."); }
-    if !has_spec.is_match(password)  { return Err("Пароль должен содержать хотя бы один специальный символ."); }
-    Ok(())
+    if !has_upper.is_match(password) { out.push(("no_uppercase", "Пароль должен содержать хотя бы одну заглавную букву.")); }
+    if !has_lower.is_match(password) { out.push(("no_lowercase", "Пароль должен содержать хотя бы одну строчную букву.")); }
+    if !has_digit.is_match(password) { out.push(("no_digit", "Пароль должен содержать хотя бы одну цифру.")); }
+    if !has_spec.is_match(password)  { out.push(("no_special", "Пароль должен содержать хотя бы один специальный символ.")); }
+    out
+}
+
+// field — имя поля с паролем в конкретном запросе
+pub fn validate(field: &'static str, password: &str, errs: &mut ValidationErrors) {
+    for (code, message) in violations(password) {
+        errs.add(field, code, message);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    #[test] fn ok()  { assert!(validate("Abcd1234!").is_ok()); }
-    #[test] fn bad() { assert!(validate("abc").is_err()); }
+    #[test] fn ok()  { assert!(violations("Abcd1234!").is_empty()); }
+    #[test] fn bad() {
+        let codes: Vec<_> = violations("abc").into_iter().map(|(c, _)| c).collect();
+        assert_eq!(codes, ["too_short", "no_uppercase", "no_digit", "no_special"]);
+    }
 }
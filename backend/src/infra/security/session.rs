@@ -1 +1,44 @@
-// cookie/session helpers placeholder
+use http::{header, HeaderMap};
+
+// длиннее в списке устройств не показываем и не храним
+const MAX_LEN: usize = 200;
+
+// Откуда выполнен вход: метка устройства от клиента («Telegram-бот», «Веб») и User-Agent.
+// Показывается в списке сессий, чтобы пользователь узнал своё устройство
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub device_label: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl ClientInfo {
+    pub fn new(device_label: Option<String>, headers: &HeaderMap) -> Self {
+        let user_agent = headers.get(header::USER_AGENT).and_then(|v| v.to_str().ok());
+        Self {
+            device_label: device_label.as_deref().and_then(clean),
+            user_agent: user_agent.and_then(clean),
+        }
+    }
+}
+
+fn clean(s: &str) -> Option<String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+    Some(s.chars().take(MAX_LEN).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trims_and_truncates() {
+        let mut h = HeaderMap::new();
+        h.insert(header::USER_AGENT, "x".repeat(500).parse().unwrap());
+        let c = ClientInfo::new(Some("  ".into()), &h);
+        assert_eq!(c.device_label, None);
+        assert_eq!(c.user_agent.map(|u| u.len()), Some(MAX_LEN));
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::infra::errors::RepoResult;
+use crate::infra::repositories::session_repo::{PgSessionRepository, SessionRepository};
+
+// дальше кэш не растёт: устаревшие записи выбрасываются
+const MAX_ENTRIES: usize = 10_000;
+
+// (user_id, активна, когда проверено)
+type Entry = (Uuid, bool, Instant);
+
+// Активна ли сессия, к которой привязан access-токен. Как и TokenVersions, отзыв из этого
+// процесса сбрасывает запись сразу, отзыв с других инстансов виден через ttl
+#[derive(Clone)]
+pub struct SessionStates {
+    repo: PgSessionRepository,
+    ttl: Duration,
+    cache: Arc<Mutex<HashMap<Uuid, Entry>>>,
+}
+
+impl SessionStates {
+    pub fn new(repo: PgSessionRepository, ttl: Duration) -> Self {
+        Self { repo, ttl, cache: Arc::default() }
+    }
+
+    pub async fn is_active(&self, user_id: Uuid, session_id: Uuid) -> RepoResult<bool> {
+        let now = Instant::now();
+        if let Some(active) = self.cached(user_id, session_id, now) {
+            return Ok(active);
+        }
+        let active = self.repo.is_active(user_id, session_id, OffsetDateTime::now_utc()).await?;
+        self.remember(user_id, session_id, active, now);
+        Ok(active)
+    }
+
+    // Вызывается после отзыва сессии, чтобы её access-токены отклонялись сразу
+    pub fn invalidate(&self, session_id: Uuid) {
+        self.cache.lock().expect("session state cache poisoned").remove(&session_id);
+    }
+
+    // то же для всех сессий пользователя (выход на всех устройствах, сброс пароля)
+    pub fn invalidate_user(&self, user_id: Uuid) {
+        self.cache.lock().expect("session state cache poisoned").retain(|_, (u, _, _)| *u != user_id);
+    }
+
+    fn cached(&self, user_id: Uuid, session_id: Uuid, now: Instant) -> Option<bool> {
+        let cache = self.cache.lock().expect("session state cache poisoned");
+        cache.get(&session_id)
+            .filter(|(u, _, at)| *u == user_id && now.saturating_duration_since(*at) < self.ttl)
+            .map(|(_, active, _)| *active)
+    }
+
+    fn remember(&self, user_id: Uuid, session_id: Uuid, active: bool, now: Instant) {
+        let mut cache = self.cache.lock().expect("session state cache poisoned");
+        if cache.len() >= MAX_ENTRIES {
+            let ttl = self.ttl;
+            cache.retain(|_, (_, _, at)| now.saturating_duration_since(*at) < ttl);
+            if cache.len() >= MAX_ENTRIES {
+                cache.clear();
+            }
+        }
+        cache.insert(session_id, (user_id, active, now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn entries_expire_and_can_be_invalidated() {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap();
+        let states = SessionStates::new(PgSessionRepository::new(pool), Duration::from_secs(30));
+        let (u, s1, s2, t0) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Instant::now());
+
+        assert_eq!(states.cached(u, s1, t0), None);
+        states.remember(u, s1, true, t0);
+        assert_eq!(states.cached(u, s1, t0 + Duration::from_secs(29)), Some(true));
+        assert_eq!(states.cached(u, s1, t0 + Duration::from_secs(30)), None);
+        // чужой токен с тем же sid кэш не подтверждает
+        assert_eq!(states.cached(Uuid::new_v4(), s1, t0), None);
+
+        states.invalidate(s1);
+        assert_eq!(states.cached(u, s1, t0), None);
+
+        states.remember(u, s1, true, t0);
+        states.remember(u, s2, true, t0);
+        states.invalidate_user(u);
+        assert_eq!((states.cached(u, s1, t0), states.cached(u, s2, t0)), (None, None));
+    }
+}
@@ -0,0 +1,95 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as b64url, Engine};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::infra::security::jwt::TokenConfig;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// префикс отделяет подпись билета от прочих HMAC на том же секрете
+const DOMAIN: &[u8] = b"tsu-ticket:v1";
+const MAC_LEN: usize = 32;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TicketError {
+    #[error("malformed ticket")]
+    Malformed,
+    #[error("invalid ticket signature")]
+    BadSignature,
+}
+
+// Билет: base64url(event_id || student_id || hmac). Хранить в БД не нужно —
+// повторное использование ловится по статусу записи (attended).
+#[derive(Clone)]
+pub struct TicketService {
+    secret: Vec<u8>,
+}
+
+impl TicketService {
+    pub fn new(cfg: &TokenConfig) -> Self {
+        Self { secret: cfg.hmac_secret.as_bytes().to_vec() }
+    }
+
+    pub fn issue(&self, event_id: Uuid, student_id: Uuid) -> String {
+        let mut buf = Vec::with_capacity(32 + MAC_LEN);
+        buf.extend_from_slice(event_id.as_bytes());
+        buf.extend_from_slice(student_id.as_bytes());
+        let tag = self.mac(&buf).finalize().into_bytes();
+        buf.extend_from_slice(&tag);
+        b64url.encode(buf)
+    }
+
+    pub fn verify(&self, token: &str) -> Result<(Uuid, Uuid), TicketError> {
+        let raw = b64url.decode(token.trim()).map_err(|_| TicketError::Malformed)?;
+        if raw.len() != 32 + MAC_LEN {
+            return Err(TicketError::Malformed);
+        }
+        let (payload, tag) = raw.split_at(32);
+        self.mac(payload)
+            .verify_slice(tag)
+            .map_err(|_| TicketError::BadSignature)?;
+
+        let event_id = Uuid::from_slice(&payload[..16]).map_err(|_| TicketError::Malformed)?;
+        let student_id = Uuid::from_slice(&payload[16..]).map_err(|_| TicketError::Malformed)?;
+        Ok((event_id, student_id))
+    }
+
+    fn mac(&self, payload: &[u8]) -> HmacSha256 {
+        let mut m = HmacSha256::new_from_slice(&self.secret).expect("hmac accepts any key length");
+        m.update(DOMAIN);
+        m.update(payload);
+        m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn svc(secret: &str) -> TicketService {
+        TicketService { secret: secret.as_bytes().to_vec() }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let s = svc("test_secret");
+        let (e, st) = (Uuid::new_v4(), Uuid::new_v4());
+        let t = s.issue(e, st);
+        assert_eq!(s.verify(&t), Ok((e, st)));
+    }
+
+    #[test]
+    fn rejects_tampered_and_foreign() {
+        let s = svc("test_secret");
+        let t = s.issue(Uuid::new_v4(), Uuid::new_v4());
+
+        let mut raw = b64url.decode(&t).unwrap();
+        raw[20] ^= 0x01;
+        assert_eq!(s.verify(&b64url.encode(raw)), Err(TicketError::BadSignature));
+
+        assert_eq!(svc("other_secret").verify(&t), Err(TicketError::BadSignature));
+        assert_eq!(s.verify("not-a-ticket"), Err(TicketError::Malformed));
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+use crate::infra::errors::RepoResult;
+use crate::infra::repositories::user_repo::{PgUserRepository, UserRepository};
+
+// дальше кэш не растёт: устаревшие записи выбрасываются
+const MAX_ENTRIES: usize = 10_000;
+
+// Версии прав пользователей для проверки access-токенов без похода в Postgres на каждый запрос.
+// Изменения из этого процесса сбрасывают запись сразу, изменения с других инстансов видны через ttl
+#[derive(Clone)]
+pub struct TokenVersions {
+    repo: PgUserRepository,
+    ttl: Duration,
+    cache: Arc<Mutex<HashMap<Uuid, (i32, Instant)>>>,
+}
+
+impl TokenVersions {
+    pub fn new(repo: PgUserRepository, ttl: Duration) -> Self {
+        Self { repo, ttl, cache: Arc::default() }
+    }
+
+    pub async fn current(&self, user_id: Uuid) -> RepoResult<i32> {
+        let now = Instant::now();
+        if let Some(v) = self.cached(user_id, now) {
+            return Ok(v);
+        }
+        let v = self.repo.token_version(user_id).await?;
+        self.remember(user_id, v, now);
+        Ok(v)
+    }
+
+    // Вызывается после смены статуса/роли/пароля, чтобы старые токены отклонялись сразу
+    pub fn invalidate(&self, user_id: Uuid) {
+        self.cache.lock().expect("token version cache poisoned").remove(&user_id);
+    }
+
+    fn cached(&self, user_id: Uuid, now: Instant) -> Option<i32> {
+        let cache = self.cache.lock().expect("token version cache poisoned");
+        cache.get(&user_id)
+            .filter(|(_, at)| now.saturating_duration_since(*at) < self.ttl)
+            .map(|(v, _)| *v)
+    }
+
+    fn remember(&self, user_id: Uuid, version: i32, now: Instant) {
+        let mut cache = self.cache.lock().expect("token version cache poisoned");
+        if cache.len() >= MAX_ENTRIES {
+            let ttl = self.ttl;
+            cache.retain(|_, (_, at)| now.saturating_duration_since(*at) < ttl);
+            if cache.len() >= MAX_ENTRIES {
+                cache.clear();
+            }
+        }
+        cache.insert(user_id, (version, now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn entries_expire_and_can_be_invalidated() {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap();
+        let versions = TokenVersions::new(PgUserRepository::new(pool), Duration::from_secs(30));
+        let (u, t0) = (Uuid::new_v4(), Instant::now());
+
+        assert_eq!(versions.cached(u, t0), None);
+        versions.remember(u, 2, t0);
+        assert_eq!(versions.cached(u, t0 + Duration::from_secs(29)), Some(2));
+        assert_eq!(versions.cached(u, t0 + Duration::from_secs(30)), None);
+
+        versions.invalidate(u);
+        assert_eq!(versions.cached(u, t0), None);
+    }
+}
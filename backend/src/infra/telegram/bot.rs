@@ -1 +1,142 @@
-// telegram bot placeholder
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::infra::telegram::rate_limit::RateLimiter;
+
+// сколько раз повторяем после 429, прежде чем вернуть ошибку наверх (в очередь задач)
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+// дольше не ждём внутри вызова: пусть задача переедет на повтор очереди
+const MAX_RETRY_AFTER: u64 = 60;
+
+#[derive(Debug, Error)]
+pub enum TelegramError {
+    #[error("telegram bot is not configured")]
+    NotConfigured,
+    // пользователь заблокировал бота или удалил аккаунт — писать ему бесполезно
+    #[error("telegram user is unreachable: {0}")]
+    Blocked(String),
+    #[error("telegram rate limit, retry after {0}s")]
+    RateLimited(u64),
+    #[error("telegram api error {status}: {description}")]
+    Api { status: u16, description: String },
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+}
+
+#[derive(Deserialize, Default)]
+struct ResponseParameters {
+    retry_after: Option<u64>,
+}
+
+// Ответ Bot API: ok=false приходит вместе с кодом и описанием ошибки
+#[derive(Deserialize)]
+struct ApiResponse {
+    ok: bool,
+    error_code: Option<u16>,
+    description: Option<String>,
+    parameters: Option<ResponseParameters>,
+}
+
+// Отправка сообщений от имени бота в личные чаты привязанных пользователей.
+// Лимиты Bot API соблюдаются общим для всех клонов RateLimiter
+#[derive(Clone)]
+pub struct TelegramBot {
+    http: reqwest::Client,
+    api_url: String,
+    token: String,
+    limiter: RateLimiter,
+}
+
+impl TelegramBot {
+    pub fn new(http: reqwest::Client, api_url: String, token: String) -> Self {
+        Self { http, api_url, token, limiter: RateLimiter::new() }
+    }
+
+    pub fn is_configured(&self) -> bool {
+        !self.token.is_empty()
+    }
+
+    // Текст в HTML-разметке (см. utils::template). Для личного чата chat_id совпадает с telegram_user_id
+    pub async fn send_message(&self, chat_id: i64, html: &str) -> Result<(), TelegramError> {
+        if !self.is_configured() {
+            return Err(TelegramError::NotConfigured);
+        }
+        let mut retries = 0;
+        loop {
+            self.limiter.acquire(chat_id).await;
+            let resp = self.http
+                .post(format!("{}/bot{}/sendMessage", self.api_url, self.token))
+                .json(&json!({
+                    "chat_id": chat_id,
+                    "text": html,
+                    "parse_mode": "HTML",
+                    "disable_web_page_preview": true,
+                }))
+                .send()
+                .await?;
+            let status = resp.status().as_u16();
+            let body: ApiResponse = resp.json().await?;
+            if body.ok {
+                return Ok(());
+            }
+
+            let code = body.error_code.unwrap_or(status);
+            let description = body.description.unwrap_or_default();
+            match code {
+                429 => {
+                    let after = body.parameters.unwrap_or_default().retry_after.unwrap_or(1);
+                    if retries >= MAX_RATE_LIMIT_RETRIES || after > MAX_RETRY_AFTER {
+                        return Err(TelegramError::RateLimited(after));
+                    }
+                    retries += 1;
+                    tracing::debug!(chat_id, retry_after = after, "telegram rate limit hit");
+                    tokio::time::sleep(Duration::from_secs(after)).await;
+                }
+                403 => return Err(TelegramError::Blocked(description)),
+                400 if description.contains("chat not found") => return Err(TelegramError::Blocked(description)),
+                _ => return Err(TelegramError::Api { status: code, description }),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    use axum::{extract::State, routing::post, Json, Router};
+
+    // Поддельный Bot API: первый запрос получает 429, чат 403 заблокировал бота
+    async fn fake_api() -> String {
+        async fn send(State(calls): State<Arc<AtomicU32>>, Json(body): Json<serde_json::Value>) -> Json<serde_json::Value> {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            Json(match body["chat_id"].as_i64() {
+                Some(403) => json!({ "ok": false, "error_code": 403, "description": "Forbidden: bot was blocked by the user" }),
+                _ if n == 0 => json!({ "ok": false, "error_code": 429, "description": "Too Many Requests", "parameters": { "retry_after": 0 } }),
+                _ => json!({ "ok": true, "result": {} }),
+            })
+        }
+        let app = Router::new()
+            .route("/bottest/sendMessage", post(send))
+            .with_state(Arc::new(AtomicU32::new(0)));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn retries_after_429_and_detects_blocked_users() {
+        let bot = TelegramBot::new(reqwest::Client::new(), fake_api().await, "test".into());
+        assert!(bot.send_message(1, "hi").await.is_ok());
+        assert!(matches!(bot.send_message(403, "hi").await, Err(TelegramError::Blocked(_))));
+
+        let off = TelegramBot::new(reqwest::Client::new(), "http://unused".into(), String::new());
+        assert!(matches!(off.send_message(1, "hi").await, Err(TelegramError::NotConfigured)));
+    }
+}
@@ -1,2 +1,3 @@
 pub mod bot;
+pub mod rate_limit;
 pub mod webhook_auth;
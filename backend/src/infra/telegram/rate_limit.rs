@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+// Лимиты Bot API: не больше 30 сообщений в секунду всего и одного в секунду в один чат
+pub const GLOBAL_INTERVAL: Duration = Duration::from_millis(1000 / 30 + 1);
+pub const CHAT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct Slots {
+    next_global: Option<Instant>,
+    next_by_chat: HashMap<i64, Instant>,
+}
+
+// Раздаёт каждому сообщению момент отправки с учётом обоих лимитов.
+// Слот резервируется под мьютексом, а ждут его уже без блокировки
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+    slots: Arc<Mutex<Slots>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self { Self::default() }
+
+    pub async fn acquire(&self, chat_id: i64) {
+        let at = self.reserve(chat_id, Instant::now());
+        tokio::time::sleep_until(at).await;
+    }
+
+    fn reserve(&self, chat_id: i64, now: Instant) -> Instant {
+        let mut s = self.slots.lock().expect("rate limiter mutex poisoned");
+        let mut at = now;
+        if let Some(g) = s.next_global {
+            at = at.max(g);
+        }
+        if let Some(&c) = s.next_by_chat.get(&chat_id) {
+            at = at.max(c);
+        }
+        s.next_global = Some(at + GLOBAL_INTERVAL);
+        // прошедшие слоты чатов больше ничего не ограничивают
+        if s.next_by_chat.len() > 1024 {
+            s.next_by_chat.retain(|_, t| *t > now);
+        }
+        s.next_by_chat.insert(chat_id, at + CHAT_INTERVAL);
+        at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spaces_messages_globally_and_per_chat() {
+        let rl = RateLimiter::new();
+        let t0 = Instant::now();
+        assert_eq!(rl.reserve(1, t0), t0);
+        assert_eq!(rl.reserve(2, t0), t0 + GLOBAL_INTERVAL);
+        // второе сообщение в тот же чат — не раньше чем через секунду после первого
+        assert_eq!(rl.reserve(1, t0), t0 + CHAT_INTERVAL);
+        // после паузы ограничений нет
+        let later = t0 + Duration::from_secs(5);
+        assert_eq!(rl.reserve(1, later), later);
+    }
+}
@@ -23,6 +23,13 @@ async fn main() -> anyhow::Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    // служебная команда: подбор параметров Argon2 под эту машину, сервер не запускается
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("argon2-tune") {
+        let target_ms = args.get(2).and_then(|s| s.parse::<u64>().ok()).unwrap_or(100);
+        return infra::security::password::tune(std::time::Duration::from_millis(target_ms));
+    }
+
     let cfg = config::Config::from_env();
 
     let app_state = state::AppState::init_with(cfg.clone()).await?;
@@ -1,26 +1,290 @@
-use tower::{Layer, Service};
-use http::Request;
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
 use std::task::{Context, Poll};
 
-#[derive(Clone, Default)]
-pub struct IdempotencyLayer;
-impl IdempotencyLayer { pub fn new() -> Self { Self } }
+use axum::body::{to_bytes, Body};
+use axum::response::{IntoResponse, Response};
+use http::{header, HeaderValue, Method, Request};
+use sha2::{Digest, Sha256};
+use time::{Duration, OffsetDateTime};
+use tower::{Layer, Service};
+
+use crate::error::ApiError;
+use crate::infra::repositories::idempotency_repo::{IdempotencyClaim, IdempotencyKey, IdempotencyRepository};
+use crate::infra::security::jwt::TokenService;
+
+pub const IDEMPOTENCY_KEY: &str = "idempotency-key";
+pub const REPLAYED: &str = "idempotent-replayed";
 
+// ответы входа, refresh и регистрации содержат токены — в таблице им не место
+const AUTH_PREFIX: &str = "/api/v1/auth/";
+const MAX_KEY_LEN: usize = 255;
+const MAX_BODY: usize = 1024 * 1024;
+// сколько считаем исходный запрос живым; после — ключ может перехватить повтор
+const LOCK_TTL: Duration = Duration::seconds(60);
+
+// Idempotency-Key для POST/PATCH: первый запрос выполняется и его ответ сохраняется,
+// повтор с тем же ключом и телом получает сохранённый ответ без повторного выполнения.
+// Ключ действует в рамках пользователя (из Bearer-токена), метода и пути
 #[derive(Clone)]
-pub struct IdempotencyMiddleware<S> { inner: S }
+pub struct IdempotencyLayer<R> {
+    repo: R,
+    tokens: TokenService,
+}
+impl<R> IdempotencyLayer<R> {
+    pub fn new(repo: R, tokens: TokenService) -> Self { Self { repo, tokens } }
+}
 
-impl<S> Layer<S> for IdempotencyLayer {
-    type Service = IdempotencyMiddleware<S>;
-    fn layer(&self, inner: S) -> Self::Service { IdempotencyMiddleware { inner } }
+#[derive(Clone)]
+pub struct IdempotencyMiddleware<S, R> {
+    inner: S,
+    repo: R,
+    tokens: TokenService,
+}
+
+impl<S, R: Clone> Layer<S> for IdempotencyLayer<R> {
+    type Service = IdempotencyMiddleware<S, R>;
+    fn layer(&self, inner: S) -> Self::Service {
+        IdempotencyMiddleware { inner, repo: self.repo.clone(), tokens: self.tokens.clone() }
+    }
 }
 
-impl<S, B> Service<Request<B>> for IdempotencyMiddleware<S>
-where S: Service<Request<B>> {
-    type Response = S::Response;
-    type Error = S::Error;
-    type Future = S::Future;
+impl<S, R> Service<Request<Body>> for IdempotencyMiddleware<S, R>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    R: IdempotencyRepository + Clone + Send + Sync + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> { self.inner.poll_ready(cx) }
-    fn call(&mut self, req: Request<B>) -> Self::Future {
-        self.inner.call(req)
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // готовый к вызову inner забираем себе, на его место — свежий клон
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let repo = self.repo.clone();
+        let tokens = self.tokens.clone();
+        Box::pin(async move {
+            let applies = matches!(*req.method(), Method::POST | Method::PATCH)
+                && req.headers().contains_key(IDEMPOTENCY_KEY)
+                && !req.uri().path().starts_with(AUTH_PREFIX);
+            if !applies {
+                return inner.call(req).await;
+            }
+            Ok(handle(inner, repo, tokens, req).await.unwrap_or_else(|e| e.into_response()))
+        })
+    }
+}
+
+async fn handle<S, R>(mut inner: S, repo: R, tokens: TokenService, req: Request<Body>) -> Result<Response, ApiError>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Send,
+    R: IdempotencyRepository,
+{
+    let key = req.headers().get(IDEMPOTENCY_KEY)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|k| !k.is_empty() && k.len() <= MAX_KEY_LEN)
+        .ok_or_else(|| ApiError::BadRequest(format!("Idempotency-Key must be 1..={MAX_KEY_LEN} visible characters")))?
+        .to_string();
+    // невалидный токен не ошибка здесь: ключ просто будет анонимным, а 401 вернёт сам хендлер
+    let user_id = req.headers().get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .and_then(|t| tokens.validate_token(t).ok())
+        .map(|c| c.user_id);
+    let scope = IdempotencyKey {
+        key,
+        user_id,
+        method: req.method().to_string(),
+        path: req.uri().path().to_string(),
+    };
+
+    let (parts, body) = req.into_parts();
+    let bytes = to_bytes(body, MAX_BODY).await
+        .map_err(|_| ApiError::BadRequest("request body is too large".into()))?;
+    let hash = Sha256::digest(&bytes);
+
+    let now = OffsetDateTime::now_utc();
+    match repo.claim(&scope, &hash, now, now + LOCK_TTL).await? {
+        IdempotencyClaim::Started => {}
+        IdempotencyClaim::InFlight => {
+            return Err(ApiError::Conflict("a request with this Idempotency-Key is still being processed".into()));
+        }
+        IdempotencyClaim::Mismatch => {
+            return Err(ApiError::Unprocessable("Idempotency-Key was already used with a different request body".into()));
+        }
+        IdempotencyClaim::Replay { status, body } => return Ok(replay(status, body)),
+    }
+
+    let resp = match inner.call(Request::from_parts(parts, Body::from(bytes))).await {
+        Ok(resp) => resp,
+        Err(never) => match never {},
+    };
+    let (parts, body) = resp.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(b) => b,
+        Err(e) => {
+            repo.release(&scope).await?;
+            return Err(ApiError::Internal(e.to_string()));
+        }
+    };
+
+    // сохраняем только осмысленные JSON-ответы; 5xx и прочее можно честно повторить
+    let stored = if parts.status.is_server_error() {
+        None
+    } else if bytes.is_empty() {
+        Some(None)
+    } else {
+        serde_json::from_slice::<serde_json::Value>(&bytes).ok().map(Some)
+    };
+    match stored {
+        Some(json) => repo.complete(&scope, parts.status.as_u16() as i32, json, OffsetDateTime::now_utc()).await?,
+        None => repo.release(&scope).await?,
+    }
+    Ok(Response::from_parts(parts, Body::from(bytes)))
+}
+
+fn replay(status: i32, body: Option<serde_json::Value>) -> Response {
+    let status = http::StatusCode::from_u16(status as u16).unwrap_or(http::StatusCode::OK);
+    let mut resp = match body {
+        Some(json) => (status, axum::Json(json)).into_response(),
+        None => status.into_response(),
+    };
+    resp.headers_mut().insert(REPLAYED, HeaderValue::from_static("true"));
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use axum::{routing::post, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::infra::errors::RepoResult;
+    use crate::infra::security::jwt::TokenConfig;
+
+    type Scope = (String, Option<uuid::Uuid>, String, String);
+    // (хеш тела, сохранённый ответ); без ответа — запрос ещё выполняется
+    type Entry = (Vec<u8>, Option<(i32, Option<serde_json::Value>)>);
+
+    #[derive(Clone, Default)]
+    struct MemRepo(Arc<Mutex<HashMap<Scope, Entry>>>);
+
+    fn scope(k: &IdempotencyKey) -> Scope {
+        (k.key.clone(), k.user_id, k.method.clone(), k.path.clone())
+    }
+
+    #[async_trait]
+    impl IdempotencyRepository for MemRepo {
+        async fn claim(&self, k: &IdempotencyKey, hash: &[u8], _: OffsetDateTime, _: OffsetDateTime) -> RepoResult<IdempotencyClaim> {
+            let mut m = self.0.lock().unwrap();
+            Ok(match m.get(&scope(k)) {
+                None => {
+                    m.insert(scope(k), (hash.to_vec(), None));
+                    IdempotencyClaim::Started
+                }
+                Some((h, _)) if h.as_slice() != hash => IdempotencyClaim::Mismatch,
+                Some((_, None)) => IdempotencyClaim::InFlight,
+                Some((_, Some((status, body)))) => IdempotencyClaim::Replay { status: *status, body: body.clone() },
+            })
+        }
+        async fn complete(&self, k: &IdempotencyKey, status: i32, body: Option<serde_json::Value>, _: OffsetDateTime) -> RepoResult<()> {
+            if let Some(e) = self.0.lock().unwrap().get_mut(&scope(k)) {
+                e.1 = Some((status, body));
+            }
+            Ok(())
+        }
+        async fn release(&self, k: &IdempotencyKey) -> RepoResult<()> {
+            self.0.lock().unwrap().remove(&scope(k));
+            Ok(())
+        }
+        async fn purge(&self, _: OffsetDateTime) -> RepoResult<u64> { Ok(0) }
+    }
+
+    // каждый вызов хендлера увеличивает счётчик и возвращает его значение
+    fn app(repo: MemRepo, calls: Arc<AtomicUsize>) -> Router {
+        let handler = move || {
+            let calls = calls.clone();
+            async move {
+                let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                (http::StatusCode::CREATED, axum::Json(serde_json::json!({ "n": n })))
+            }
+        };
+        Router::new()
+            .route("/api/v1/things", post(handler.clone()))
+            .route("/api/v1/auth/login", post(handler))
+            .layer(IdempotencyLayer::new(repo, TokenService::new(TokenConfig::from_env())))
+    }
+
+    fn request(path: &str, key: &str, body: &str) -> Request<Body> {
+        Request::post(path)
+            .header(IDEMPOTENCY_KEY, key)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_owned()))
+            .unwrap()
+    }
+
+    async fn json(resp: Response) -> serde_json::Value {
+        serde_json::from_slice(&to_bytes(resp.into_body(), usize::MAX).await.unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn repeat_with_same_key_replays_stored_response() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(MemRepo::default(), calls.clone());
+
+        let first = app.clone().oneshot(request("/api/v1/things", "k1", "{}")).await.unwrap();
+        assert_eq!(first.status(), http::StatusCode::CREATED);
+        assert!(first.headers().get(REPLAYED).is_none());
+
+        let again = app.oneshot(request("/api/v1/things", "k1", "{}")).await.unwrap();
+        assert_eq!(again.status(), http::StatusCode::CREATED);
+        assert_eq!(again.headers().get(REPLAYED).unwrap(), "true");
+        assert_eq!(json(again).await, serde_json::json!({ "n": 1 }));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn key_still_in_flight_is_a_conflict() {
+        let (repo, calls) = (MemRepo::default(), Arc::new(AtomicUsize::new(0)));
+        let k = IdempotencyKey { key: "k2".into(), user_id: None, method: "POST".into(), path: "/api/v1/things".into() };
+        let now = OffsetDateTime::now_utc();
+        repo.claim(&k, &Sha256::digest(b"{}"), now, now + LOCK_TTL).await.unwrap();
+
+        let resp = app(repo, calls.clone()).oneshot(request("/api/v1/things", "k2", "{}")).await.unwrap();
+        assert_eq!(resp.status(), http::StatusCode::CONFLICT);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn same_key_with_other_body_is_rejected() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(MemRepo::default(), calls.clone());
+
+        app.clone().oneshot(request("/api/v1/things", "k3", r#"{"a":1}"#)).await.unwrap();
+        let resp = app.oneshot(request("/api/v1/things", "k3", r#"{"a":2}"#)).await.unwrap();
+        assert_eq!(resp.status(), http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn auth_responses_are_not_stored() {
+        let (repo, calls) = (MemRepo::default(), Arc::new(AtomicUsize::new(0)));
+        let app = app(repo.clone(), calls.clone());
+
+        app.clone().oneshot(request("/api/v1/auth/login", "k4", "{}")).await.unwrap();
+        app.oneshot(request("/api/v1/auth/login", "k4", "{}")).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(repo.0.lock().unwrap().is_empty());
     }
 }
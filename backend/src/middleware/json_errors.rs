@@ -1,7 +1,19 @@
-use tower::{Layer, Service};
-use http::Request;
+use std::future::Future;
+use std::pin::Pin;
 use std::task::{Context, Poll};
 
+use axum::body::to_bytes;
+use axum::response::Response;
+use http::{header, Request};
+use tower::{Layer, Service};
+
+use crate::error::{error_response, status_code_name};
+
+// текст отказа axum короткий; всё, что длиннее, не показываем
+const MAX_TEXT: usize = 16 * 1024;
+
+// Приводит все ответы 4xx/5xx, которые не являются JSON, к формату ApiError:
+// отказы экстракторов (Json, Path, Query), 404 неизвестного маршрута, 405
 #[derive(Clone, Default)]
 pub struct JsonErrorLayer;
 impl JsonErrorLayer { pub fn new() -> Self { Self } }
@@ -15,10 +27,144 @@ impl<S> Layer<S> for JsonErrorLayer {
 }
 
 impl<S, B> Service<Request<B>> for JsonErrorMiddleware<S>
-where S: Service<Request<B>> {
-    type Response = S::Response;
+where
+    S: Service<Request<B>, Response = Response>,
+    S::Future: Send + 'static,
+    S::Error: 'static,
+{
+    type Response = Response;
     type Error = S::Error;
-    type Future = S::Future;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> { self.inner.poll_ready(cx) }
-    fn call(&mut self, req: Request<B>) -> Self::Future { self.inner.call(req) }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let fut = self.inner.call(req);
+        Box::pin(async move {
+            let resp = fut.await?;
+            let status = resp.status();
+            if !(status.is_client_error() || status.is_server_error()) || is_json(&resp) {
+                return Ok(resp);
+            }
+
+            let (parts, body) = resp.into_parts();
+            let text = match to_bytes(body, MAX_TEXT).await {
+                Ok(b) => String::from_utf8_lossy(&b).trim().to_string(),
+                Err(_) => String::new(),
+            };
+            let reason = status.canonical_reason().unwrap_or("Error").to_string();
+            // у 5xx текст может содержать внутренности — клиенту не отдаём
+            let message = if status.is_server_error() {
+                if !text.is_empty() {
+                    tracing::error!(status = status.as_u16(), error = %text, "non-json server error");
+                }
+                reason
+            } else if text.is_empty() {
+                reason
+            } else {
+                text
+            };
+
+            let mut out = error_response(status, status_code_name(status), &message);
+            // сохраняем служебные заголовки исходного ответа (Allow у 405, WWW-Authenticate и т.п.)
+            for (name, value) in parts.headers.iter() {
+                if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+                    out.headers_mut().insert(name.clone(), value.clone());
+                }
+            }
+            Ok(out)
+        })
+    }
+}
+
+fn is_json(resp: &Response) -> bool {
+    resp.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json") || ct.contains("+json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::extract::{Path, Query};
+    use axum::{routing::{get, post}, Json, Router};
+    use http::StatusCode;
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::error::ApiError;
+
+    #[derive(Deserialize)]
+    struct Page { #[allow(dead_code)] page: u32 }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/items/:id", get(|Path(_): Path<uuid::Uuid>| async { "ok" }))
+            .route("/items", post(|Json(_): Json<serde_json::Value>| async { "ok" }))
+            .route("/list", get(|Query(_): Query<Page>| async { "ok" }))
+            .route("/boom", get(|| async { Err::<(), _>(ApiError::Internal("db password=secret".into())) }))
+            .layer(JsonErrorLayer::new())
+    }
+
+    async fn call(req: Request<Body>) -> (StatusCode, Option<http::HeaderValue>, serde_json::Value) {
+        let resp = app().oneshot(req).await.unwrap();
+        let status = resp.status();
+        let allow = resp.headers().get(header::ALLOW).cloned();
+        assert!(is_json(&resp), "{status}: ответ не JSON");
+        let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        (status, allow, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn get_req(uri: &str) -> Request<Body> {
+        Request::get(uri).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn unknown_route_is_json_404() {
+        let (status, _, body) = call(get_req("/nope")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "NOT_FOUND");
+        assert_eq!(body["error"]["message"], "Not Found");
+    }
+
+    #[tokio::test]
+    async fn wrong_method_is_json_405_and_keeps_allow() {
+        let (status, allow, body) = call(Request::delete("/items").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(body["error"]["code"], "METHOD_NOT_ALLOWED");
+        assert!(allow.is_some());
+    }
+
+    #[tokio::test]
+    async fn bad_json_body_is_json_error() {
+        let req = Request::post("/items")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from("{not json"))
+            .unwrap();
+        let (status, _, body) = call(req).await;
+        assert!(status.is_client_error());
+        assert_eq!(body["error"]["code"], status_code_name(status));
+        assert!(!body["error"]["message"].as_str().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn bad_path_and_query_are_json_400() {
+        for uri in ["/items/abc", "/list?page=-1"] {
+            let (status, _, body) = call(get_req(uri)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+            assert_eq!(body["error"]["code"], "BAD_REQUEST", "{uri}");
+            assert!(!body["error"]["message"].as_str().unwrap().is_empty(), "{uri}");
+        }
+    }
+
+    #[tokio::test]
+    async fn internal_error_hides_detail() {
+        let (status, _, body) = call(get_req("/boom")).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["error"]["code"], "INTERNAL");
+        assert_eq!(body["error"]["message"], "Internal server error");
+        assert!(!body.to_string().contains("secret"));
+    }
 }
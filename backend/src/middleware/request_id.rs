@@ -1,8 +1,38 @@
+use std::future::Future;
+use std::pin::Pin;
 use std::task::{Context, Poll};
+
+use http::{HeaderName, HeaderValue, Request, Response};
 use tower::{Layer, Service};
-use http::{Request, HeaderValue};
+use tracing::Instrument;
 use uuid::Uuid;
 
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+const MAX_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT: RequestId;
+}
+
+// Идентификатор запроса: берётся из входящего x-request-id (веб-прокси, бот)
+// или генерируется, возвращается клиенту в ответе и попадает в span и тела ошибок
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    // id обрабатываемого запроса; None вне запроса (воркеры, очередь задач)
+    pub fn current() -> Option<String> {
+        CURRENT.try_with(|r| r.0.clone()).ok()
+    }
+}
+
+// чужой id принимаем, только если он годится для логов и заголовков
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_LEN
+        && id.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'))
+}
+
 #[derive(Clone, Default)]
 pub struct RequestIdLayer;
 impl RequestIdLayer { pub fn new() -> Self { Self } }
@@ -15,15 +45,56 @@ impl<S> Layer<S> for RequestIdLayer {
     fn layer(&self, inner: S) -> Self::Service { RequestIdMiddleware { inner } }
 }
 
-impl<S, B> Service<Request<B>> for RequestIdMiddleware<S>
-where S: Service<Request<B>> {
+impl<S, B, ResBody> Service<Request<B>> for RequestIdMiddleware<S>
+where
+    S: Service<Request<B>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    S::Error: 'static,
+    ResBody: 'static,
+{
     type Response = S::Response;
     type Error = S::Error;
-    type Future = S::Future;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> { self.inner.poll_ready(cx) }
+
     fn call(&mut self, mut req: Request<B>) -> Self::Future {
-        let rid = Uuid::new_v4().to_string();
-        req.headers_mut().insert("x-request-id", HeaderValue::from_str(&rid).unwrap());
-        self.inner.call(req)
+        let rid = req.headers().get(&X_REQUEST_ID)
+            .and_then(|v| v.to_str().ok())
+            .filter(|v| is_valid(v))
+            .map(str::to_owned)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        // в id только ASCII из is_valid или UUID — заголовок всегда корректен
+        let value = HeaderValue::from_str(&rid).unwrap();
+        req.headers_mut().insert(X_REQUEST_ID, value.clone());
+        req.extensions_mut().insert(RequestId(rid.clone()));
+
+        // метод и путь добавит вложенный span TraceLayer
+        let span = tracing::info_span!("request", request_id = %rid);
+        let fut = span.in_scope(|| self.inner.call(req));
+        Box::pin(
+            CURRENT
+                .scope(RequestId(rid), async move {
+                    let mut resp = fut.await?;
+                    resp.headers_mut().insert(X_REQUEST_ID, value);
+                    Ok(resp)
+                })
+                .instrument(span),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_only_safe_ids() {
+        assert!(is_valid("2f1c9a1e-3b7d-4c55-9d0e-1a2b3c4d5e6f"));
+        assert!(is_valid("bot:42.abc_1"));
+        assert!(!is_valid(""));
+        assert!(!is_valid("has space"));
+        assert!(!is_valid("line\nbreak"));
+        assert!(!is_valid(&"a".repeat(MAX_LEN + 1)));
     }
 }
@@ -1,9 +1,12 @@
 use crate::api::requests::{login::LoginRequest, refresh::RefreshRequest};
-use axum::{Router, routing::post, extract::State, Json};
+use crate::api::requests::password::{ForgotPasswordRequest, ResetPasswordRequest};
+use crate::api::requests::verify_email::VerifyEmailRequest;
+use axum::{Router, routing::post, extract::State, http::HeaderMap, Json};
 use crate::{state::AppState, error::ApiResult};
 use crate::api::requests::student_register::StudentRegisterRequest;
 use crate::api::models::auth::{RegisterOut, LoginOut};
 use crate::api::requests::manager_register::ManagerRegisterRequest;
+use crate::infra::security::session::ClientInfo;
 use crate::utils::token::TokenDTO;
 
 pub fn router(state: AppState) -> Router {
@@ -13,44 +16,83 @@ pub fn router(state: AppState) -> Router {
         .route("/api/v1/auth/refresh",  post(refresh))
         .route("/api/v1/auth/register/student", post(register_student))
         .route("/api/v1/auth/register/manager", post(register_manager))
+        .route("/api/v1/auth/password/forgot",  post(forgot_password))
+        .route("/api/v1/auth/password/reset",   post(reset_password))
+        .route("/api/v1/auth/verify-email",     post(verify_email))
         .with_state(state)
 }
 
-async fn login(State(st): State<AppState>, Json(body): Json<LoginRequest>)
+async fn login(State(st): State<AppState>, headers: HeaderMap, Json(body): Json<LoginRequest>)
                -> ApiResult<Json<LoginOut>>
 {
-    let out = st.auth_service.login(body).await?;
+    let client = ClientInfo::new(body.device.clone(), &headers);
+    let out = st.auth_service.login(body, client).await?;
     Ok(Json(out))
 }
 
 async fn logout(State(st): State<AppState>, user: crate::auth::extractor::AuthUser)
                 -> ApiResult<()>
 {
-    st.auth_service.logout(user.user_id).await?;
+    st.auth_service.logout(user.user_id, user.session_id).await?;
     Ok(())
 }
 
-async fn refresh(State(st): State<AppState>, Json(body): Json<RefreshRequest>)
+async fn refresh(State(st): State<AppState>, headers: HeaderMap, Json(body): Json<RefreshRequest>)
                  -> ApiResult<Json<TokenDTO>>
 {
-    let out = st.auth_service.refresh_by_token(body).await?;
+    let out = st.auth_service.refresh_by_token(body, ClientInfo::new(None, &headers)).await?;
     Ok(Json(out))
 }
 
 async fn register_student(
     State(st): State<AppState>,
+    headers: HeaderMap,
     Json(body): Json<StudentRegisterRequest>
 ) -> ApiResult<(axum::http::StatusCode, Json<RegisterOut>)>
 {
-    let out = st.auth_service.register_student(body).await?;
+    let client = ClientInfo::new(body.device.clone(), &headers);
+    let out = st.auth_service.register_student(body, client).await?;
+    send_verification(&st, out.user.id).await;
     Ok((axum::http::StatusCode::CREATED, Json(out)))
 }
 
 async fn register_manager(
     State(st): State<AppState>,
+    headers: HeaderMap,
     Json(body): Json<ManagerRegisterRequest>
 ) -> ApiResult<(axum::http::StatusCode, Json<RegisterOut>)>
 {
-    let out = st.auth_service.register_manager(body).await?;
+    let client = ClientInfo::new(body.device.clone(), &headers);
+    let out = st.auth_service.register_manager(body, client).await?;
+    send_verification(&st, out.user.id).await;
     Ok((axum::http::StatusCode::CREATED, Json(out)))
-}
\ No newline at end of file
+}
+
+// 202 при любом email, чтобы по ответу нельзя было проверить, есть ли такой аккаунт
+async fn forgot_password(State(st): State<AppState>, Json(body): Json<ForgotPasswordRequest>)
+                         -> ApiResult<axum::http::StatusCode>
+{
+    st.auth_service.forgot_password(body).await?;
+    Ok(axum::http::StatusCode::ACCEPTED)
+}
+
+async fn reset_password(State(st): State<AppState>, Json(body): Json<ResetPasswordRequest>)
+                        -> ApiResult<()>
+{
+    let user_id = st.auth_service.reset_password(body).await?;
+    st.auth.versions.invalidate(user_id);
+    Ok(())
+}
+
+async fn verify_email(State(st): State<AppState>, Json(body): Json<VerifyEmailRequest>) -> ApiResult<()> {
+    st.verification.verify(&body.token).await?;
+    Ok(())
+}
+
+// аккаунт уже создан: сбой постановки письма не должен превращать регистрацию в ошибку,
+// письмо можно запросить повторно из профиля
+async fn send_verification(st: &AppState, user_id: uuid::Uuid) {
+    if let Err(e) = st.verification.send(user_id).await {
+        tracing::warn!(%user_id, error = ?e, "failed to queue email verification");
+    }
+}
@@ -0,0 +1,82 @@
+use axum::{
+    Router,
+    routing::{get, post},
+    extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::state::AppState;
+use crate::auth::extractor::AuthUser;
+use crate::auth::roles::UserRole;
+use crate::error::{ApiError, ApiResult};
+use crate::infra::security::rbac;
+
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/api/v1/events/:id/ics", get(event_ics))
+        .route("/api/v1/companies/:company_id/events.ics", get(company_ics))
+        .route("/api/v1/me/calendar-feed", post(rotate_feed).delete(revoke_feed))
+        // без JWT: токен в пути и есть доступ, календарные приложения заголовков не шлют
+        .route("/api/v1/calendar/:token/feed.ics", get(student_feed))
+        .with_state(state)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+struct FeedOut {
+    token: String,
+    url: String,
+}
+
+fn ics_response(body: String, filename: &str) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{filename}\"")),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+async fn event_ics(State(st): State<AppState>, user: Option<AuthUser>, Path(id): Path<Uuid>)
+    -> ApiResult<Response> {
+    let e = st.events.get(id).await?;
+    if !e.is_published {
+        let u = user.ok_or(ApiError::Forbidden)?;
+        rbac::require_dean_or_company_manager(&u, e.company_id)?;
+    }
+    let body = st.calendar.event_ics(id).await?;
+    Ok(ics_response(body, &format!("event-{id}.ics")))
+}
+
+// лента компании публичная, как и список её опубликованных событий
+async fn company_ics(State(st): State<AppState>, Path(company_id): Path<Uuid>)
+    -> ApiResult<Response> {
+    let body = st.calendar.company_ics(company_id).await?;
+    Ok(ics_response(body, &format!("company-{company_id}.ics")))
+}
+
+async fn student_feed(State(st): State<AppState>, Path(token): Path<String>)
+    -> ApiResult<Response> {
+    let body = st.calendar.student_ics(&token).await?;
+    Ok(ics_response(body, "my-events.ics"))
+}
+
+async fn rotate_feed(State(st): State<AppState>, user: AuthUser)
+    -> ApiResult<Json<FeedOut>> {
+    rbac::require_role(&user, &[UserRole::Student])?;
+    let token = st.calendar.rotate_feed_token(user.user_id).await?;
+    let url = format!("{}/api/v1/calendar/{token}/feed.ics", st.config.public_base_url);
+    Ok(Json(FeedOut { token, url }))
+}
+
+async fn revoke_feed(State(st): State<AppState>, user: AuthUser)
+    -> ApiResult<()> {
+    rbac::require_role(&user, &[UserRole::Student])?;
+    st.calendar.revoke_feed_token(user.user_id).await
+}
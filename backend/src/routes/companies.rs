@@ -154,5 +154,6 @@ async fn set_manager_status(
         ManagerStatusParam::Rejected => DManagerStatus::Rejected,
     };
     st.managers.set_status(company_id, user_id, target).await?;
+    st.auth.versions.invalidate(user_id);
     Ok(())
 }
@@ -0,0 +1,32 @@
+use axum::{extract::State, routing::get, Json, Router};
+
+use crate::{
+    api::models::settings::SettingsOut,
+    api::requests::settings::UpdateSettingsIn,
+    auth::extractor::AuthUser,
+    error::ApiResult,
+    infra::security::rbac,
+    state::AppState,
+};
+
+// Общие настройки деканата (сейчас — требование проверенного email для подтверждения студента)
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/api/v1/dean/settings", get(get_settings).patch(update_settings))
+        .with_state(state)
+}
+
+async fn get_settings(State(st): State<AppState>, user: AuthUser) -> ApiResult<Json<SettingsOut>> {
+    rbac::require_dean(&user)?;
+    Ok(Json(st.users.settings().await?.into()))
+}
+
+async fn update_settings(
+    State(st): State<AppState>,
+    user: AuthUser,
+    Json(input): Json<UpdateSettingsIn>,
+) -> ApiResult<Json<SettingsOut>> {
+    rbac::require_dean(&user)?;
+    let settings = st.users.update_settings(user.user_id, input.require_verified_email).await?;
+    Ok(Json(settings.into()))
+}
@@ -28,6 +28,8 @@ struct ListQ {
     page:   Option<i32>,
     limit:  Option<i32>,
     q:      Option<String>, 
+    // true — только с подтверждённым email, false — только с неподтверждённым
+    verified: Option<bool>,
 }
 
 #[derive(serde::Serialize)]
@@ -37,6 +39,7 @@ struct StudentAdminOut {
     name: String,
     email: String,
     status: String, // "created" | "linked" | "confirmed" | "rejected"
+    email_verified: bool,
 }
 
 async fn list_pending_students(
@@ -63,8 +66,8 @@ async fn list_pending_students(
     let limit = q.limit.unwrap_or(50);
     let search = q.q.unwrap_or_default();
 
-    let rows = st.users.list_students_by_status(&statuses, page, limit, &search).await?;
-    let out = rows.into_iter().map(|(u, st)| StudentAdminOut {
+    let rows = st.users.list_students_by_status(&statuses, q.verified, page, limit, &search).await?;
+    let out = rows.into_iter().map(|(u, st, email_verified)| StudentAdminOut {
         id: u.id,
         name: u.name,
         email: u.email,
@@ -74,7 +77,8 @@ async fn list_pending_students(
             Some(StudentStatus::Confirmed) => "confirmed".into(),
             Some(StudentStatus::Rejected)  => "rejected".into(),
             None => "created".into(),
-        }
+        },
+        email_verified,
     }).collect();
 
     Ok(Json(out))
@@ -89,6 +93,7 @@ async fn approve_student(
         return Err(ApiError::Forbidden);
     }
     st.users.set_student_status(student_user_id, StudentStatus::Confirmed).await?;
+    st.auth.versions.invalidate(student_user_id);
     Ok(())
 }
 
@@ -101,5 +106,6 @@ async fn reject_student(
         return Err(ApiError::Forbidden);
     }
     st.users.set_student_status(student_user_id, StudentStatus::Rejected).await?;
+    st.auth.versions.invalidate(student_user_id);
     Ok(())
 }
\ No newline at end of file
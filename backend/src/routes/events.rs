@@ -2,6 +2,8 @@ use axum::{
     Router,
     routing::{get, post},
     extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
@@ -9,14 +11,18 @@ use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::state::AppState;
-use crate::api::models::event::EventOut;
+use crate::api::models::event::{EventOut, EventPageOut, SeriesOut};
 pub(crate) use crate::api::models::registration::RegistrationOut;
-use crate::api::requests::event::{CreateEventIn, UpdateEventIn};
-use crate::infra::repositories::event_repo::EventListFilter;
+use crate::api::models::registration::{CheckInOut, RegistrationStateOut, SeriesRegistrationOut, TicketOut};
+use crate::api::requests::event::{CreateEventIn, CreateSeriesIn, UpdateEventIn};
+use crate::api::requests::registration::{CheckInIn, VerifyTicketIn};
+use crate::domain::entities::event_row::EventStatus;
+use crate::infra::repositories::event_repo::{EventListFilter, EventSort, SortOrder};
 use crate::infra::security::rbac;
 use crate::auth::extractor::AuthUser;
 use crate::auth::roles::{ManagerStatus, UserRole};
 use crate::error::ApiResult;
+use crate::utils::qr;
 
 pub fn router(state: AppState) -> Router {
     Router::new()
@@ -24,27 +30,117 @@ pub fn router(state: AppState) -> Router {
         .route("/api/v1/events/:id", get(get_event).patch(update_event).delete(delete_event))
         .route("/api/v1/events/companies/:company_id", get(list_company_events))
         .route("/api/v1/events/students/:student_id", get(list_student_events))
+        .route("/api/v1/events/series", post(create_series))
+        .route("/api/v1/events/series/:series_id", get(get_series))
+        .route("/api/v1/events/series/:series_id/register", post(register_series))
+        .route("/api/v1/events/series/:series_id/cancel", post(cancel_series_registration))
         .route("/api/v1/events/:id/publish", post(publish_event))
         .route("/api/v1/events/:id/unpublish", post(unpublish_event))
+        .route("/api/v1/events/review-queue", get(review_queue))
+        .route("/api/v1/events/:id/approve", post(approve_event))
+        .route("/api/v1/events/:id/reject", post(reject_event))
+        .route("/api/v1/events/:id/cancellation", post(cancel_event))
         .route("/api/v1/events/:id/deadline", post(update_deadline))
         .route("/api/v1/events/:id/registrations", get(list_registrations))
         .route("/api/v1/events/:id/register", post(register_event))
         .route("/api/v1/events/:id/cancel", post(cancel_registration))
+        .route("/api/v1/events/:id/check-in", post(check_in_batch))
+        .route("/api/v1/events/:id/check-in/:student_id", post(check_in_one))
+        .route("/api/v1/events/:id/ticket", get(my_ticket))
+        .route("/api/v1/events/:id/tickets/verify", post(verify_ticket))
         .with_state(state)
 }
 
 #[derive(Deserialize)]
 struct ListQ {
-    page: Option<i32>,
-    limit: Option<i32>,
+    cursor: Option<String>,
+    limit: Option<i64>,
     q: Option<String>,
     company_id: Option<Uuid>,
     manager_id: Option<Uuid>,
     published: Option<bool>,
-    // #[serde(with = "time::serde::rfc3339::option")]
-    // from: Option<OffsetDateTime>,
-    // #[serde(with = "time::serde::rfc3339::option")]
-    // to: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    from: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    to: Option<OffsetDateTime>,
+    when: Option<TimeWindow>,
+    sort: Option<EventSort>,
+    order: Option<SortOrder>,
+    // через запятую: tags=backend,ml — нужны все
+    tags: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum TimeWindow {
+    Upcoming,
+    Past,
+}
+
+impl ListQ {
+    fn filter(&self) -> EventListFilter {
+        let now = OffsetDateTime::now_utc();
+        EventListFilter {
+            company_id: self.company_id,
+            manager_id: self.manager_id,
+            published: self.published,
+            q: self.q.clone(),
+            from: self.from,
+            to: self.to,
+            ends_after: (self.when == Some(TimeWindow::Upcoming)).then_some(now),
+            ends_before: (self.when == Some(TimeWindow::Past)).then_some(now),
+            tags: self.tag_slugs(),
+        }
+    }
+
+    // фильтр требует все теги сразу и сверяет их число — повторы бы его обнулили
+    fn tag_slugs(&self) -> Vec<String> {
+        let mut slugs: Vec<String> = self.tags
+            .as_deref()
+            .map(|v| v.split(',').map(str::trim).filter(|t| !t.is_empty()).map(str::to_string).collect())
+            .unwrap_or_default();
+        slugs.sort();
+        slugs.dedup();
+        slugs
+    }
+
+    // ближайшие события естественно смотреть с начала, остальное — свежее сверху
+    fn order(&self) -> SortOrder {
+        self.order.unwrap_or(match self.when {
+            Some(TimeWindow::Upcoming) => SortOrder::Asc,
+            _ => SortOrder::Desc,
+        })
+    }
+
+    async fn fetch(&self, st: &AppState, f: EventListFilter) -> ApiResult<EventPageOut> {
+        st.events
+            .list(f, self.sort.unwrap_or_default(), self.order(), self.cursor.as_deref(), self.limit.unwrap_or(20))
+            .await
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum TicketFormat {
+    #[default]
+    Json,
+    Svg,
+    Png,
+}
+
+#[derive(Deserialize)]
+struct TicketQ {
+    format: Option<TicketFormat>,
+}
+
+#[derive(Deserialize)]
+struct RejectIn {
+    reason: String,
+}
+
+#[derive(serde::Deserialize)]
+struct CancelEventIn {
+    reason: String,
 }
 
 #[derive(serde::Deserialize)]
@@ -67,21 +163,14 @@ pub struct RegistrationRow {
 }
 
 async fn list_events(State(st): State<AppState>, user: Option<AuthUser>, q: Query<ListQ>)
-    -> ApiResult<Json<Vec<EventOut>>> {
-    let mut f = EventListFilter {
-        company_id: q.company_id,
-        manager_id: q.manager_id,
-        published: q.published,
-        q: q.q.clone(),
-        // from: q.from,
-        // to: q.to,
-    };
+    -> ApiResult<Json<EventPageOut>> {
+    let mut f = q.filter();
 
     if must_filter_to_published(user.as_ref(), f.company_id) {
         f.published = Some(true);
     }
 
-    Ok(Json(st.events.list(q.page.unwrap_or(1), q.limit.unwrap_or(20), f).await?))
+    Ok(Json(q.fetch(&st, f).await?))
 }
 
 async fn create_event(State(st): State<AppState>, user: AuthUser, Json(body): Json<CreateEventIn>)
@@ -95,20 +184,58 @@ async fn create_event(State(st): State<AppState>, user: AuthUser, Json(body): Js
     Ok((http::StatusCode::CREATED, Json(e)))
 }
 
+async fn create_series(State(st): State<AppState>, user: AuthUser, Json(body): Json<CreateSeriesIn>)
+    -> ApiResult<(http::StatusCode, Json<SeriesOut>)> {
+    rbac::require_manager_confirmed(&user)?;
+
+    let company_id = user.company_id.ok_or(crate::error::ApiError::Forbidden)?;
+    let s = st.events.create_series(body, company_id, user.user_id).await?;
+    Ok((http::StatusCode::CREATED, Json(s)))
+}
+
+async fn get_series(State(st): State<AppState>, user: Option<AuthUser>, Path(series_id): Path<Uuid>)
+    -> ApiResult<Json<SeriesOut>> {
+    let mut s = st.events.get_series(series_id).await?;
+    if must_filter_to_published(user.as_ref(), Some(s.company_id)) {
+        s.events.retain(|e| e.is_published);
+        if s.events.is_empty() {
+            return Err(crate::error::ApiError::NotFound);
+        }
+    }
+    Ok(Json(s))
+}
+
+async fn register_series(State(st): State<AppState>, user: AuthUser, Path(series_id): Path<Uuid>)
+    -> ApiResult<Json<Vec<SeriesRegistrationOut>>> {
+    rbac::require_student_confirmed(&user)?;
+    Ok(Json(st.events.register_series(series_id, user.user_id).await?))
+}
+
+async fn cancel_series_registration(State(st): State<AppState>, user: AuthUser, Path(series_id): Path<Uuid>)
+    -> ApiResult<()> {
+    rbac::require_student_confirmed(&user)?;
+    st.events.cancel_series_registration(series_id, user.user_id).await
+}
+
 async fn get_event(State(st): State<AppState>, user: Option<AuthUser>, Path(id): Path<Uuid>)
     -> ApiResult<Json<EventOut>> {
-    let e = st.events.get(id).await?;
+    let e = match user.as_ref() {
+        Some(u) if u.role == UserRole::Student => st.events.get_for_student(id, u.user_id).await?,
+        _ => st.events.get(id).await?,
+    };
 
+    // отменённое видно ещё и тем студентам, у кого была запись: оно остаётся в их истории
     if !e.is_published {
-        if let Some(u) = user {
-            let allowed = u.role == UserRole::Dean
+        let allowed = user.as_ref().is_some_and(|u| {
+            u.role == UserRole::Dean
                 || (u.role == UserRole::Manager
                 && u.company_id == Some(e.company_id)
-                && matches!(u.manager_status, Some(ManagerStatus::Confirmed)));
-            if !allowed {
-                return Err(crate::error::ApiError::Forbidden);
-            }
-        } else {
+                && matches!(u.manager_status, Some(ManagerStatus::Confirmed)))
+                || (u.role == UserRole::Student
+                && e.status == EventStatus::Canceled
+                && e.registration_status.is_some())
+        });
+        if !allowed {
             return Err(crate::error::ApiError::Forbidden);
         }
     }
@@ -120,7 +247,7 @@ async fn update_event(State(st): State<AppState>, user: AuthUser,
     -> ApiResult<Json<EventOut>> {
     let e = st.events.get(id).await?;
     rbac::require_dean_or_company_manager(&user, e.company_id)?;
-    Ok(Json(st.events.update(id, body).await?))
+    Ok(Json(st.events.update(id, body, user.role == UserRole::Dean).await?))
 }
 
 async fn delete_event(State(st): State<AppState>, user: AuthUser, Path(id): Path<Uuid>)
@@ -130,18 +257,46 @@ async fn delete_event(State(st): State<AppState>, user: AuthUser, Path(id): Path
     st.events.delete(id).await
 }
 
+// (/:id/cancel занят отменой записи студентом)
+async fn cancel_event(State(st): State<AppState>, user: AuthUser,
+                      Path(id): Path<Uuid>, Json(body): Json<CancelEventIn>)
+    -> ApiResult<Json<EventOut>> {
+    let e = st.events.get(id).await?;
+    rbac::require_dean_or_company_manager(&user, e.company_id)?;
+    Ok(Json(st.events.cancel(id, user.user_id, &body.reason).await?))
+}
+
 async fn publish_event(State(st): State<AppState>, user: AuthUser, Path(id): Path<Uuid>)
     -> ApiResult<Json<EventOut>> {
     let e = st.events.get(id).await?;
     rbac::require_dean_or_company_manager(&user, e.company_id)?;
-    Ok(Json(st.events.set_published(id, true).await?))
+    Ok(Json(st.events.set_published(id, true, user.role == UserRole::Dean).await?))
 }
 
 async fn unpublish_event(State(st): State<AppState>, user: AuthUser, Path(id): Path<Uuid>)
     -> ApiResult<Json<EventOut>> {
     let e = st.events.get(id).await?;
     rbac::require_dean_or_company_manager(&user, e.company_id)?;
-    Ok(Json(st.events.set_published(id, false).await?))
+    Ok(Json(st.events.set_published(id, false, user.role == UserRole::Dean).await?))
+}
+
+async fn review_queue(State(st): State<AppState>, user: AuthUser)
+    -> ApiResult<Json<Vec<EventOut>>> {
+    rbac::require_dean(&user)?;
+    Ok(Json(st.events.review_queue().await?))
+}
+
+async fn approve_event(State(st): State<AppState>, user: AuthUser, Path(id): Path<Uuid>)
+    -> ApiResult<Json<EventOut>> {
+    rbac::require_dean(&user)?;
+    Ok(Json(st.events.approve(id, user.user_id).await?))
+}
+
+async fn reject_event(State(st): State<AppState>, user: AuthUser,
+                      Path(id): Path<Uuid>, Json(body): Json<RejectIn>)
+    -> ApiResult<Json<EventOut>> {
+    rbac::require_dean(&user)?;
+    Ok(Json(st.events.reject(id, user.user_id, &body.reason).await?))
 }
 
 async fn update_deadline(State(st): State<AppState>, user: AuthUser,
@@ -160,10 +315,66 @@ async fn list_registrations(State(st): State<AppState>, user: AuthUser, Path(eve
     Ok(Json(rows))
 }
 
+async fn check_in_batch(State(st): State<AppState>, user: AuthUser,
+                        Path(event_id): Path<Uuid>, Json(body): Json<CheckInIn>)
+    -> ApiResult<Json<CheckInOut>> {
+    let e = st.events.get(event_id).await?;
+    rbac::require_dean_or_company_manager(&user, e.company_id)?;
+    Ok(Json(st.events.check_in(event_id, body.student_ids, user.user_id).await?))
+}
+
+async fn check_in_one(State(st): State<AppState>, user: AuthUser,
+                      Path((event_id, student_id)): Path<(Uuid, Uuid)>)
+    -> ApiResult<Json<CheckInOut>> {
+    let e = st.events.get(event_id).await?;
+    rbac::require_dean_or_company_manager(&user, e.company_id)?;
+    let out = st.events.check_in(event_id, vec![student_id], user.user_id).await?;
+    if out.checked_in.is_empty() {
+        return Err(crate::error::ApiError::PreconditionFailed("student is not registered for this event".into()));
+    }
+    Ok(Json(out))
+}
+
+async fn my_ticket(State(st): State<AppState>, user: AuthUser,
+                   Path(event_id): Path<Uuid>, q: Query<TicketQ>)
+    -> ApiResult<Response> {
+    rbac::require_student_confirmed(&user)?;
+    st.events.ensure_ticket_holder(event_id, user.user_id).await?;
+
+    let token = st.tickets.issue(event_id, user.user_id);
+    let internal = |e: anyhow::Error| crate::error::ApiError::Internal(e.to_string());
+    let resp = match q.format.unwrap_or_default() {
+        TicketFormat::Json => Json(TicketOut { event_id, student_id: user.user_id, token }).into_response(),
+        TicketFormat::Svg => {
+            let body = qr::svg(&token).map_err(internal)?;
+            ([(header::CONTENT_TYPE, "image/svg+xml")], body).into_response()
+        }
+        TicketFormat::Png => {
+            let body = qr::png(&token).map_err(internal)?;
+            ([(header::CONTENT_TYPE, "image/png")], body).into_response()
+        }
+    };
+    Ok(resp)
+}
+
+async fn verify_ticket(State(st): State<AppState>, user: AuthUser,
+                       Path(event_id): Path<Uuid>, Json(body): Json<VerifyTicketIn>)
+    -> ApiResult<Json<RegistrationOut>> {
+    let e = st.events.get(event_id).await?;
+    rbac::require_dean_or_company_manager(&user, e.company_id)?;
+
+    let (ticket_event, student_id) = st.tickets.verify(&body.token)
+        .map_err(|e| crate::error::ApiError::Unprocessable(e.to_string()))?;
+    if ticket_event != event_id {
+        return Err(crate::error::ApiError::Unprocessable("ticket belongs to another event".into()));
+    }
+    Ok(Json(st.events.redeem_ticket(event_id, student_id, user.user_id).await?))
+}
+
 async fn register_event(State(st): State<AppState>, user: AuthUser, Path(event_id): Path<Uuid>)
-    -> ApiResult<()> {
+    -> ApiResult<Json<RegistrationStateOut>> {
     rbac::require_student_confirmed(&user)?;
-    st.events.register(event_id, user.user_id).await
+    Ok(Json(st.events.register(event_id, user.user_id).await?))
 }
 
 async fn cancel_registration(State(st): State<AppState>, user: AuthUser, Path(event_id): Path<Uuid>)
@@ -174,21 +385,15 @@ async fn cancel_registration(State(st): State<AppState>, user: AuthUser, Path(ev
 
 async fn list_company_events(State(st): State<AppState>, user: Option<AuthUser>,
                              Path(company_id): Path<Uuid>, q: Query<ListQ>)
-    -> ApiResult<Json<Vec<EventOut>>> {
-    let mut f = EventListFilter {
-        company_id: Some(company_id),
-        manager_id: None,
-        published: q.published,
-        q: q.q.clone(),
-        // from: q.from,
-        // to: q.to,
-    };
+    -> ApiResult<Json<EventPageOut>> {
+    let mut f = q.filter();
+    f.company_id = Some(company_id);
 
     if must_filter_to_published(user.as_ref(), f.company_id) {
         f.published = Some(true);
     }
 
-    Ok(Json(st.events.list(q.page.unwrap_or(1), q.limit.unwrap_or(20), f).await?))
+    Ok(Json(q.fetch(&st, f).await?))
 }
 
 async fn list_student_events(State(st): State<AppState>, user: AuthUser, Path(student_id): Path<Uuid>)
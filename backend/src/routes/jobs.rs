@@ -0,0 +1,44 @@
+use axum::{
+    Router,
+    routing::{get, post},
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::state::AppState;
+use crate::api::models::job::JobOut;
+use crate::auth::extractor::AuthUser;
+use crate::error::ApiResult;
+use crate::infra::repositories::job_repo::JobStatus;
+use crate::infra::security::rbac;
+
+// Очередь фоновых задач: разбор упавших — дело деканата
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/api/v1/admin/jobs", get(list_jobs))
+        .route("/api/v1/admin/jobs/:id/retry", post(retry_job))
+        .with_state(state)
+}
+
+#[derive(Deserialize)]
+struct JobsQ {
+    // по умолчанию — dead-letter
+    status: Option<JobStatus>,
+    kind: Option<String>,
+    limit: Option<i64>,
+}
+
+async fn list_jobs(State(st): State<AppState>, user: AuthUser, q: Query<JobsQ>)
+    -> ApiResult<Json<Vec<JobOut>>> {
+    rbac::require_dean(&user)?;
+    let status = q.status.unwrap_or(JobStatus::Dead);
+    Ok(Json(st.jobs.list(Some(status), q.kind.as_deref(), q.limit.unwrap_or(50)).await?))
+}
+
+async fn retry_job(State(st): State<AppState>, user: AuthUser, Path(id): Path<Uuid>)
+    -> ApiResult<Json<JobOut>> {
+    rbac::require_dean(&user)?;
+    Ok(Json(st.jobs.retry(id).await?))
+}
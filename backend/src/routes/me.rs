@@ -1,7 +1,13 @@
-use axum::{Router, routing::{get, post, delete}, Json};
+use axum::{Router, routing::{get, post, delete}, extract::{Path, State}, Json};
+use uuid::Uuid;
+use crate::api::models::auth::SessionOut;
+use crate::api::models::notification::NotificationPrefsOut;
+use crate::api::requests::notification::UpdateNotificationPrefsIn;
+use crate::api::requests::password::ChangePasswordRequest;
 use crate::error::ApiResult;
 use crate::auth::extractor::AuthUser;
 use crate::auth::roles::{ManagerStatus, UserRole, StudentStatus};
+use crate::infra::security::rbac;
 use crate::state::AppState;
 
 #[derive(serde::Serialize)]
@@ -13,6 +19,7 @@ struct MeOut {
     company_id: Option<uuid::Uuid>,
     student_status: Option<StudentStatus>,
     email: String,
+    email_verified: bool,
 }
 
 pub fn router(state: AppState) -> Router {
@@ -20,10 +27,15 @@ pub fn router(state: AppState) -> Router {
         .route("/api/v1/me", get(me))
         .route("/api/v1/me/google/connect", post(google_connect))
         .route("/api/v1/me/google", delete(google_disconnect))
+        .route("/api/v1/me/notifications", get(notifications).patch(update_notifications))
+        .route("/api/v1/me/sessions", get(sessions).delete(revoke_other_sessions))
+        .route("/api/v1/me/sessions/:id", delete(revoke_session))
+        .route("/api/v1/me/password", post(change_password))
+        .route("/api/v1/me/email/verification", post(resend_verification))
         .with_state(state)
 }
 
-async fn me(user: AuthUser) -> ApiResult<Json<MeOut>> {
+async fn me(State(st): State<AppState>, user: AuthUser) -> ApiResult<Json<MeOut>> {
     let out = MeOut {
         user_id: user.user_id,
         role: user.role,
@@ -31,14 +43,60 @@ async fn me(user: AuthUser) -> ApiResult<Json<MeOut>> {
         company_id: user.company_id,
         student_status: user.student_status,
         email: user.raw.sub.clone(),
+        email_verified: st.users.email_verified(user.user_id).await?,
     };
     Ok(Json(out))
 }
 
-async fn google_connect() -> ApiResult<Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
-        "redirect_url": "https://accounts.google.com/o/oauth2/v2/auth?..."
-    })))
+// синхронизируются записи на события, поэтому подключать Google могут только студенты
+async fn google_connect(State(st): State<AppState>, user: AuthUser) -> ApiResult<Json<serde_json::Value>> {
+    rbac::require_role(&user, &[UserRole::Student])?;
+    let url = st.google.authorize_url(user.user_id).await?;
+    Ok(Json(serde_json::json!({ "redirect_url": url })))
 }
 
-async fn google_disconnect() -> ApiResult<()> { Ok(()) }
\ No newline at end of file
+async fn google_disconnect(State(st): State<AppState>, user: AuthUser) -> ApiResult<()> {
+    st.google.disconnect(user.user_id).await
+}
+async fn notifications(State(st): State<AppState>, user: AuthUser) -> ApiResult<Json<NotificationPrefsOut>> {
+    Ok(Json(st.notifications.preferences(user.user_id).await?))
+}
+
+async fn update_notifications(
+    State(st): State<AppState>,
+    user: AuthUser,
+    Json(input): Json<UpdateNotificationPrefsIn>,
+) -> ApiResult<Json<NotificationPrefsOut>> {
+    Ok(Json(st.notifications.update_preferences(user.user_id, input).await?))
+}
+
+async fn sessions(State(st): State<AppState>, user: AuthUser) -> ApiResult<Json<Vec<SessionOut>>> {
+    Ok(Json(st.auth_service.sessions(user.user_id, user.session_id).await?))
+}
+
+// завершить все сессии, кроме текущей
+async fn revoke_other_sessions(State(st): State<AppState>, user: AuthUser) -> ApiResult<Json<serde_json::Value>> {
+    let revoked = st.auth_service.revoke_other_sessions(user.user_id, user.session_id).await?;
+    Ok(Json(serde_json::json!({ "revoked": revoked })))
+}
+
+async fn revoke_session(State(st): State<AppState>, user: AuthUser, Path(id): Path<Uuid>) -> ApiResult<()> {
+    st.auth_service.revoke_session(user.user_id, id).await
+}
+
+// после смены пароля access-токен устаревает: клиенту нужен refresh текущей сессии
+async fn change_password(
+    State(st): State<AppState>,
+    user: AuthUser,
+    Json(body): Json<ChangePasswordRequest>,
+) -> ApiResult<()> {
+    st.auth_service.change_password(user.user_id, user.session_id, body).await?;
+    st.auth.versions.invalidate(user.user_id);
+    Ok(())
+}
+
+// повторное письмо со ссылкой; 409, если адрес уже подтверждён
+async fn resend_verification(State(st): State<AppState>, user: AuthUser) -> ApiResult<axum::http::StatusCode> {
+    st.verification.send(user.user_id).await?;
+    Ok(axum::http::StatusCode::ACCEPTED)
+}
@@ -2,7 +2,13 @@ pub mod auth;
 pub mod me;
 pub mod oauth;
 pub mod dean_student;
+pub mod dean_settings;
 pub mod companies;
 pub mod events;
 pub mod telegram;
+pub mod calendar;
 pub mod health;
+pub mod search;
+pub mod tags;
+
+pub mod jobs;
\ No newline at end of file
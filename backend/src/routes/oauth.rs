@@ -7,6 +7,19 @@ pub fn router(state: AppState) -> Router {
 }
 
 #[derive(Deserialize)]
-struct GoogleCb { code: String, state: String }
+struct GoogleCb { code: Option<String>, state: String, error: Option<String> }
 
-async fn google_callback(_st: State<AppState>, _q: Query<GoogleCb>) -> Redirect { Redirect::temporary("/connected") }
+// Браузер приходит сюда от Google без JWT — пользователя определяет state
+async fn google_callback(State(st): State<AppState>, Query(q): Query<GoogleCb>) -> Redirect {
+    // отказ пользователя на экране согласия приходит как error без code
+    let Some(code) = q.code.filter(|_| q.error.is_none()) else {
+        return Redirect::temporary("/connected?error=denied");
+    };
+    match st.google.complete(&code, &q.state).await {
+        Ok(()) => Redirect::temporary("/connected"),
+        Err(e) => {
+            tracing::warn!(error = %e, "google oauth callback failed");
+            Redirect::temporary("/connected?error=google")
+        }
+    }
+}
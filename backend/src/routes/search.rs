@@ -0,0 +1,54 @@
+use axum::{
+    Router,
+    routing::get,
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+use crate::state::AppState;
+use crate::api::models::search::SearchOut;
+use crate::auth::extractor::AuthUser;
+use crate::auth::roles::{ManagerStatus, UserRole};
+use crate::domain::entities::search::SearchScope;
+use crate::error::ApiResult;
+
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/api/v1/search", get(search))
+        .with_state(state)
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum HitType {
+    Event,
+    Company,
+}
+
+#[derive(Deserialize)]
+struct SearchQ {
+    q: String,
+    #[serde(rename = "type")]
+    kind: Option<HitType>,
+    limit: Option<i64>,
+}
+
+// видимость та же, что у списков: черновики — декану и менеджерам своей компании
+fn scope_for(user: Option<&AuthUser>, kind: Option<HitType>) -> SearchScope {
+    let own_company = user
+        .filter(|u| u.role == UserRole::Manager && matches!(u.manager_status, Some(ManagerStatus::Confirmed)))
+        .and_then(|u| u.company_id);
+    SearchScope {
+        events: kind != Some(HitType::Company),
+        companies: kind != Some(HitType::Event),
+        everything: user.is_some_and(|u| u.role == UserRole::Dean),
+        own_company,
+    }
+}
+
+async fn search(State(st): State<AppState>, user: Option<AuthUser>, q: Query<SearchQ>)
+    -> ApiResult<Json<SearchOut>> {
+    let scope = scope_for(user.as_ref(), q.kind);
+    Ok(Json(st.search.search(&q.q, scope, q.limit.unwrap_or(20)).await?))
+}
@@ -0,0 +1,45 @@
+use axum::{
+    Router,
+    routing::{get, patch},
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+
+use crate::state::AppState;
+use crate::api::models::tag::TagOut;
+use crate::api::requests::tag::{CreateTagIn, UpdateTagIn};
+use crate::auth::extractor::AuthUser;
+use crate::error::ApiResult;
+use crate::infra::security::rbac;
+
+// Словарь тегов читают все (меню фильтров), правит только деканат
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/api/v1/tags", get(list_tags).post(create_tag))
+        .route("/api/v1/tags/:id", patch(update_tag).delete(delete_tag))
+        .with_state(state)
+}
+
+async fn list_tags(State(st): State<AppState>) -> ApiResult<Json<Vec<TagOut>>> {
+    Ok(Json(st.tags.list().await?))
+}
+
+async fn create_tag(State(st): State<AppState>, user: AuthUser, Json(body): Json<CreateTagIn>)
+    -> ApiResult<(StatusCode, Json<TagOut>)> {
+    rbac::require_dean(&user)?;
+    Ok((StatusCode::CREATED, Json(st.tags.create(body).await?)))
+}
+
+async fn update_tag(State(st): State<AppState>, user: AuthUser, Path(id): Path<Uuid>, Json(body): Json<UpdateTagIn>)
+    -> ApiResult<Json<TagOut>> {
+    rbac::require_dean(&user)?;
+    Ok(Json(st.tags.update(id, body).await?))
+}
+
+async fn delete_tag(State(st): State<AppState>, user: AuthUser, Path(id): Path<Uuid>)
+    -> ApiResult<()> {
+    rbac::require_dean(&user)?;
+    st.tags.delete(id).await
+}
@@ -1,50 +1,84 @@
 use crate::infra::repositories::user_repo::UserRepository;
 use crate::infra::repositories::telegram_repo::TelegramLinkRepository;
+use crate::infra::repositories::session_repo::{Rotation, SessionRepository};
+use crate::infra::repositories::password_reset_repo::{PasswordResetRepository, ResetDelivery};
+use crate::infra::jobs::TelegramJob;
+use crate::infra::mail::templates::Mail;
+use crate::api::requests::password::{ChangePasswordRequest, ForgotPasswordRequest, ResetChannel, ResetPasswordRequest};
+use crate::utils::template;
+use crate::infra::security::session::ClientInfo;
 use crate::infra::security::{password, password_policy};
 use crate::infra::security::jwt::TokenService;
+use crate::infra::security::session_state::SessionStates;
 use crate::error::{ApiError, ApiResult};
-use crate::api::models::auth::{UserOut, RegisterOut, LoginOut};
+use crate::infra::errors::RepoError;
+use crate::api::models::auth::{UserOut, RegisterOut, LoginOut, SessionOut};
 use crate::utils::token::TokenDTO;
 use time::{Duration, OffsetDateTime};
 use uuid::Uuid;
 
 use crate::auth::roles::{ManagerStatus, StudentStatus, UserRole};
 use crate::domain::entities::user_row::UserRow;
+use crate::domain::entities::validation::ValidationErrors;
+
+// не чаще одной ссылки на сброс в минуту на пользователя
+const RESET_MIN_INTERVAL: Duration = Duration::minutes(1);
+
+// Ссылка на страницу сброса (к ней дописывается ?token=) и срок её действия
+#[derive(Clone)]
+pub struct PasswordResetSettings {
+    pub page_url: String,
+    pub ttl: Duration,
+}
 
 #[derive(Clone)]
-pub struct AuthService<R, L>
+pub struct AuthService<R, L, S, P>
 where
     R: UserRepository + Send + Sync + 'static,
     L: TelegramLinkRepository + Send + Sync + 'static,
+    S: SessionRepository + Send + Sync + 'static,
+    P: PasswordResetRepository + Send + Sync + 'static,
 {
     repo: R,
     tokens: TokenService,
     tg_links: L,
+    sessions: S,
+    // кэш активности сессий для AuthUser; сбрасывается при каждом отзыве
+    session_states: SessionStates,
+    resets: P,
+    refresh_ttl: Duration,
+    reset: PasswordResetSettings,
 }
 
-impl<R, L> AuthService<R, L>
+impl<R, L, S, P> AuthService<R, L, S, P>
 where
     R: UserRepository + Send + Sync + 'static,
     L: TelegramLinkRepository + Send + Sync + 'static,
+    S: SessionRepository + Send + Sync + 'static,
+    P: PasswordResetRepository + Send + Sync + 'static,
 {
-    pub fn new(repo: R, tokens: TokenService, tg_links: L) -> Self {
-        Self { repo, tokens, tg_links }
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        repo: R,
+        tokens: TokenService,
+        tg_links: L,
+        sessions: S,
+        session_states: SessionStates,
+        resets: P,
+        refresh_ttl: Duration,
+        reset: PasswordResetSettings,
+    ) -> Self {
+        Self { repo, tokens, tg_links, sessions, session_states, resets, refresh_ttl, reset }
     }
 
     pub async fn register_manager(
         &self,
         req: crate::api::requests::manager_register::ManagerRegisterRequest,
+        client: ClientInfo,
     ) -> ApiResult<RegisterOut> {
-        if req.name.trim().is_empty() {
-            return Err(ApiError::Unprocessable("name must not be empty".into()));
-        }
-        if !req.email.contains('@') {
-            return Err(ApiError::Unprocessable("email is invalid".into()));
-        }
-        password_policy::validate(&req.password)
-            .map_err(|m| ApiError::Unprocessable(m.into()))?;
+        validate_registration(&req.name, &req.email, &req.password)?;
 
-        let hash = password::hash_password(&req.password)
+        let hash = password::hash(&req.password).await
             .map_err(|e| ApiError::Internal(e.to_string()))?;
 
         let user: UserRow = self.repo
@@ -53,43 +87,20 @@ where
 
         self.maybe_link_telegram(&user, req.telegram_user_id).await?;
 
-        let access = self.tokens
-            .generate_manager_token(&user.email, user.id, Some("pending"), req.company_id.into())
-            .map_err(|e| ApiError::Internal(e.to_string()))?;
-
-        let refresh_plain = self.tokens.generate_refresh_token();
-        let refresh_hash  = self.tokens.hash_refresh_token(&refresh_plain);
-        let refresh_exp   = OffsetDateTime::now_utc() + Duration::days(30);
-        self.repo.set_refresh_token(user.id, &refresh_hash, refresh_exp).await?;
-
-        let access_exp = OffsetDateTime::now_utc()
-            + Duration::minutes(self.tokens.lifetime_minutes());
-
         Ok(RegisterOut {
             user: Self::user_row_to_out(&user),
-            tokens: TokenDTO {
-                access_token: access,
-                access_token_expiration: access_exp,
-                refresh_token: refresh_plain,
-                refresh_token_expiration: refresh_exp,
-            },
+            tokens: self.start_session(&user, &client).await?,
         })
     }
 
     pub async fn register_student(
         &self,
         req: crate::api::requests::student_register::StudentRegisterRequest,
+        client: ClientInfo,
     ) -> ApiResult<RegisterOut> {
-        if req.name.trim().is_empty() {
-            return Err(ApiError::Unprocessable("name must not be empty".into()));
-        }
-        if !req.email.contains('@') {
-            return Err(ApiError::Unprocessable("email is invalid".into()));
-        }
-        password_policy::validate(&req.password)
-            .map_err(|m| ApiError::Unprocessable(m.into()))?;
+        validate_registration(&req.name, &req.email, &req.password)?;
 
-        let hash = password::hash_password(&req.password)
+        let hash = password::hash(&req.password).await
             .map_err(|e| ApiError::Internal(e.to_string()))?;
 
         let user: UserRow = self.repo
@@ -98,66 +109,50 @@ where
 
         self.maybe_link_telegram(&user, req.telegram_user_id).await?;
 
-        let access = self.tokens
-            .generate_student_token(&user.email, user.id, "created")
-            .map_err(|e| ApiError::Internal(e.to_string()))?;
-
-        let refresh_plain = self.tokens.generate_refresh_token();
-        let refresh_hash  = self.tokens.hash_refresh_token(&refresh_plain);
-        let refresh_exp   = OffsetDateTime::now_utc() + Duration::days(30);
-        self.repo.set_refresh_token(user.id, &refresh_hash, refresh_exp).await?;
-
-        let access_exp = OffsetDateTime::now_utc()
-            + Duration::minutes(self.tokens.lifetime_minutes());
-
         Ok(RegisterOut {
             user: Self::user_row_to_out(&user),
-            tokens: TokenDTO {
-                access_token: access,
-                access_token_expiration: access_exp,
-                refresh_token: refresh_plain,
-                refresh_token_expiration: refresh_exp,
-            },
+            tokens: self.start_session(&user, &client).await?,
         })
     }
 
     pub async fn login(
         &self,
         req: crate::api::requests::login::LoginRequest,
+        client: ClientInfo,
     ) -> ApiResult<LoginOut> {
         let user = self.repo.find_by_email(&req.email).await?;
 
-        let ok = password::verify_password(&req.password, &user.password_hash);
+        let ok = password::verify(&req.password, &user.password_hash).await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
         if !ok {
             return Err(ApiError::Unauthorized);
         }
+        self.upgrade_hash(&user, &req.password).await;
 
         // self.maybe_link_telegram(&user, req.telegram_user_id).await?;
 
-        let (access, access_exp, refresh_plain, refresh_exp) =
-            self.issue_full_token_set(&user).await?;
-
         Ok(LoginOut {
             user: Self::user_row_to_out(&user),
-            tokens: TokenDTO {
-                access_token: access,
-                access_token_expiration: access_exp,
-                refresh_token: refresh_plain,
-                refresh_token_expiration: refresh_exp,
-            },
+            tokens: self.start_session(&user, &client).await?,
         })
     }
 
-    pub async fn logout(&self, user_id: Uuid) -> ApiResult<()> {
-        let refresh_hash = "-";
-        let refresh_exp  = OffsetDateTime::now_utc() - Duration::seconds(1);
-        self.repo.set_refresh_token(user_id, refresh_hash, refresh_exp).await?;
-        Ok(())
+    // Завершает только текущую сессию; остальные устройства остаются в системе
+    pub async fn logout(&self, user_id: Uuid, session_id: Option<Uuid>) -> ApiResult<()> {
+        let Some(sid) = session_id else { return Ok(()) };
+        let revoked = self.sessions.revoke(user_id, sid, "logout", OffsetDateTime::now_utc()).await;
+        self.session_states.invalidate(sid);
+        match revoked {
+            // уже отозвана (например, с другого устройства) — выходить больше неоткуда
+            Ok(()) | Err(RepoError::NotFound) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
     }
 
     pub async fn refresh_by_token(
         &self,
         body: crate::api::requests::refresh::RefreshRequest,
+        client: ClientInfo,
     ) -> ApiResult<TokenDTO> {
         let now = OffsetDateTime::now_utc();
         let provided_plain = body.refresh_token.trim();
@@ -166,34 +161,164 @@ where
         }
 
         let provided_hash = self.tokens.hash_refresh_token(provided_plain);
+        let refresh_plain = self.tokens.generate_refresh_token();
+        let refresh_hash  = self.tokens.hash_refresh_token(&refresh_plain);
+        let refresh_exp   = now + self.refresh_ttl;
 
-        let user = self
-            .repo
-            .find_by_refresh_token(&provided_hash, now)
-            .await
-            .map_err(|_| ApiError::Unauthorized)?;
+        let rotation = self.sessions
+            .rotate(&provided_hash, &refresh_hash, client.user_agent.as_deref(), now, refresh_exp)
+            .await?;
+        let (session_id, user_id) = match rotation {
+            Rotation::Rotated { session_id, user_id } => (session_id, user_id),
+            Rotation::Reused { session_id, user_id } => {
+                self.session_states.invalidate(session_id);
+                tracing::warn!(%session_id, %user_id, "refresh token reuse detected, session revoked");
+                return Err(ApiError::Unauthorized);
+            }
+            Rotation::Invalid => return Err(ApiError::Unauthorized),
+        };
 
-        let (access, access_exp, refresh_plain, refresh_exp) =
-            self.issue_full_token_set(&user).await?;
+        let user = self.repo.find_by_id(user_id).await?;
+        let access = self.access_token_for(&user, session_id).await?;
 
         Ok(TokenDTO {
             access_token: access,
-            access_token_expiration: access_exp,
+            access_token_expiration: now + Duration::minutes(self.tokens.lifetime_minutes()),
             refresh_token: refresh_plain,
             refresh_token_expiration: refresh_exp,
         })
     }
 
-    pub async fn refresh(&self, user_id: Uuid) -> ApiResult<TokenDTO> {
+    pub async fn sessions(&self, user_id: Uuid, current: Option<Uuid>) -> ApiResult<Vec<SessionOut>> {
+        let rows = self.sessions.list_active(user_id, OffsetDateTime::now_utc()).await?;
+        Ok(rows.into_iter().map(|r| SessionOut::from_row(r, current)).collect())
+    }
+
+    pub async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> ApiResult<()> {
+        self.sessions.revoke(user_id, session_id, "revoked", OffsetDateTime::now_utc()).await?;
+        self.session_states.invalidate(session_id);
+        Ok(())
+    }
+
+    // «Выйти на всех остальных устройствах»
+    pub async fn revoke_other_sessions(&self, user_id: Uuid, current: Option<Uuid>) -> ApiResult<u64> {
+        let revoked = self.sessions.revoke_all(user_id, current, "revoked", OffsetDateTime::now_utc()).await?;
+        self.session_states.invalidate_user(user_id);
+        Ok(revoked)
+    }
+
+    // «Забыли пароль»: ответ одинаковый, есть такой email или нет
+    pub async fn forgot_password(&self, req: ForgotPasswordRequest) -> ApiResult<()> {
+        let user = match self.repo.find_by_email(req.email.trim()).await {
+            Ok(u) => u,
+            Err(RepoError::NotFound) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let now = OffsetDateTime::now_utc();
+        let token = self.tokens.generate_link_token();
+        let link = format!("{}?token={token}", self.reset.page_url);
+        let minutes = self.reset.ttl.whole_minutes();
+        let chat = match req.channel {
+            ResetChannel::Telegram => self.tg_links.active_chat(user.id).await?,
+            ResetChannel::Email => None,
+        };
+        let delivery = match chat {
+            Some(_) => ResetDelivery::Telegram(TelegramJob {
+                user_id: user.id,
+                html: template::render(
+                    "<b>Сброс пароля</b>\nЧтобы задать новый пароль, откройте ссылку: {link}\nСсылка одноразовая и действует {minutes} мин.",
+                    &[("link", &link), ("minutes", &minutes.to_string())],
+                ),
+            }),
+            None => ResetDelivery::Email(Mail::PasswordReset { link, minutes }),
+        };
+
+        let issued = self.resets
+            .issue(
+                user.id,
+                &self.tokens.hash_refresh_token(&token),
+                now,
+                now + self.reset.ttl,
+                RESET_MIN_INTERVAL,
+                &delivery,
+            )
+            .await?;
+        if !issued {
+            tracing::debug!(user_id = %user.id, "password reset throttled");
+        }
+        Ok(())
+    }
+
+    // Новый пароль по ссылке из «Забыли пароль»; все сессии пользователя завершаются
+    pub async fn reset_password(&self, req: ResetPasswordRequest) -> ApiResult<Uuid> {
+        let mut errs = ValidationErrors::new();
+        if req.token.trim().is_empty() {
+            errs.add("token", "required", "token must not be empty");
+        }
+        password_policy::validate("new_password", &req.new_password, &mut errs);
+        errs.check()?;
+
+        let hash = password::hash(&req.new_password).await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        let now = OffsetDateTime::now_utc();
+        let user_id = match self.resets.reset(&self.tokens.hash_refresh_token(req.token.trim()), &hash, now).await {
+            Ok(id) => id,
+            Err(RepoError::NotFound) => {
+                let mut errs = ValidationErrors::new();
+                errs.add("token", "invalid", "reset link is invalid or expired");
+                return Err(errs.into());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        self.session_states.invalidate_user(user_id);
+        Ok(user_id)
+    }
+
+    // Смена пароля из профиля: текущая сессия остаётся, остальные завершаются.
+    // Access-токен текущей сессии устаревает вместе с версией прав — клиент делает refresh
+    pub async fn change_password(
+        &self,
+        user_id: Uuid,
+        session_id: Option<Uuid>,
+        req: ChangePasswordRequest,
+    ) -> ApiResult<()> {
         let user = self.repo.find_by_id(user_id).await?;
-        let (access, access_exp, refresh_plain, refresh_exp) =
-            self.issue_full_token_set(&user).await?;
-        Ok(TokenDTO {
-            access_token: access,
-            access_token_expiration: access_exp,
-            refresh_token: refresh_plain,
-            refresh_token_expiration: refresh_exp,
-        })
+
+        let mut errs = ValidationErrors::new();
+        let old_ok = password::verify(&req.old_password, &user.password_hash).await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        if !old_ok {
+            errs.add("old_password", "mismatch", "current password is incorrect");
+        }
+        password_policy::validate("new_password", &req.new_password, &mut errs);
+        errs.check()?;
+
+        let hash = password::hash(&req.new_password).await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        self.repo.set_password(user_id, &hash, session_id, OffsetDateTime::now_utc()).await?;
+        self.session_states.invalidate_user(user_id);
+        Ok(())
+    }
+
+    // Старая схема (PBKDF2) или прежние параметры Argon2: пароль только что проверен,
+    // пересчитываем хеш на месте. Сбой не мешает входу — попробуем при следующем
+    async fn upgrade_hash(&self, user: &UserRow, plain: &str) {
+        if !password::needs_rehash(&user.password_hash) {
+            return;
+        }
+        let upgraded = match password::hash(plain).await {
+            Ok(hash) => self.repo.upgrade_password_hash(user.id, &user.password_hash, &hash).await,
+            Err(e) => {
+                tracing::warn!(user_id = %user.id, error = %e, "password rehash failed");
+                return;
+            }
+        };
+        match upgraded {
+            Ok(true) => tracing::info!(user_id = %user.id, "password hash upgraded"),
+            Ok(false) => {}
+            Err(e) => tracing::warn!(user_id = %user.id, error = ?e, "password rehash failed"),
+        }
     }
 
     async fn maybe_link_telegram(
@@ -218,33 +343,41 @@ where
         Ok(())
     }
 
-    async fn issue_full_token_set(
-        &self,
-        user: &UserRow,
-    ) -> ApiResult<(String, OffsetDateTime, String, OffsetDateTime)> {
+    async fn access_token_for(&self, user: &UserRow, session_id: Uuid) -> ApiResult<String> {
+        // версию читаем до статусов: если они сменятся между запросами, токен окажется
+        // устаревшим и будет отклонён, а не наоборот
+        let version = self.repo.token_version(user.id).await?;
         let (student_status, manager_status, company_id) =
             self.resolve_statuses_and_company(user).await?;
 
-        let access = self.tokens
-            .generate_token(
-                &user.email,
-                user.id,
-                Self::role_str(user.role),
-                student_status.clone(),
-                manager_status.clone(),
-                company_id,
-            )
-            .map_err(|e| ApiError::Internal(e.to_string()))?;
-
-        let access_exp = OffsetDateTime::now_utc()
-            + Duration::minutes(self.tokens.lifetime_minutes());
+        let mut claims = self.tokens.new_claims(
+            &user.email,
+            user.id,
+            Self::role_str(user.role),
+            student_status,
+            manager_status,
+            company_id,
+        );
+        claims.sid = Some(session_id);
+        claims.ver = version;
+        self.tokens.sign(&claims).map_err(|e| ApiError::Internal(e.to_string()))
+    }
 
+    // Новая сессия входа: первый refresh-токен открывает семейство, access-токен несёт её id
+    async fn start_session(&self, user: &UserRow, client: &ClientInfo) -> ApiResult<TokenDTO> {
+        let now = OffsetDateTime::now_utc();
         let refresh_plain = self.tokens.generate_refresh_token();
         let refresh_hash  = self.tokens.hash_refresh_token(&refresh_plain);
-        let refresh_exp   = OffsetDateTime::now_utc() + Duration::days(30);
-        self.repo.set_refresh_token(user.id, &refresh_hash, refresh_exp).await?;
+        let refresh_exp   = now + self.refresh_ttl;
+        let session_id = self.sessions.create(user.id, client, &refresh_hash, now, refresh_exp).await?;
 
-        Ok((access, access_exp, refresh_plain, refresh_exp))
+        let access = self.access_token_for(user, session_id).await?;
+        Ok(TokenDTO {
+            access_token: access,
+            access_token_expiration: now + Duration::minutes(self.tokens.lifetime_minutes()),
+            refresh_token: refresh_plain,
+            refresh_token_expiration: refresh_exp,
+        })
     }
 
     async fn resolve_statuses_and_company(
@@ -299,4 +432,17 @@ where
             UserRole::Dean    => "dean",
         }
     }
-}
\ No newline at end of file
+}
+
+// Общие поля обеих регистраций; все ошибки возвращаются разом
+fn validate_registration(name: &str, email: &str, password: &str) -> Result<(), ValidationErrors> {
+    let mut errs = ValidationErrors::new();
+    if name.trim().is_empty() {
+        errs.add("name", "required", "name must not be empty");
+    }
+    if !email.contains('@') {
+        errs.add("email", "invalid", "email is invalid");
+    }
+    password_policy::validate("password", password, &mut errs);
+    errs.check()
+}
@@ -0,0 +1,86 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as b64;
+use base64::Engine;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::domain::entities::calendar::{CalendarEntryRow, CalendarEntryStatus};
+use crate::error::ApiResult;
+use crate::infra::repositories::calendar_repo::CalendarRepository;
+use crate::utils::ics::{self, IcsEvent, IcsStatus};
+
+// сколько держим в лентах отменённое/удалённое, чтобы подписчики успели его убрать
+const TOMBSTONE_WINDOW: Duration = Duration::days(30);
+
+#[derive(Clone)]
+pub struct CalendarService<R: CalendarRepository + Send + Sync + 'static> {
+    repo: R,
+}
+
+impl<R: CalendarRepository + Send + Sync + 'static> CalendarService<R> {
+    pub fn new(repo: R) -> Self { Self { repo } }
+
+    pub async fn event_ics(&self, event_id: Uuid) -> ApiResult<String> {
+        let entry = self.repo.event_entry(event_id).await?;
+        let name = entry.title.clone();
+        Ok(render(&name, vec![entry]))
+    }
+
+    pub async fn company_ics(&self, company_id: Uuid) -> ApiResult<String> {
+        let name = self.repo.company_name(company_id).await?;
+        let since = OffsetDateTime::now_utc() - TOMBSTONE_WINDOW;
+        let entries = self.repo.company_entries(company_id, since).await?;
+        Ok(render(&name, entries))
+    }
+
+    pub async fn student_ics(&self, feed_token: &str) -> ApiResult<String> {
+        let student_id = self.repo.user_by_feed_token(&hash_token(feed_token)).await?;
+        let since = OffsetDateTime::now_utc() - TOMBSTONE_WINDOW;
+        let entries = self.repo.student_entries(student_id, since).await?;
+        Ok(render("Мои события ТГУ", entries))
+    }
+
+    // Новый токен сразу отзывает прежний: у пользователя одна действующая ссылка
+    pub async fn rotate_feed_token(&self, user_id: Uuid) -> ApiResult<String> {
+        let mut buf = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut buf);
+        let token = b64.encode(buf);
+        self.repo.set_feed_token(user_id, &hash_token(&token)).await?;
+        Ok(token)
+    }
+
+    pub async fn revoke_feed_token(&self, user_id: Uuid) -> ApiResult<()> {
+        self.repo.delete_feed_token(user_id).await?;
+        Ok(())
+    }
+}
+
+fn hash_token(token: &str) -> String {
+    b64.encode(Sha256::digest(token.as_bytes()))
+}
+
+fn render(name: &str, entries: Vec<CalendarEntryRow>) -> String {
+    let events: Vec<IcsEvent> = entries.into_iter().map(IcsEvent::from).collect();
+    ics::render_calendar(name, &events, OffsetDateTime::now_utc())
+}
+
+impl From<CalendarEntryRow> for IcsEvent {
+    fn from(r: CalendarEntryRow) -> Self {
+        Self {
+            id: r.id,
+            sequence: r.sequence,
+            summary: r.title,
+            description: r.description,
+            location: r.location,
+            starts_at: r.starts_at,
+            ends_at: r.ends_at,
+            last_modified: r.updated_at,
+            status: match r.status {
+                CalendarEntryStatus::Confirmed => IcsStatus::Confirmed,
+                CalendarEntryStatus::Tentative => IcsStatus::Tentative,
+                CalendarEntryStatus::Cancelled => IcsStatus::Cancelled,
+            },
+        }
+    }
+}
@@ -2,7 +2,7 @@ use uuid::Uuid;
 
 use crate::api::models::company::CompanyOut;
 use crate::api::requests::company::{CreateCompanyIn, UpdateCompanyIn};
-use crate::domain::entities::company::CompanyWithCounts;
+use crate::domain::entities::company::{self, CompanyWithCounts};
 use crate::domain::entities::company_row::{CompanyRow, CompanyStatus};
 use crate::infra::repositories::company::CompanyRepository;
 use crate::error::ApiResult;
@@ -45,11 +45,20 @@ impl<R: CompanyRepository + Send + Sync + 'static> CompanyService<R> {
     }
 
     pub async fn update(&self, id: Uuid, payload: UpdateCompanyIn) -> ApiResult<CompanyOut> {
+        if let Some(name) = &payload.name {
+            company::validate_name(name)?;
+        }
+        let mut updated = None;
         if let Some(name) = payload.name {
-            let updated = self.repo.update_name(id, &name).await?;
-            return Ok(CompanyOut::from(updated));
+            updated = Some(self.repo.update_name(id, &name).await?);
+        }
+        if let Some(flag) = payload.requires_event_review {
+            updated = Some(self.repo.set_requires_review(id, flag).await?);
         }
-        let current = self.repo.get(id).await?;
+        let current = match updated {
+            Some(c) => c,
+            None => self.repo.get(id).await?,
+        };
         Ok(CompanyOut::from(current))
     }
 
@@ -0,0 +1,61 @@
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::domain::entities::validation::ValidationErrors;
+use crate::error::{ApiError, ApiResult};
+use crate::infra::errors::RepoError;
+use crate::infra::mail::templates::Mail;
+use crate::infra::repositories::email_verification_repo::EmailVerificationRepository;
+use crate::infra::security::jwt::TokenService;
+
+// повторное письмо не чаще раза в минуту
+const RESEND_MIN_INTERVAL: Duration = Duration::minutes(1);
+
+#[derive(Clone)]
+pub struct EmailVerificationService<V: EmailVerificationRepository + Send + Sync + 'static> {
+    repo: V,
+    tokens: TokenService,
+    // страница веб-интерфейса, к которой дописывается ?token=
+    page_url: String,
+    ttl: Duration,
+}
+
+impl<V: EmailVerificationRepository + Send + Sync + 'static> EmailVerificationService<V> {
+    pub fn new(repo: V, tokens: TokenService, page_url: String, ttl: Duration) -> Self {
+        Self { repo, tokens, page_url, ttl }
+    }
+
+    // Письмо со ссылкой; при регистрации и по запросу повторной отправки
+    pub async fn send(&self, user_id: Uuid) -> ApiResult<()> {
+        let now = OffsetDateTime::now_utc();
+        let token = self.tokens.generate_link_token();
+        let mail = Mail::VerifyEmail {
+            link: format!("{}?token={token}", self.page_url),
+            hours: self.ttl.whole_hours(),
+        };
+        let sent = self.repo
+            .issue(user_id, &self.tokens.hash_refresh_token(&token), now, now + self.ttl, RESEND_MIN_INTERVAL, &mail)
+            .await?;
+        if !sent {
+            tracing::debug!(%user_id, "email verification resend throttled");
+        }
+        Ok(())
+    }
+
+    pub async fn verify(&self, token: &str) -> ApiResult<Uuid> {
+        let token = token.trim();
+        let mut errs = ValidationErrors::new();
+        if token.is_empty() {
+            errs.add("token", "required", "token must not be empty");
+            return Err(errs.into());
+        }
+        match self.repo.verify(&self.tokens.hash_refresh_token(token), OffsetDateTime::now_utc()).await {
+            Ok(user_id) => Ok(user_id),
+            Err(RepoError::NotFound) => {
+                errs.add("token", "invalid", "verification link is invalid or expired");
+                Err(ApiError::Validation(errs))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
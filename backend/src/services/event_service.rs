@@ -1,28 +1,104 @@
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use time::OffsetDateTime;
+use time::{Duration, OffsetDateTime};
 
-use crate::api::models::event::EventOut;
-use crate::api::requests::event::{CreateEventIn, UpdateEventIn};
+use crate::api::models::event::{EventOut, EventPageOut, SeriesOut};
+use crate::api::models::tag::TagFacetOut;
+use crate::api::models::registration::{CheckInOut, RegistrationOut, RegistrationStateOut, SeriesRegistrationOut};
+use crate::api::requests::event::{CreateEventIn, CreateSeriesIn, EditScope, UpdateEventIn};
 use crate::domain::entities::event::{Event, EventPatch};
+use crate::domain::entities::validation::ValidationErrors;
+use crate::domain::entities::event_row::{EventRow, EventStatus};
+use crate::domain::entities::event_series::{EventSeriesRow, Recurrence};
+use crate::domain::entities::registration::{RegistrationState, RegistrationStatus};
 use crate::domain::mappers::event::EventWithCount;
-use crate::infra::repositories::event_repo::{EventRepository, EventListFilter};
+use crate::infra::errors::RepoError;
+use crate::infra::repositories::event_repo::{
+    EventCursor, EventListFilter, EventPageRequest, EventRepository, EventSort, SortOrder,
+};
+use crate::infra::repositories::tag_repo::TagRepository;
+use crate::utils::cursor;
 use crate::error::{ApiResult, ApiError};
 
+// отмечать на входе можно чуть заранее — пока собираются участники
+const CHECK_IN_OPENS_BEFORE: Duration = Duration::hours(1);
+
+// курсор помнит сортировку, под которую выдан: ключи разных сортировок несравнимы
+#[derive(Serialize, Deserialize)]
+struct PageCursor {
+    sort: EventSort,
+    order: SortOrder,
+    at: EventCursor,
+}
+
 #[derive(Clone)]
-pub struct EventService<R: EventRepository + Send + Sync + 'static> {
+pub struct EventService<R, T>
+where
+    R: EventRepository + Send + Sync + 'static,
+    T: TagRepository + Send + Sync + 'static,
+{
     repo: R,
+    tags: T,
 }
 
-impl<R: EventRepository + Send + Sync + 'static> EventService<R> {
-    pub fn new(repo: R) -> Self { Self { repo } }
+impl<R, T> EventService<R, T>
+where
+    R: EventRepository + Send + Sync + 'static,
+    T: TagRepository + Send + Sync + 'static,
+{
+    pub fn new(repo: R, tags: T) -> Self { Self { repo, tags } }
 
-    pub async fn list(&self, page: i32, limit: i32, f: EventListFilter) -> ApiResult<Vec<EventOut>> {
-        let rows: Vec<EventWithCount> = self.repo.list(page, limit, f).await?;
-        Ok(rows.into_iter().map(EventOut::from).collect())
+    // slug'и -> id тегов; незнакомый slug — ошибка поля tags, а не молчаливый пропуск
+    async fn resolve_tags(&self, slugs: Option<Vec<String>>, errs: &mut ValidationErrors) -> ApiResult<Option<Vec<Uuid>>> {
+        let Some(mut slugs) = slugs else { return Ok(None) };
+        slugs.sort();
+        slugs.dedup();
+        let found = self.tags.by_slugs(&slugs).await?;
+        let unknown: Vec<&str> = slugs
+            .iter()
+            .filter(|s| !found.iter().any(|t| &t.slug == *s))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            errs.add("tags", "unknown", format!("unknown tags: {}", unknown.join(", ")));
+        }
+        Ok(Some(found.into_iter().map(|t| t.id).collect()))
+    }
+
+    pub async fn list(&self, f: EventListFilter, sort: EventSort, order: SortOrder,
+                      cursor: Option<&str>, limit: i64) -> ApiResult<EventPageOut> {
+        let after = match cursor {
+            None => None,
+            Some(raw) => {
+                let c: PageCursor = cursor::decode(raw)
+                    .ok_or_else(|| ApiError::BadRequest("malformed cursor".into()))?;
+                if c.sort != sort || c.order != order {
+                    return Err(ApiError::BadRequest("cursor was issued for a different sort".into()));
+                }
+                Some(c.at)
+            }
+        };
+
+        let page = self.repo.list(f, EventPageRequest { sort, order, after, limit }).await?;
+        Ok(EventPageOut {
+            items: page.items.into_iter().map(EventOut::from).collect(),
+            next_cursor: page.next.map(|at| cursor::encode(&PageCursor { sort, order, at })),
+            total: page.total,
+            facets: page.facets.into_iter().map(TagFacetOut::from).collect(),
+        })
+    }
+
+    // Публикация компании с модерацией уходит декану; сам декан публикует сразу
+    async fn needs_review(&self, company_id: Uuid, by_dean: bool) -> ApiResult<bool> {
+        Ok(!by_dean && self.repo.requires_review(company_id).await?)
     }
 
     pub async fn create(&self, body: CreateEventIn, company_id: Uuid, manager_id: Uuid) -> ApiResult<EventOut> {
-        let d = Event::new(
+        let mut errs = ValidationErrors::new();
+        let tags = self.resolve_tags(body.tags, &mut errs).await?;
+        let wants_publish = body.is_published.unwrap_or(false);
+        let submit = wants_publish && self.needs_review(company_id, false).await?;
+        let d = errs.and(Event::new(
             Uuid::new_v4(),
             company_id,
             manager_id,
@@ -33,10 +109,24 @@ impl<R: EventRepository + Send + Sync + 'static> EventService<R> {
             body.ends_at,
             body.signup_deadline,
             body.capacity,
-            body.is_published.unwrap_or(false),
-        ).map_err(|e| ApiError::Unprocessable(e.to_string()))?;
+            wants_publish && !submit,
+        )
+            .and_then(|e| e.with_schedule(body.publish_at, body.unpublish_at)))?;
 
         let saved = self.repo.create(d.into()).await?;
+        let tagged = match tags {
+            Some(ids) if !ids.is_empty() => {
+                self.repo.set_tags(&[saved.id], &ids).await?;
+                true
+            }
+            _ => false,
+        };
+        if submit {
+            return Ok(self.repo.set_status(saved.id, EventStatus::PendingReview).await?.into());
+        }
+        if tagged {
+            return Ok(self.repo.get(saved.id).await?.into());
+        }
         Ok(EventOut::from(saved))
     }
 
@@ -44,17 +134,191 @@ impl<R: EventRepository + Send + Sync + 'static> EventService<R> {
         Ok(self.repo.get(id).await?.into())
     }
 
-    pub async fn update(&self, id: Uuid, patch_in: UpdateEventIn) -> ApiResult<EventOut> {
+    pub async fn get_for_student(&self, id: Uuid, student_id: Uuid) -> ApiResult<EventOut> {
+        let e = EventOut::from(self.repo.get(id).await?);
+        let state = self.repo.registration_state(id, student_id).await?;
+        self.repo.record_view(id, student_id, OffsetDateTime::now_utc()).await?;
+        Ok(e.with_registration(state))
+    }
+
+    pub async fn update(&self, id: Uuid, mut patch_in: UpdateEventIn, by_dean: bool) -> ApiResult<EventOut> {
+        let scope = patch_in.scope.take().unwrap_or_default();
+        let mut errs = ValidationErrors::new();
+        let tags = self.resolve_tags(patch_in.tags.take(), &mut errs).await?;
         let current = self.repo.get(id).await?;
-        let mut d = Event::new(
-            current.id, current.company_id, current.manager_id, current.title,
-            current.description, current.location, current.starts_at, current.ends_at,
-            current.signup_deadline, current.capacity, current.is_published
-        ).expect("already validated");
+        if current.status == EventStatus::Canceled {
+            return Err(ApiError::PreconditionFailed("canceled events cannot be edited".into()));
+        }
+        let series_id = current.series_id;
+        let current_status = current.status;
+
+        // is_published=true там, где нужна модерация, — это отправка на проверку
+        let submit = patch_in.is_published == Some(true) && self.needs_review(current.company_id, by_dean).await?;
+        if submit {
+            patch_in.is_published = None;
+        }
+        let mut d = to_domain(current);
 
         let patch: EventPatch = patch_in.into();
-        d.apply(patch).map_err(|e| ApiError::Unprocessable(e.to_string()))?;
-        Ok(self.repo.update_all(d.into()).await?.into())
+        let series_id = match (scope, series_id) {
+            (EditScope::This, _) | (_, None) => {
+                errs.and(d.apply(patch))?;
+                if let Some(ids) = &tags {
+                    self.repo.set_tags(&[id], ids).await?;
+                }
+                let updated = self.repo.update_all(d.into()).await?;
+                if submit && awaits_submission(current_status) {
+                    return Ok(self.repo.set_status(id, EventStatus::PendingReview).await?.into());
+                }
+                return Ok(updated.into());
+            }
+            (_, Some(sid)) => {
+                errs.check()?;
+                sid
+            }
+        };
+
+        let mut rows = Vec::new();
+        let mut to_submit = Vec::new();
+        for occ in self.repo.list_series_events(series_id).await? {
+            if scope == EditScope::Following && occ.starts_at < d.starts_at {
+                continue;
+            }
+            if occ.status == EventStatus::Canceled {
+                continue;
+            }
+            if submit && awaits_submission(occ.status) {
+                to_submit.push(occ.id);
+            }
+            let mut e = to_domain(occ);
+            e.apply(patch.relative_to(&d, &e))?;
+            rows.push(e.into());
+        }
+        if let Some(ids) = &tags {
+            let event_ids: Vec<Uuid> = rows.iter().map(|r: &EventRow| r.id).collect();
+            self.repo.set_tags(&event_ids, ids).await?;
+        }
+        self.repo.update_many(rows).await?;
+        for occ_id in to_submit {
+            self.repo.set_status(occ_id, EventStatus::PendingReview).await?;
+        }
+        Ok(self.repo.get(id).await?.into())
+    }
+
+    pub async fn create_series(&self, body: CreateSeriesIn, company_id: Uuid, manager_id: Uuid) -> ApiResult<SeriesOut> {
+        let ev = body.event;
+        let mut errs = ValidationErrors::new();
+        let tags = self.resolve_tags(ev.tags, &mut errs).await?;
+        let wants_publish = ev.is_published.unwrap_or(false);
+        let submit = wants_publish && self.needs_review(company_id, false).await?;
+        let template = errs.and(Event::new(
+            Uuid::new_v4(),
+            company_id,
+            manager_id,
+            ev.title,
+            ev.short_desc,
+            ev.location,
+            ev.starts_at,
+            ev.ends_at,
+            ev.signup_deadline,
+            ev.capacity,
+            wants_publish && !submit,
+        )
+            .and_then(|e| e.with_schedule(ev.publish_at, ev.unpublish_at)))?;
+
+        let rule: Recurrence = body.recurrence.into();
+        let starts = rule.expand(template.starts_at).map_err(|e| ApiError::Unprocessable(e.to_string()))?;
+
+        let series = EventSeriesRow {
+            id: Uuid::new_v4(),
+            company_id,
+            manager_id,
+            frequency: rule.frequency,
+            until: rule.until,
+            occurrences: rule.count,
+            custom_dates: rule.dates,
+        };
+        let series_id = series.id;
+
+        let rows = starts
+            .into_iter()
+            .enumerate()
+            .map(|(i, at)| {
+                let shift = at - template.starts_at;
+                let occ = Event {
+                    id: Uuid::new_v4(),
+                    starts_at: at,
+                    ends_at: template.ends_at.map(|v| v + shift),
+                    signup_deadline: template.signup_deadline.map(|v| v + shift),
+                    publish_at: template.publish_at.map(|v| v + shift),
+                    unpublish_at: template.unpublish_at.map(|v| v + shift),
+                    ..template.clone()
+                };
+                EventRow { series_id: Some(series_id), series_index: Some(i as i32), ..occ.into() }
+            })
+            .collect::<Vec<EventRow>>();
+        let event_ids: Vec<Uuid> = rows.iter().map(|r| r.id).collect();
+
+        self.repo.create_series(series, rows).await?;
+        if let Some(ids) = tags.filter(|ids| !ids.is_empty()) {
+            self.repo.set_tags(&event_ids, &ids).await?;
+        }
+        if submit {
+            for id in &event_ids {
+                self.repo.set_status(*id, EventStatus::PendingReview).await?;
+            }
+        }
+        self.get_series(series_id).await
+    }
+
+    pub async fn get_series(&self, id: Uuid) -> ApiResult<SeriesOut> {
+        let s = self.repo.get_series(id).await?;
+        let events = self.repo.list_series_events(id).await?;
+        Ok(SeriesOut {
+            id: s.id,
+            company_id: s.company_id,
+            frequency: s.frequency,
+            until: s.until,
+            count: s.occurrences,
+            events: events.into_iter().map(EventOut::from).collect(),
+        })
+    }
+
+    // Запись на все будущие опубликованные вхождения; на заполненных — лист ожидания.
+    pub async fn register_series(&self, series_id: Uuid, student_id: Uuid) -> ApiResult<Vec<SeriesRegistrationOut>> {
+        let now = OffsetDateTime::now_utc();
+        self.repo.get_series(series_id).await?;
+
+        let open: Vec<EventWithCount> = self.repo.list_series_events(series_id).await?
+            .into_iter()
+            .filter(|e| e.is_published && e.starts_at > now && e.signup_deadline.is_none_or(|dl| now <= dl))
+            .collect();
+        if open.is_empty() {
+            return Err(ApiError::PreconditionFailed("no upcoming occurrences open for registration".into()));
+        }
+
+        let mut out = Vec::with_capacity(open.len());
+        for e in open {
+            let state = self.repo.register(e.id, student_id, now).await?;
+            out.push(SeriesRegistrationOut { event_id: e.id, state: state.into() });
+        }
+        Ok(out)
+    }
+
+    pub async fn cancel_series_registration(&self, series_id: Uuid, student_id: Uuid) -> ApiResult<()> {
+        let now = OffsetDateTime::now_utc();
+        self.repo.get_series(series_id).await?;
+
+        for e in self.repo.list_series_events(series_id).await? {
+            if e.starts_at <= now {
+                continue;
+            }
+            match self.repo.cancel_registration(e.id, student_id, now).await {
+                Ok(()) | Err(RepoError::NotFound) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(())
     }
 
     pub async fn delete(&self, id: Uuid) -> ApiResult<()> {
@@ -62,8 +326,44 @@ impl<R: EventRepository + Send + Sync + 'static> EventService<R> {
         Ok(())
     }
 
-    pub async fn set_published(&self, id: Uuid, flag: bool) -> ApiResult<EventOut> {
-        Ok(self.repo.set_published(id, flag).await?.into())
+    pub async fn cancel(&self, id: Uuid, by: Uuid, reason: &str) -> ApiResult<EventOut> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(ApiError::Unprocessable("reason must not be empty".into()));
+        }
+        Ok(self.repo.cancel(id, by, reason, OffsetDateTime::now_utc()).await?.into())
+    }
+
+    // Снятие с публикации (и отзыв с модерации) возвращает событие в черновики
+    pub async fn set_published(&self, id: Uuid, flag: bool, by_dean: bool) -> ApiResult<EventOut> {
+        let e = self.repo.get(id).await?;
+        if e.status == EventStatus::Canceled {
+            return Err(ApiError::PreconditionFailed("event is canceled".into()));
+        }
+        let status = match flag {
+            false => EventStatus::Draft,
+            true if e.status == EventStatus::Published => return Ok(e.into()),
+            true if self.needs_review(e.company_id, by_dean).await? => EventStatus::PendingReview,
+            true => EventStatus::Published,
+        };
+        Ok(self.repo.set_status(id, status).await?.into())
+    }
+
+    pub async fn review_queue(&self) -> ApiResult<Vec<EventOut>> {
+        let rows = self.repo.review_queue().await?;
+        Ok(rows.into_iter().map(EventOut::from).collect())
+    }
+
+    pub async fn approve(&self, id: Uuid, reviewer: Uuid) -> ApiResult<EventOut> {
+        Ok(self.repo.review(id, EventStatus::Published, reviewer, None).await?.into())
+    }
+
+    pub async fn reject(&self, id: Uuid, reviewer: Uuid, reason: &str) -> ApiResult<EventOut> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(ApiError::Unprocessable("reason must not be empty".into()));
+        }
+        Ok(self.repo.review(id, EventStatus::Rejected, reviewer, Some(reason)).await?.into())
     }
 
     pub async fn set_deadline(&self, id: Uuid, deadline: Option<OffsetDateTime>) -> ApiResult<EventOut> {
@@ -80,6 +380,8 @@ impl<R: EventRepository + Send + Sync + 'static> EventService<R> {
         &self,
         event_id: Uuid,
     ) -> ApiResult<Vec<crate::routes::events::RegistrationOut>> {
+        // воркер переводит в no_show периодически; здесь — чтобы список был точным сразу
+        self.repo.mark_no_shows(Some(event_id), OffsetDateTime::now_utc()).await?;
         let regs = self.repo.list_registrations(event_id).await?;
         Ok(regs
             .into_iter()
@@ -88,13 +390,73 @@ impl<R: EventRepository + Send + Sync + 'static> EventService<R> {
                 student_name:   r.student_name,
                 student_email:  r.student_email,
                 registered_at:  r.registered_at,
+                status:         r.status,
+                checked_in_at:  r.checked_in_at,
             })
             .collect())
     }
 
-    pub async fn register(&self, event_id: Uuid, student_id: Uuid) -> ApiResult<()> {
+    pub async fn check_in(&self, event_id: Uuid, student_ids: Vec<Uuid>, checked_by: Uuid) -> ApiResult<CheckInOut> {
+        if student_ids.is_empty() {
+            return Err(ApiError::Unprocessable("student_ids must not be empty".into()));
+        }
+        let now = OffsetDateTime::now_utc();
+        let e = self.repo.get(event_id).await?;
+        if e.status == EventStatus::Canceled {
+            return Err(ApiError::PreconditionFailed("event is canceled".into()));
+        }
+        if now < e.starts_at - CHECK_IN_OPENS_BEFORE {
+            return Err(ApiError::PreconditionFailed("check-in is not open yet".into()));
+        }
+
+        let checked_in = self.repo.check_in(event_id, &student_ids, checked_by, now).await?;
+        let mut not_registered: Vec<Uuid> = student_ids
+            .into_iter()
+            .filter(|id| !checked_in.contains(id))
+            .collect();
+        not_registered.sort();
+        not_registered.dedup();
+
+        Ok(CheckInOut { checked_in, not_registered })
+    }
+
+    pub async fn ensure_ticket_holder(&self, event_id: Uuid, student_id: Uuid) -> ApiResult<()> {
+        let e = self.repo.get(event_id).await?;
+        if e.status == EventStatus::Canceled {
+            return Err(ApiError::PreconditionFailed("event is canceled".into()));
+        }
+        match self.repo.registration_state(event_id, student_id).await? {
+            Some(RegistrationState { status: RegistrationStatus::Registered | RegistrationStatus::Attended, .. }) => Ok(()),
+            _ => Err(ApiError::PreconditionFailed("no active registration for this event".into())),
+        }
+    }
+
+    pub async fn redeem_ticket(&self, event_id: Uuid, student_id: Uuid, checked_by: Uuid) -> ApiResult<RegistrationOut> {
         let now = OffsetDateTime::now_utc();
         let e = self.repo.get(event_id).await?;
+        if e.status == EventStatus::Canceled {
+            return Err(ApiError::PreconditionFailed("event is canceled".into()));
+        }
+        if now < e.starts_at - CHECK_IN_OPENS_BEFORE {
+            return Err(ApiError::PreconditionFailed("check-in is not open yet".into()));
+        }
+
+        if let Some(r) = self.repo.redeem_ticket(event_id, student_id, checked_by, now).await? {
+            return Ok(r.into());
+        }
+        match self.repo.registration_state(event_id, student_id).await? {
+            Some(RegistrationState { status: RegistrationStatus::Attended, .. }) =>
+                Err(ApiError::Conflict("ticket already used".into())),
+            _ => Err(ApiError::PreconditionFailed("no active registration for this event".into())),
+        }
+    }
+
+    pub async fn register(&self, event_id: Uuid, student_id: Uuid) -> ApiResult<RegistrationStateOut> {
+        let now = OffsetDateTime::now_utc();
+        let e = self.repo.get(event_id).await?;
+        if e.status == EventStatus::Canceled {
+            return Err(ApiError::PreconditionFailed("event is canceled".into()));
+        }
         if !e.is_published {
             return Err(ApiError::PreconditionFailed("event not published".into()));
         }
@@ -103,14 +465,8 @@ impl<R: EventRepository + Send + Sync + 'static> EventService<R> {
                 return Err(ApiError::PreconditionFailed("deadline passed".into()));
             }
         }
-        if let Some(cap) = e.capacity {
-            let used = self.repo.count_registrations(event_id).await?;
-            if used >= cap as i64 {
-                return Err(ApiError::PreconditionFailed("no seats available".into()));
-            }
-        }
-        self.repo.register(event_id, student_id, now).await?;
-        Ok(())
+        // при заполненной вместимости репозиторий ставит студента в лист ожидания
+        Ok(self.repo.register(event_id, student_id, now).await?.into())
     }
 
     pub async fn cancel_registration(&self, event_id: Uuid, student_id: Uuid) -> ApiResult<()> {
@@ -123,8 +479,21 @@ impl<R: EventRepository + Send + Sync + 'static> EventService<R> {
         let mut out = Vec::new();
         for r in regs {
             let e = self.repo.get(r.event_id).await?;
-            out.push(EventOut::from(e));
+            out.push(EventOut::from(e).with_registration(Some(r.state)));
         }
         Ok(out)
     }
-}
\ No newline at end of file
+}
+
+fn to_domain(e: EventWithCount) -> Event {
+    Event::new(
+        e.id, e.company_id, e.manager_id, e.title,
+        e.description, e.location, e.starts_at, e.ends_at,
+        e.signup_deadline, e.capacity, e.is_published
+    ).and_then(|d| d.with_schedule(e.publish_at, e.unpublish_at)).expect("already validated")
+}
+
+// опубликованное и уже ждущее проверки повторно не отправляем
+fn awaits_submission(status: EventStatus) -> bool {
+    matches!(status, EventStatus::Draft | EventStatus::Rejected)
+}
@@ -0,0 +1,117 @@
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::infra::errors::RepoError;
+use crate::infra::google::calendar::{CalendarEventData, GoogleCalendarClient};
+use crate::infra::google::oauth::{random_urlsafe, GoogleError, GoogleOAuthClient, Pkce};
+use crate::infra::repositories::google_repo::{GcalSyncTarget, GoogleAccountRepository};
+
+// сколько живёт state между редиректом на Google и callback
+const OAUTH_STATE_TTL: Duration = Duration::minutes(10);
+
+#[derive(Clone)]
+pub struct GoogleService<R: GoogleAccountRepository + Clone + Send + Sync + 'static> {
+    repo: R,
+    oauth: GoogleOAuthClient,
+    calendar: GoogleCalendarClient,
+}
+
+impl<R: GoogleAccountRepository + Clone + Send + Sync + 'static> GoogleService<R> {
+    pub fn new(repo: R, oauth: GoogleOAuthClient, calendar: GoogleCalendarClient) -> Self {
+        Self { repo, oauth, calendar }
+    }
+
+    pub async fn authorize_url(&self, user_id: Uuid) -> ApiResult<String> {
+        let state = random_urlsafe(24);
+        let pkce = Pkce::generate();
+        let url = self.oauth.authorize_url(&state, &pkce.challenge)?;
+        let expires_at = OffsetDateTime::now_utc() + OAUTH_STATE_TTL;
+        self.repo.save_oauth_state(&state, user_id, &pkce.verifier, expires_at).await?;
+        Ok(url)
+    }
+
+    pub async fn complete(&self, code: &str, state: &str) -> ApiResult<()> {
+        let (user_id, verifier) = match self.repo.take_oauth_state(state, OffsetDateTime::now_utc()).await {
+            Ok(v) => v,
+            Err(RepoError::NotFound) => return Err(ApiError::BadRequest("unknown or expired oauth state".into())),
+            Err(e) => return Err(e.into()),
+        };
+        let tokens = self.oauth.exchange_code(code, &verifier).await?;
+        let refresh = tokens.refresh_token.ok_or(GoogleError::NoRefreshToken)?;
+        self.repo.upsert_account(user_id, &refresh, tokens.scope.as_deref(), OffsetDateTime::now_utc()).await?;
+        Ok(())
+    }
+
+    pub async fn disconnect(&self, user_id: Uuid) -> ApiResult<()> {
+        self.repo.delete_account(user_id).await?;
+        Ok(())
+    }
+
+    // Приводит копии в календарях студентов к текущему состоянию события и записей.
+    // Ошибка по кому-то из студентов — повод повторить задачу целиком: синхронизация идемпотентна
+    pub async fn sync_event(&self, event_id: Uuid) -> anyhow::Result<()> {
+        let targets = self.repo.sync_targets(event_id).await?;
+        if targets.is_empty() {
+            return Ok(());
+        }
+        let data = match self.repo.event_data(event_id).await {
+            // событие удалили раньше, чем дошла очередь; копиями займётся удаление
+            Err(RepoError::NotFound) => return Ok(()),
+            other => other?,
+        };
+        let total = targets.len();
+        let mut failed = 0;
+        for t in targets {
+            if let Err(e) = self.sync_one(&data, &t).await {
+                if e.is_revoked() {
+                    tracing::info!(student_id = %t.student_id, "google access revoked, unlinking account");
+                    self.repo.delete_account(t.student_id).await.ok();
+                } else {
+                    tracing::warn!(student_id = %t.student_id, error = %e, "google calendar sync failed");
+                    failed += 1;
+                }
+            }
+        }
+        anyhow::ensure!(failed == 0, "{failed} of {total} calendar copies not synced");
+        Ok(())
+    }
+
+    async fn sync_one(&self, data: &CalendarEventData, t: &GcalSyncTarget) -> Result<(), GoogleError> {
+        let event_id = data.event_id;
+        let Some(refresh) = t.refresh_token.as_deref() else {
+            // аккаунт отвязан, копию удалить уже нечем
+            self.forget(event_id, t.student_id).await;
+            return Ok(());
+        };
+        let access = self.oauth.access_token(refresh).await?;
+
+        match (t.active, t.gcal_event_id.as_deref()) {
+            (true, Some(gcal_id)) => match self.calendar.update(&access, gcal_id, data).await {
+                Err(e) if e.is_gone() => self.insert(&access, data, t.student_id).await,
+                other => other,
+            },
+            (true, None) => self.insert(&access, data, t.student_id).await,
+            (false, Some(gcal_id)) => {
+                self.calendar.delete(&access, gcal_id).await?;
+                self.forget(event_id, t.student_id).await;
+                Ok(())
+            }
+            (false, None) => Ok(()),
+        }
+    }
+
+    async fn insert(&self, access: &str, data: &CalendarEventData, student_id: Uuid) -> Result<(), GoogleError> {
+        let gcal_id = self.calendar.insert(access, data).await?;
+        if let Err(e) = self.repo.set_gcal_event_id(data.event_id, student_id, Some(&gcal_id)).await {
+            tracing::warn!(error = %e, "failed to store gcal_event_id");
+        }
+        Ok(())
+    }
+
+    async fn forget(&self, event_id: Uuid, student_id: Uuid) {
+        if let Err(e) = self.repo.set_gcal_event_id(event_id, student_id, None).await {
+            tracing::warn!(error = %e, "failed to clear gcal_event_id");
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::api::models::job::JobOut;
+use crate::error::ApiResult;
+use crate::infra::repositories::job_repo::{JobRepository, JobStatus};
+
+#[derive(Clone)]
+pub struct JobService<R: JobRepository + Send + Sync + 'static> {
+    repo: R,
+}
+
+impl<R: JobRepository + Send + Sync + 'static> JobService<R> {
+    pub fn new(repo: R) -> Self { Self { repo } }
+
+    pub async fn list(&self, status: Option<JobStatus>, kind: Option<&str>, limit: i64) -> ApiResult<Vec<JobOut>> {
+        let rows = self.repo.list(status, kind, limit.clamp(1, 200)).await?;
+        Ok(rows.into_iter().map(JobOut::from).collect())
+    }
+
+    pub async fn retry(&self, id: Uuid) -> ApiResult<JobOut> {
+        Ok(self.repo.retry(id, OffsetDateTime::now_utc()).await?.into())
+    }
+}
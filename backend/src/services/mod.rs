@@ -4,4 +4,11 @@ pub mod registration_service;
 pub mod company_service;
 pub mod telegram_service;
 pub mod auth_service;
-pub mod manager_service;
\ No newline at end of file
+pub mod manager_service;
+pub mod calendar_service;
+pub mod google_service;
+pub mod search_service;
+pub mod tag_service;
+pub mod job_service;
+pub mod email_verification_service;
+pub mod notification_service;
@@ -0,0 +1,128 @@
+use time::UtcOffset;
+use uuid::Uuid;
+
+use crate::api::models::notification::NotificationPrefsOut;
+use crate::api::requests::notification::UpdateNotificationPrefsIn;
+use crate::domain::entities::notification::{render_reminder, NotificationChannel, ReminderEvent, ReminderKind};
+use crate::domain::entities::registration::RegistrationStatus;
+use crate::error::ApiResult;
+use crate::infra::errors::RepoError;
+use crate::infra::jobs::{ReminderJob, TelegramJob};
+use crate::infra::mail::templates::MailJob;
+use crate::infra::mail::Mailer;
+use crate::infra::repositories::reminder_repo::ReminderRepository;
+use crate::infra::repositories::telegram_repo::TelegramLinkRepository;
+use crate::infra::telegram::bot::{TelegramBot, TelegramError};
+
+#[derive(Clone)]
+pub struct NotificationService<R, L>
+where
+    R: ReminderRepository + Send + Sync + 'static,
+    L: TelegramLinkRepository + Send + Sync + 'static,
+{
+    repo: R,
+    links: L,
+    bot: TelegramBot,
+    mailer: Mailer,
+    offset: UtcOffset,
+}
+
+impl<R, L> NotificationService<R, L>
+where
+    R: ReminderRepository + Send + Sync + 'static,
+    L: TelegramLinkRepository + Send + Sync + 'static,
+{
+    pub fn new(repo: R, links: L, bot: TelegramBot, mailer: Mailer, offset: UtcOffset) -> Self {
+        Self { repo, links, bot, mailer, offset }
+    }
+
+    // Обработчик задачи notify.reminder. Условия перепроверяются перед отправкой;
+    // если напоминание стало неактуальным, задача просто завершается
+    pub async fn deliver(&self, job: ReminderJob) -> anyhow::Result<()> {
+        let Some(kind) = ReminderKind::parse(&job.kind) else {
+            anyhow::bail!("unknown reminder kind {}", job.kind);
+        };
+        let t = match self.repo.target(job.event_id, job.user_id, job.channel).await {
+            Ok(t) => t,
+            Err(RepoError::NotFound) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let relevant = match kind {
+            ReminderKind::BeforeStart(_) => t.registration == Some(RegistrationStatus::Registered),
+            ReminderKind::Deadline => t.registration.is_none(),
+        };
+        let Some(chat_id) = t.telegram_user_id else { return Ok(()) };
+        if !t.published || t.opted_out || !relevant {
+            return Ok(());
+        }
+
+        let text = render_reminder(kind, &ReminderEvent {
+            title: t.title,
+            location: t.location,
+            starts_at: t.starts_at,
+            signup_deadline: t.signup_deadline,
+        }, self.offset);
+        // напоминания планируются только в Telegram
+        if job.channel != NotificationChannel::Telegram {
+            anyhow::bail!("email reminders are not supported");
+        }
+        // строка забирается до отправки: повтор задачи после удачной отправки
+        // (сбой воркера, истёкшая блокировка) уже ничего не пришлёт
+        let now = time::OffsetDateTime::now_utc();
+        if !self.repo.claim(job.event_id, job.user_id, &job.kind, job.channel, now).await? {
+            return Ok(());
+        }
+        match self.bot.send_message(chat_id, &text).await {
+            Ok(()) => Ok(()),
+            // повторять бессмысленно: выключаем связь до повторной привязки
+            Err(TelegramError::Blocked(reason)) => {
+                tracing::info!(user_id = %job.user_id, reason = %reason, "telegram link deactivated");
+                self.links.deactivate(job.user_id, now).await?;
+                Ok(())
+            }
+            // сообщение не ушло — отдаём строку повторному запуску
+            Err(e) => {
+                self.repo.release(job.event_id, job.user_id, &job.kind, job.channel).await?;
+                Err(e.into())
+            }
+        }
+    }
+
+    // Обработчик задачи mail.send; отказ от почты проверен при постановке
+    pub async fn send_mail(&self, job: MailJob) -> anyhow::Result<()> {
+        let rendered = job.mail.render(&job.name, self.offset);
+        self.mailer.send(&job.to, &job.name, rendered).await
+    }
+
+    // Обработчик задачи telegram.send; без активной привязки отправлять некуда
+    pub async fn send_telegram(&self, job: TelegramJob) -> anyhow::Result<()> {
+        let Some(chat_id) = self.links.active_chat(job.user_id).await? else { return Ok(()) };
+        match self.bot.send_message(chat_id, &job.html).await {
+            Ok(()) => Ok(()),
+            Err(TelegramError::Blocked(reason)) => {
+                tracing::info!(user_id = %job.user_id, reason = %reason, "telegram link deactivated");
+                self.links.deactivate(job.user_id, time::OffsetDateTime::now_utc()).await?;
+                Ok(())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn preferences(&self, user_id: Uuid) -> ApiResult<NotificationPrefsOut> {
+        let off = self.repo.opted_out(user_id).await?;
+        Ok(NotificationPrefsOut {
+            telegram: !off.contains(&NotificationChannel::Telegram),
+            email: !off.contains(&NotificationChannel::Email),
+        })
+    }
+
+    pub async fn update_preferences(&self, user_id: Uuid, input: UpdateNotificationPrefsIn) -> ApiResult<NotificationPrefsOut> {
+        if let Some(on) = input.telegram {
+            self.repo.set_opt_out(user_id, NotificationChannel::Telegram, !on).await?;
+        }
+        if let Some(on) = input.email {
+            self.repo.set_opt_out(user_id, NotificationChannel::Email, !on).await?;
+        }
+        self.preferences(user_id).await
+    }
+}
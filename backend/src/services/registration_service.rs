@@ -40,6 +40,8 @@ impl From<RegistrationRow> for RegistrationOut {
             registered_at: r.registered_at,
             student_name: r.student_name,
             student_email: r.student_email,
+            status: r.status,
+            checked_in_at: r.checked_in_at,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,28 @@
+use crate::api::models::search::{SearchHitOut, SearchOut};
+use crate::domain::entities::search::SearchScope;
+use crate::error::{ApiError, ApiResult};
+use crate::infra::repositories::search_repo::SearchRepository;
+
+// длинные запросы смысла не имеют, а разбор tsquery не бесплатный
+const MAX_QUERY_LEN: usize = 200;
+
+#[derive(Clone)]
+pub struct SearchService<R: SearchRepository + Send + Sync + 'static> {
+    repo: R,
+}
+
+impl<R: SearchRepository + Send + Sync + 'static> SearchService<R> {
+    pub fn new(repo: R) -> Self { Self { repo } }
+
+    pub async fn search(&self, q: &str, scope: SearchScope, limit: i64) -> ApiResult<SearchOut> {
+        let q = q.trim();
+        if q.is_empty() {
+            return Err(ApiError::BadRequest("q must not be empty".into()));
+        }
+        if q.chars().count() > MAX_QUERY_LEN {
+            return Err(ApiError::BadRequest(format!("q must be at most {MAX_QUERY_LEN} characters")));
+        }
+        let rows = self.repo.search(q, scope, limit.clamp(1, 50)).await?;
+        Ok(SearchOut { items: rows.into_iter().map(SearchHitOut::from).collect() })
+    }
+}
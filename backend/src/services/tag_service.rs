@@ -0,0 +1,56 @@
+use uuid::Uuid;
+
+use crate::api::models::tag::TagOut;
+use crate::api::requests::tag::{CreateTagIn, UpdateTagIn};
+use crate::domain::entities::tag::{validate_name, validate_slug, TagRow};
+use crate::error::{ApiError, ApiResult};
+use crate::infra::repositories::tag_repo::TagRepository;
+
+#[derive(Clone)]
+pub struct TagService<R: TagRepository + Send + Sync + 'static> {
+    repo: R,
+}
+
+impl<R: TagRepository + Send + Sync + 'static> TagService<R> {
+    pub fn new(repo: R) -> Self { Self { repo } }
+
+    pub async fn list(&self) -> ApiResult<Vec<TagOut>> {
+        let rows = self.repo.list().await?;
+        Ok(rows.into_iter().map(TagOut::from).collect())
+    }
+
+    pub async fn create(&self, body: CreateTagIn) -> ApiResult<TagOut> {
+        let row = TagRow {
+            id: Uuid::new_v4(),
+            slug: body.slug.trim().to_string(),
+            name: body.name.trim().to_string(),
+            category: normalize_category(body.category),
+        };
+        validate(&row)?;
+        Ok(self.repo.create(row).await?.into())
+    }
+
+    pub async fn update(&self, id: Uuid, body: UpdateTagIn) -> ApiResult<TagOut> {
+        let mut row = self.repo.get(id).await?;
+        if let Some(v) = body.slug { row.slug = v.trim().to_string(); }
+        if let Some(v) = body.name { row.name = v.trim().to_string(); }
+        if body.category.is_some() { row.category = normalize_category(body.category); }
+        validate(&row)?;
+        Ok(self.repo.update(row).await?.into())
+    }
+
+    pub async fn delete(&self, id: Uuid) -> ApiResult<()> {
+        self.repo.delete(id).await?;
+        Ok(())
+    }
+}
+
+fn normalize_category(v: Option<String>) -> Option<String> {
+    v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+fn validate(row: &TagRow) -> ApiResult<()> {
+    validate_slug(&row.slug)
+        .and_then(|_| validate_name(&row.name))
+        .map_err(|e| ApiError::Unprocessable(e.to_string()))
+}
@@ -56,4 +56,32 @@ where
         self.links_repo.unlink_by_user(user_id).await?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use sqlx::{Pool, Postgres};
+
+    use super::*;
+    use crate::error::ApiError;
+    use crate::infra::repositories::telegram_code_repo::PgTelegramCodeRepository;
+    use crate::infra::repositories::telegram_repo::PgTelegramLinkRepository;
+    use crate::infra::repositories::user_repo::{PgUserRepository, UserRepository};
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn student_can_relink_after_blocking_the_bot(pool: Pool<Postgres>) {
+        let links = PgTelegramLinkRepository::new(pool.clone());
+        let service = TelegramService::new(links.clone(), PgTelegramCodeRepository::new(pool.clone()), 10);
+        let student = PgUserRepository::new(pool).create_student("Аня", "a@test.io", "x").await.unwrap().id;
+
+        let code = service.create_link_code_for_user(student).await.unwrap();
+        service.consume_link_code(&code, 100).await.unwrap();
+        assert!(matches!(service.create_link_code_for_user(student).await, Err(ApiError::Conflict(_))));
+
+        links.deactivate(student, OffsetDateTime::now_utc()).await.unwrap();
+        assert_eq!(links.active_chat(student).await.unwrap(), None);
+
+        let code = service.create_link_code_for_user(student).await.unwrap();
+        service.consume_link_code(&code, 200).await.unwrap();
+        assert_eq!(links.active_chat(student).await.unwrap(), Some(200));
+    }
+}
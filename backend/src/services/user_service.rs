@@ -1,26 +1,43 @@
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::auth::roles::StudentStatus;
 use crate::domain::entities::user_row::UserRow;
-use crate::infra::errors::RepoResult;
+use crate::infra::errors::{RepoError, RepoResult};
+use crate::infra::repositories::settings_repo::{AppSettings, SettingsRepository};
 use crate::infra::repositories::user_repo::UserRepository;
 
 #[derive(Clone)]
-pub struct UsersService<R: UserRepository + Send + Sync + 'static> {
+pub struct UsersService<R, S>
+where
+    R: UserRepository + Send + Sync + 'static,
+    S: SettingsRepository + Send + Sync + 'static,
+{
     repo: R,
+    // настройки деканата, в т.ч. требование проверенного email для подтверждения студента
+    settings: S,
 }
 
-impl<R: UserRepository + Send + Sync + 'static> UsersService<R> {
-    pub fn new(repo: R) -> Self { Self { repo } }
+impl<R, S> UsersService<R, S>
+where
+    R: UserRepository + Send + Sync + 'static,
+    S: SettingsRepository + Send + Sync + 'static,
+{
+    pub fn new(repo: R, settings: S) -> Self { Self { repo, settings } }
 
     pub async fn list_students_by_status(
         &self,
         statuses: &[StudentStatus],
+        verified: Option<bool>,
         page: i32,
         limit: i32,
         search: &str,
-    ) -> RepoResult<Vec<(UserRow, Option<StudentStatus>)>> {
-        self.repo.list_students_by_status(statuses, page, limit, search).await
+    ) -> RepoResult<Vec<(UserRow, Option<StudentStatus>, bool)>> {
+        self.repo.list_students_by_status(statuses, verified, page, limit, search).await
+    }
+
+    pub async fn email_verified(&self, user_id: Uuid) -> RepoResult<bool> {
+        self.repo.email_verified(user_id).await
     }
 
     pub async fn set_student_status(
@@ -28,6 +45,52 @@ impl<R: UserRepository + Send + Sync + 'static> UsersService<R> {
         user_id: Uuid,
         status: StudentStatus,
     ) -> RepoResult<()> {
+        if status == StudentStatus::Confirmed
+            && self.settings.get().await?.require_verified_email
+            && !self.repo.email_verified(user_id).await?
+        {
+            return Err(RepoError::Precondition("student email is not verified".into()));
+        }
         self.repo.set_student_status(user_id, status).await
     }
-}
\ No newline at end of file
+
+    pub async fn settings(&self) -> RepoResult<AppSettings> {
+        self.settings.get().await
+    }
+
+    pub async fn update_settings(&self, dean_id: Uuid, require_verified_email: Option<bool>) -> RepoResult<AppSettings> {
+        self.settings.update(require_verified_email, dean_id, OffsetDateTime::now_utc()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{Pool, Postgres};
+
+    use super::*;
+    use crate::infra::repositories::settings_repo::PgSettingsRepository;
+    use crate::infra::repositories::user_repo::PgUserRepository;
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn approval_requires_verified_email_only_when_dean_enabled_it(pool: Pool<Postgres>) {
+        let users = PgUserRepository::new(pool.clone());
+        let service = UsersService::new(users.clone(), PgSettingsRepository::new(pool));
+        let dean = users.create_student("Декан", "dean@test.io", "x").await.unwrap().id;
+        let a = users.create_student("Аня", "a@test.io", "x").await.unwrap().id;
+        let b = users.create_student("Боря", "b@test.io", "x").await.unwrap().id;
+
+        assert!(!service.settings().await.unwrap().require_verified_email);
+        service.set_student_status(a, StudentStatus::Confirmed).await.unwrap();
+
+        let s = service.update_settings(dean, Some(true)).await.unwrap();
+        assert!(s.require_verified_email);
+        assert_eq!(s.updated_by, Some(dean));
+        assert!(matches!(
+            service.set_student_status(b, StudentStatus::Confirmed).await,
+            Err(RepoError::Precondition(_))
+        ));
+        // отклонить можно и без подтверждённого адреса
+        service.set_student_status(b, StudentStatus::Rejected).await.unwrap();
+        assert_eq!(users.student_status(b).await.unwrap(), Some(StudentStatus::Rejected));
+    }
+}
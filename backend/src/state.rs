@@ -11,19 +11,44 @@ use crate::infra::repositories::{
     telegram_repo::PgTelegramLinkRepository,
     telegram_code_repo::PgTelegramCodeRepository,
     manager_repo::PgManagerRepository,
+    calendar_repo::PgCalendarRepository,
+    google_repo::PgGoogleAccountRepository,
+    search_repo::PgSearchRepository,
+    tag_repo::PgTagRepository,
+    job_repo::PgJobRepository,
+    reminder_repo::PgReminderRepository,
+    idempotency_repo::PgIdempotencyRepository,
+    session_repo::PgSessionRepository,
+    password_reset_repo::PgPasswordResetRepository,
+    email_verification_repo::PgEmailVerificationRepository,
+    settings_repo::PgSettingsRepository,
 };
 
 use crate::services::{
     company_service::CompanyService,
     event_service::EventService,
-    auth_service::AuthService,
+    auth_service::{AuthService, PasswordResetSettings},
     telegram_service::TelegramService,
     manager_service::ManagerService,
     user_service::UsersService,
+    calendar_service::CalendarService,
+    google_service::GoogleService,
+    search_service::SearchService,
+    tag_service::TagService,
+    job_service::JobService,
+    notification_service::NotificationService,
+    email_verification_service::EmailVerificationService,
 };
 
 use crate::auth::extractor::AuthState;
 use crate::infra::security::jwt::{TokenConfig, TokenService};
+use crate::infra::security::ticket::TicketService;
+use crate::infra::security::session_state::SessionStates;
+use crate::infra::security::token_version::TokenVersions;
+use crate::infra::google::{calendar::GoogleCalendarClient, oauth::GoogleOAuthClient};
+use crate::infra::jobs::{kinds, EventJob, JobRegistry, ReminderJob, TelegramJob};
+use crate::infra::telegram::bot::TelegramBot;
+use crate::infra::mail::{templates::MailJob, Mailer};
 use crate::services::registration_service::RegistrationService;
 
 #[derive(Clone)]
@@ -32,14 +57,29 @@ pub struct AppState {
     pub config: Arc<Config>,
 
     pub companies: CompanyService<PgCompanyRepository>,
-    pub events:    EventService<PgEventRepository>,
+    pub events:    EventService<PgEventRepository, PgTagRepository>,
     pub managers:  ManagerService<PgManagerRepository>,
-    pub users:     UsersService<PgUserRepository>,
+    pub users:     UsersService<PgUserRepository, PgSettingsRepository>,
 
     pub telegram:  TelegramService<PgTelegramLinkRepository, PgTelegramCodeRepository>,
 
+    pub tickets:   TicketService,
+    pub calendar:  CalendarService<PgCalendarRepository>,
+    pub google:    GoogleService<PgGoogleAccountRepository>,
+    pub search:    SearchService<PgSearchRepository>,
+    pub tags:      TagService<PgTagRepository>,
+    pub jobs:      JobService<PgJobRepository>,
+    pub notifications: NotificationService<PgReminderRepository, PgTelegramLinkRepository>,
+
+    pub verification: EmailVerificationService<PgEmailVerificationRepository>,
+
     pub auth:         AuthState,
-    pub auth_service: AuthService<PgUserRepository, PgTelegramLinkRepository>,
+    pub auth_service: AuthService<
+        PgUserRepository,
+        PgTelegramLinkRepository,
+        PgSessionRepository,
+        PgPasswordResetRepository,
+    >,
 }
 
 impl AppState {
@@ -52,19 +92,120 @@ impl AppState {
         let tg_links       = PgTelegramLinkRepository::new(db.clone());
         let tg_codes       = PgTelegramCodeRepository::new(db.clone());
         let managers_repo  = PgManagerRepository::new(db.clone());
+        let calendar_repo  = PgCalendarRepository::new(db.clone());
+        let google_repo    = PgGoogleAccountRepository::new(db.clone());
+        let search_repo    = PgSearchRepository::new(db.clone());
+        let tags_repo      = PgTagRepository::new(db.clone());
+        let jobs_repo      = PgJobRepository::new(db.clone());
+        let reminders_repo = PgReminderRepository::new(db.clone());
+        let sessions_repo  = PgSessionRepository::new(db.clone());
+
+        let http = reqwest::Client::new();
+        let bot  = TelegramBot::new(http.clone(), config.telegram_api_url.clone(), config.telegram_bot_token.clone());
+        if !bot.is_configured() {
+            tracing::warn!("TELEGRAM_BOT_TOKEN is not set, event reminders are disabled");
+        }
+
+        crate::infra::jobs::worker::spawn(
+            events_repo.clone(),
+            jobs_repo.clone(),
+            reminders_repo.clone(),
+            PgIdempotencyRepository::new(db.clone()),
+            sessions_repo.clone(),
+            bot.is_configured().then(|| config.reminder_leads.clone()),
+            std::time::Duration::from_secs(config.jobs_interval_secs.max(1)),
+        );
 
         // let registration = RegistrationService::new(registration_repo);
         let companies = CompanyService::new(companies_repo);
-        let events    = EventService::new(events_repo);
+        let events    = EventService::new(events_repo, tags_repo.clone());
         let managers  = ManagerService::new(managers_repo);
-        let users     = UsersService::new(users_repo.clone());
-
-        let token_service = TokenService::new(TokenConfig::from_env());
-        let auth          = AuthState { token_service: token_service.clone() };
+        let users     = UsersService::new(users_repo.clone(), PgSettingsRepository::new(db.clone()));
+        let calendar  = CalendarService::new(calendar_repo);
+        let search    = SearchService::new(search_repo);
+        let tags      = TagService::new(tags_repo);
+        let jobs      = JobService::new(jobs_repo.clone());
+        let notifications = NotificationService::new(
+            reminders_repo,
+            tg_links.clone(),
+            bot,
+            Mailer::from_config(&config)?,
+            config.notify_utc_offset,
+        );
+
+        let google = GoogleService::new(
+            google_repo,
+            GoogleOAuthClient::new(http.clone(), &config),
+            GoogleCalendarClient::new(http, config.google_calendar_api_url.clone()),
+        );
+
+        let registry = JobRegistry::new()
+            .on(kinds::GCAL_SYNC_EVENT, {
+                let google = google.clone();
+                move |job: EventJob| {
+                    let google = google.clone();
+                    async move { google.sync_event(job.event_id).await }
+                }
+            })
+            .on(kinds::NOTIFY_REMINDER, {
+                let notifications = notifications.clone();
+                move |job: ReminderJob| {
+                    let notifications = notifications.clone();
+                    async move { notifications.deliver(job).await }
+                }
+            })
+            .on(kinds::MAIL_SEND, {
+                let notifications = notifications.clone();
+                move |job: MailJob| {
+                    let notifications = notifications.clone();
+                    async move { notifications.send_mail(job).await }
+                }
+            })
+            .on(kinds::TELEGRAM_SEND, {
+                let notifications = notifications.clone();
+                move |job: TelegramJob| {
+                    let notifications = notifications.clone();
+                    async move { notifications.send_telegram(job).await }
+                }
+            });
+        crate::infra::jobs::worker::spawn_queue(
+            jobs_repo,
+            registry,
+            std::time::Duration::from_millis(config.jobs_poll_ms.max(50)),
+        );
+
+        let token_config  = TokenConfig::from_env();
+        let tickets       = TicketService::new(&token_config);
+        let token_service = TokenService::new(token_config);
+        let auth_cache_ttl = std::time::Duration::from_secs(config.token_version_cache_secs);
+        let auth          = AuthState {
+            token_service: token_service.clone(),
+            versions: TokenVersions::new(users_repo.clone(), auth_cache_ttl),
+            sessions: SessionStates::new(sessions_repo.clone(), auth_cache_ttl),
+        };
+
+        let verification = EmailVerificationService::new(
+            PgEmailVerificationRepository::new(db.clone()),
+            token_service.clone(),
+            format!("{}/verify.html", config.web_base_url),
+            time::Duration::hours(config.email_verification_ttl_hours),
+        );
 
         let telegram = TelegramService::new(tg_links.clone(), tg_codes, config.telegram_code_ttl);
 
-        let auth_service = AuthService::new(users_repo, token_service, tg_links);
+        let auth_service = AuthService::new(
+            users_repo,
+            token_service,
+            tg_links,
+            sessions_repo,
+            auth.sessions.clone(),
+            PgPasswordResetRepository::new(db.clone()),
+            time::Duration::days(config.refresh_token_ttl_days),
+            PasswordResetSettings {
+                page_url: format!("{}/reset.html", config.web_base_url),
+                ttl: time::Duration::minutes(config.password_reset_ttl_minutes),
+            },
+        );
 
         Ok(Self {
             db,
@@ -74,6 +215,14 @@ impl AppState {
             events,
             users,
             telegram,
+            tickets,
+            calendar,
+            google,
+            search,
+            tags,
+            jobs,
+            notifications,
+            verification,
             auth,
             auth_service,
         })
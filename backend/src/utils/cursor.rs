@@ -0,0 +1,27 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as b64;
+use base64::Engine;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+// Непрозрачный курсор пагинации: JSON в base64url. Клиент его не разбирает,
+// а сервер проверяет, что курсор выдан под те же параметры сортировки.
+pub fn encode<T: Serialize>(value: &T) -> String {
+    b64.encode(serde_json::to_vec(value).expect("cursor is serializable"))
+}
+
+pub fn decode<T: DeserializeOwned>(s: &str) -> Option<T> {
+    let bytes = b64.decode(s).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_and_garbage() {
+        let c = encode(&("starts_at", 42i64));
+        assert_eq!(decode::<(String, i64)>(&c), Some(("starts_at".to_string(), 42)));
+        assert_eq!(decode::<(String, i64)>("not a cursor"), None);
+    }
+}
@@ -0,0 +1,40 @@
+use time::Duration;
+
+// Список длительностей из конфига: "24h,1h,30m". Пустой или с ошибкой — None,
+// чтобы опечатка в env не отключала напоминания молча
+pub fn parse_list(s: &str) -> Option<Vec<Duration>> {
+    let items = s
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(parse)
+        .collect::<Option<Vec<_>>>()?;
+    (!items.is_empty()).then_some(items)
+}
+
+pub fn parse(s: &str) -> Option<Duration> {
+    let (num, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit())?);
+    let n: i64 = num.parse().ok()?;
+    let d = match unit {
+        "d" => Duration::days(n),
+        "h" => Duration::hours(n),
+        "m" => Duration::minutes(n),
+        _ => return None,
+    };
+    d.is_positive().then_some(d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_units_and_rejects_garbage() {
+        assert_eq!(parse_list("24h, 1h,30m"), Some(vec![Duration::hours(24), Duration::hours(1), Duration::minutes(30)]));
+        assert_eq!(parse_list("2d"), Some(vec![Duration::days(2)]));
+        assert_eq!(parse_list(""), None);
+        assert_eq!(parse_list("24h,soon"), None);
+        assert_eq!(parse_list("0h"), None);
+        assert_eq!(parse_list("h"), None);
+    }
+}
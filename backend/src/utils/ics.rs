@@ -0,0 +1,157 @@
+use time::macros::format_description;
+use time::{OffsetDateTime, UtcOffset};
+use uuid::Uuid;
+
+// RFC 5545: строки не длиннее 75 октетов, перевод строки — CRLF
+const MAX_LINE: usize = 75;
+const PRODID: &str = "-//TSU HITs//Event Board//RU";
+const UID_DOMAIN: &str = "events.tsu-hits";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IcsStatus {
+    Confirmed,
+    Tentative,
+    Cancelled,
+}
+
+impl IcsStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            IcsStatus::Confirmed => "CONFIRMED",
+            IcsStatus::Tentative => "TENTATIVE",
+            IcsStatus::Cancelled => "CANCELLED",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IcsEvent {
+    pub id: Uuid,
+    pub sequence: i32,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub starts_at: OffsetDateTime,
+    pub ends_at: Option<OffsetDateTime>,
+    pub last_modified: OffsetDateTime,
+    pub status: IcsStatus,
+}
+
+pub fn uid(event_id: Uuid) -> String {
+    format!("{event_id}@{UID_DOMAIN}")
+}
+
+pub fn render_calendar(name: &str, events: &[IcsEvent], now: OffsetDateTime) -> String {
+    let mut out = String::new();
+    push(&mut out, "BEGIN:VCALENDAR");
+    push(&mut out, "VERSION:2.0");
+    push(&mut out, &format!("PRODID:{PRODID}"));
+    push(&mut out, "CALSCALE:GREGORIAN");
+    push(&mut out, "METHOD:PUBLISH");
+    push(&mut out, &format!("X-WR-CALNAME:{}", escape(name)));
+
+    for e in events {
+        push(&mut out, "BEGIN:VEVENT");
+        push(&mut out, &format!("UID:{}", uid(e.id)));
+        push(&mut out, &format!("SEQUENCE:{}", e.sequence));
+        push(&mut out, &format!("DTSTAMP:{}", utc(now)));
+        push(&mut out, &format!("LAST-MODIFIED:{}", utc(e.last_modified)));
+        push(&mut out, &format!("DTSTART:{}", utc(e.starts_at)));
+        if let Some(end) = e.ends_at {
+            push(&mut out, &format!("DTEND:{}", utc(end)));
+        }
+        push(&mut out, &format!("SUMMARY:{}", escape(&e.summary)));
+        if let Some(d) = e.description.as_deref().filter(|d| !d.is_empty()) {
+            push(&mut out, &format!("DESCRIPTION:{}", escape(d)));
+        }
+        if let Some(l) = e.location.as_deref().filter(|l| !l.is_empty()) {
+            push(&mut out, &format!("LOCATION:{}", escape(l)));
+        }
+        push(&mut out, &format!("STATUS:{}", e.status.as_str()));
+        push(&mut out, "END:VEVENT");
+    }
+
+    push(&mut out, "END:VCALENDAR");
+    out
+}
+
+fn utc(t: OffsetDateTime) -> String {
+    let fmt = format_description!("[year][month][day]T[hour][minute][second]Z");
+    t.to_offset(UtcOffset::UTC).format(&fmt).expect("valid utc timestamp")
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// Перенос длинной строки: продолжение начинается с пробела.
+// Режем по границе символа, чтобы не разорвать UTF-8.
+fn push(out: &mut String, line: &str) {
+    let mut used = 0;
+    for ch in line.chars() {
+        let len = ch.len_utf8();
+        if used + len > MAX_LINE {
+            out.push_str("\r\n ");
+            used = 1;
+        }
+        out.push(ch);
+        used += len;
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn sample() -> IcsEvent {
+        IcsEvent {
+            id: Uuid::nil(),
+            sequence: 2,
+            summary: "Хакатон; финал, день 2".into(),
+            description: Some("line1\nline2".into()),
+            location: None,
+            starts_at: datetime!(2025-10-01 15:00 +7),
+            ends_at: Some(datetime!(2025-10-01 18:00 +7)),
+            last_modified: datetime!(2025-09-20 10:00 UTC),
+            status: IcsStatus::Confirmed,
+        }
+    }
+
+    #[test]
+    fn renders_escaped_utc_event() {
+        let ics = render_calendar("TSU", &[sample()], datetime!(2025-09-21 00:00 UTC));
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.contains("UID:00000000-0000-0000-0000-000000000000@events.tsu-hits\r\n"));
+        assert!(ics.contains("SEQUENCE:2\r\n"));
+        assert!(ics.contains("DTSTART:20251001T080000Z\r\n"));
+        assert!(ics.contains("SUMMARY:Хакатон\\; финал\\, день 2\r\n"));
+        assert!(ics.contains("DESCRIPTION:line1\\nline2\r\n"));
+        assert!(!ics.contains("LOCATION"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn folds_long_lines_on_char_boundaries() {
+        let mut e = sample();
+        e.summary = "ж".repeat(100);
+        let ics = render_calendar("TSU", &[e], datetime!(2025-09-21 00:00 UTC));
+        for line in ics.split("\r\n") {
+            assert!(line.len() <= MAX_LINE, "line too long: {}", line.len());
+        }
+        let unfolded = ics.replace("\r\n ", "");
+        assert!(unfolded.contains(&format!("SUMMARY:{}", "ж".repeat(100))));
+    }
+}
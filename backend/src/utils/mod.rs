@@ -1,2 +1,7 @@
 pub mod token;
-pub mod codegen;
\ No newline at end of file
+pub mod codegen;
+pub mod qr;
+pub mod ics;
+pub mod cursor;
+pub mod duration;
+pub mod template;
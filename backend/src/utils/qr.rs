@@ -0,0 +1,25 @@
+use std::io::Cursor;
+
+use qrcode::{render::svg, QrCode};
+
+pub fn svg(data: &str) -> anyhow::Result<String> {
+    let code = QrCode::new(data.as_bytes())?;
+    Ok(code
+        .render::<svg::Color>()
+        .min_dimensions(256, 256)
+        .quiet_zone(true)
+        .build())
+}
+
+pub fn png(data: &str) -> anyhow::Result<Vec<u8>> {
+    let code = QrCode::new(data.as_bytes())?;
+    let img = code
+        .render::<image::Luma<u8>>()
+        .min_dimensions(256, 256)
+        .quiet_zone(true)
+        .build();
+
+    let mut out = Cursor::new(Vec::new());
+    img.write_to(&mut out, image::ImageFormat::Png)?;
+    Ok(out.into_inner())
+}
@@ -0,0 +1,63 @@
+// Шаблоны сообщений с подстановками {name}. Значения экранируются под HTML:
+// этот режим разметки понимают и Telegram (parse_mode=HTML), и почтовые клиенты.
+// Неизвестная подстановка остаётся как есть, чтобы ошибку в шаблоне было видно в тексте
+pub fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    render_with(template, vars, escape_html)
+}
+
+// для текстовой версии письма — без экранирования
+pub fn render_plain(template: &str, vars: &[(&str, &str)]) -> String {
+    render_with(template, vars, str::to_owned)
+}
+
+fn render_with(template: &str, vars: &[(&str, &str)], escape: fn(&str) -> String) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let value = tail.find('}').and_then(|end| {
+            let name = &tail[1..end];
+            vars.iter().find(|(k, _)| *k == name).map(|(_, v)| (end, *v))
+        });
+        match value {
+            Some((end, v)) => {
+                out.push_str(&escape(v));
+                rest = &tail[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+pub fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substitutes_and_escapes() {
+        let s = render("<b>{title}</b> в {place}{missing}", &[("title", "R&D <meetup>"), ("place", "ауд. 222")]);
+        assert_eq!(s, "<b>R&amp;D &lt;meetup&gt;</b> в ауд. 222{missing}");
+        assert_eq!(render("{a}{", &[("a", "x")]), "x{");
+        assert_eq!(render_plain("{t}", &[("t", "a<b")]), "a<b");
+    }
+}
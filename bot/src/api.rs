@@ -5,22 +5,64 @@ use uuid::Uuid;
 
 use crate::{app::App, dto};
 
+// так бот подписан в списке сессий пользователя (/me/sessions)
+const DEVICE_LABEL: &str = "Telegram-бот";
+
 fn truncate(s: &str, max: usize) -> String {
     if s.len() > max { format!("{}…", &s[..max]) } else { s.to_string() }
 }
 
+// Сообщение об ошибке бэкенда; request_id добавляем, чтобы пользователь мог передать его в поддержку
 fn extract_err_message(body_text: &str) -> String {
     match serde_json::from_str::<serde_json::Value>(body_text) {
-        Ok(v) => v.get("error")
-            .and_then(|e| e.get("message"))
-            .and_then(|m| m.as_str())
-            .or_else(|| v.get("message").and_then(|m| m.as_str()))
-            .unwrap_or(body_text)
-            .to_string(),
+        Ok(v) => {
+            let msg = v.get("error")
+                .and_then(|e| e.get("message"))
+                .and_then(|m| m.as_str())
+                .or_else(|| v.get("message").and_then(|m| m.as_str()))
+                .unwrap_or(body_text);
+            match v.get("request_id").and_then(|r| r.as_str()) {
+                Some(rid) => format!("{msg} (request id: {rid})"),
+                None => msg.to_string(),
+            }
+        }
         Err(_) => body_text.to_string(),
     }
 }
 
+// Ошибка бэкенда вместе с именами полей формы, не прошедших проверку (error.fields)
+#[derive(Debug)]
+pub struct BackendError {
+    pub status: reqwest::StatusCode,
+    pub message: String,
+    pub fields: Vec<String>,
+}
+
+impl BackendError {
+    fn new(status: reqwest::StatusCode, body_text: &str) -> Self {
+        let fields = serde_json::from_str::<serde_json::Value>(body_text)
+            .ok()
+            .and_then(|v| v.pointer("/error/fields").and_then(|f| f.as_array()).cloned())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|f| f.get("field").and_then(|n| n.as_str()).map(str::to_owned))
+            .collect();
+        Self { status, message: extract_err_message(body_text), fields }
+    }
+
+    pub fn has_field(&self, field: &str) -> bool {
+        self.fields.iter().any(|f| f == field)
+    }
+}
+
+impl std::fmt::Display for BackendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HTTP {}: {}", self.status, self.message)
+    }
+}
+
+impl std::error::Error for BackendError {}
+
 pub async fn login_and_link(app: &Arc<App>, email: &str, password: &str, telegram_user_id: i64)
     -> Result<dto::Tokens> {
     let url = format!("{}/api/v1/auth/login", app.base_url);
@@ -28,12 +70,13 @@ pub async fn login_and_link(app: &Arc<App>, email: &str, password: &str, telegra
         "email": email,
         "password": password,
         "telegram_user_id": telegram_user_id,
+        "device": DEVICE_LABEL,
     });
 
     println!("[bot][api] -> POST {url}");
     println!("[bot][api] body: {}", truncate(&body.to_string(), 500));
 
-    let resp = app.http.post(&url).json(&body).send().await?;
+    let resp = app.post(&url).json(&body).send().await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
 
@@ -61,12 +104,13 @@ pub async fn register_student_and_link(app: &Arc<App>, email: &str, password: &s
         "email": email,
         "password": password,
         "telegram_user_id": telegram_user_id,
+        "device": DEVICE_LABEL,
     });
 
     println!("[bot][api] -> POST {url}");
     println!("[bot][api] body: {}", truncate(&body.to_string(), 500));
 
-    let resp = app.http.post(&url).json(&body).send().await?;
+    let resp = app.post(&url).json(&body).send().await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
 
@@ -74,8 +118,7 @@ pub async fn register_student_and_link(app: &Arc<App>, email: &str, password: &s
     println!("[bot][api] response: {}", truncate(&text, 500));
 
     if !status.is_success() {
-        let msg = extract_err_message(&text);
-        return Err(anyhow!("HTTP {}: {}", status, msg));
+        return Err(BackendError::new(status, &text).into());
     }
 
     let out: dto::RegisterOut = serde_json::from_str(&text)
@@ -93,12 +136,13 @@ pub async fn register_manager_and_link(app: &Arc<App>, email: &str, password: &s
         "password": password,
         "company_id": company_id,
         "telegram_user_id": telegram_user_id,
+        "device": DEVICE_LABEL,
     });
 
     println!("[bot][api] -> POST {url}");
     println!("[bot][api] body: {}", truncate(&body.to_string(), 500));
 
-    let resp = app.http.post(&url).json(&body).send().await?;
+    let resp = app.post(&url).json(&body).send().await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
 
@@ -106,8 +150,7 @@ pub async fn register_manager_and_link(app: &Arc<App>, email: &str, password: &s
     println!("[bot][api] response: {}", truncate(&text, 500));
 
     if !status.is_success() {
-        let msg = extract_err_message(&text);
-        return Err(anyhow!("HTTP {}: {}", status, msg));
+        return Err(BackendError::new(status, &text).into());
     }
 
     let out: dto::RegisterOut = serde_json::from_str(&text)
@@ -120,7 +163,7 @@ pub async fn list_companies(app: &Arc<App>) -> Result<Vec<dto::Company>> {
     let url = format!("{}/api/v1/companies?page=1&limit=1000", app.base_url);
 
     println!("[bot][api] -> GET {url}");
-    let resp = app.http.get(&url).send().await?;
+    let resp = app.get(&url).send().await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
 
@@ -142,7 +185,7 @@ pub async fn list_companies(app: &Arc<App>) -> Result<Vec<dto::Company>> {
 pub async fn me(app: &Arc<App>, access_token: &str) -> Result<dto::MeOut> {
     let url = format!("{}/api/v1/me", app.base_url);
     println!("[bot][api] -> GET {url}");
-    let resp = app.http.get(&url).bearer_auth(access_token).send().await?;
+    let resp = app.get(&url).bearer_auth(access_token).send().await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
     println!("[bot][api] <- status={status}");
@@ -161,21 +204,14 @@ pub async fn student_list_available_events(
     app: &Arc<App>,
     access_token: &str,
 ) -> Result<Vec<dto::EventShort>> {
-    // let now = OffsetDateTime::now_utc();
-    // let to  = now + Duration::days(90);
-    //
-    // let from_s = now.format(&Rfc3339)?;
-    // let to_s   = to.format(&Rfc3339)?;
-
+    // ближайшие сначала; бэкенд сам отсекает уже прошедшие
     let url = format!(
-        "{}/api/v1/events?published=true",
+        "{}/api/v1/events?published=true&when=upcoming&sort=starts_at&limit=50",
         app.base_url,
-        // urlencoding::encode(&from_s),
-        // urlencoding::encode(&to_s),
     );
 
     println!("[bot][api] -> GET {url}");
-    let resp = app.http.get(&url).bearer_auth(access_token).send().await?;
+    let resp = app.get(&url).bearer_auth(access_token).send().await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
     println!("[bot][api] <- status={status}");
@@ -186,27 +222,29 @@ pub async fn student_list_available_events(
         anyhow::bail!("HTTP {}: {}", status, msg);
     }
 
-    let list: Vec<dto::EventShort> =
+    let page: dto::EventPage =
         serde_json::from_str(&text).map_err(|e| anyhow::anyhow!("decode events: {e}"))?;
-    Ok(list)
+    Ok(page.items)
 }
 
-pub async fn student_register_event(app: &Arc<App>, token: &str, event_id: Uuid) -> Result<()> {
+pub async fn student_register_event(app: &Arc<App>, token: &str, event_id: Uuid) -> Result<dto::RegistrationState> {
     let url = format!("{}/api/v1/events/{event_id}/register", app.base_url);
     println!("[bot][api] -> POST {url}");
-    let resp = app.http.post(&url).bearer_auth(token).send().await?;
+    let resp = app.post_action(&url, |r| r.bearer_auth(token)).await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
     println!("[bot][api] <- status={status} {}", truncate(&text, 300));
     if !status.is_success() {
         return Err(anyhow!("HTTP {}: {}", status, extract_err_message(&text)));
     }
-    Ok(())
+    let st: dto::RegistrationState = serde_json::from_str(&text)
+        .map_err(|e| anyhow!("decode registration: {e}"))?;
+    Ok(st)
 }
 pub async fn student_unregister_event(app: &Arc<App>, token: &str, event_id: Uuid) -> Result<()> {
-    let url = format!("{}/api/v1/events/{event_id}/register", app.base_url);
-    println!("[bot][api] -> DELETE {url}");
-    let resp = app.http.delete(&url).bearer_auth(token).send().await?;
+    let url = format!("{}/api/v1/events/{event_id}/cancel", app.base_url);
+    println!("[bot][api] -> POST {url}");
+    let resp = app.post_action(&url, |r| r.bearer_auth(token)).await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
     println!("[bot][api] <- status={status} {}", truncate(&text, 300));
@@ -220,7 +258,7 @@ pub async fn student_unregister_event(app: &Arc<App>, token: &str, event_id: Uui
 pub async fn manager_list_company_managers(app: &Arc<App>, token: &str, company_id: Uuid) -> Result<serde_json::Value> {
     let url = format!("{}/api/v1/companies/{company_id}/managers", app.base_url);
     println!("[bot][api] -> GET {url}");
-    let resp = app.http.get(&url).bearer_auth(token).send().await?;
+    let resp = app.get(&url).bearer_auth(token).send().await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
     println!("[bot][api] <- status={status}");
@@ -233,7 +271,7 @@ pub async fn manager_list_company_managers(app: &Arc<App>, token: &str, company_
 pub async fn manager_set_manager_status(app: &Arc<App>, token: &str, company_id: Uuid, user_id: Uuid, status: &str) -> Result<()> {
     let url = format!("{}/api/v1/companies/{company_id}/managers/{user_id}/status/{status}", app.base_url);
     println!("[bot][api] -> POST {url}");
-    let resp = app.http.post(&url).bearer_auth(token).send().await?;
+    let resp = app.post_action(&url, |r| r.bearer_auth(token)).await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
     println!("[bot][api] <- status={status} {}", truncate(&text, 300));
@@ -249,7 +287,7 @@ pub async fn manager_list_event_students(
     event_id: Uuid,
 ) -> Result<Vec<dto::RegistrationEntry>> {
     let url = format!("{}/api/v1/events/{}/registrations", app.base_url, event_id);
-    let resp = app.http.get(&url).bearer_auth(access_token).send().await?;
+    let resp = app.get(&url).bearer_auth(access_token).send().await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
 
@@ -294,7 +332,7 @@ pub async fn manager_create_event(
     println!("[bot][api] -> POST {url}");
     println!("[bot][api] body: {}", truncate(&body.to_string(), 500));
 
-    let resp = app.http.post(&url).bearer_auth(access_token).json(&body).send().await?;
+    let resp = app.post_action(&url, |r| r.bearer_auth(access_token).json(&body)).await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
 
@@ -307,6 +345,51 @@ pub async fn manager_create_event(
     }
     Ok(serde_json::from_str(&text).unwrap_or(json!({ "ok": true })))
 }
+// серия ивентов; возвращает число созданных вхождений
+#[allow(clippy::too_many_arguments)]
+pub async fn manager_create_event_series(
+    app: &Arc<App>,
+    access_token: &str,
+    title: &str,
+    short_desc: &str,
+    starts_at: &str,
+    ends_at: &str,
+    signup_deadline: &str,
+    location: &str,
+    capacity: Option<i32>,
+    is_published: bool,
+    frequency: &str,
+    count: i32,
+) -> Result<usize> {
+    let url = format!("{}/api/v1/events/series", app.base_url);
+    let mut body = json!({
+        "title": title,
+        "short_desc": short_desc,
+        "starts_at": starts_at,
+        "ends_at": ends_at,
+        "signup_deadline": signup_deadline,
+        "location": location,
+        "is_published": is_published,
+        "recurrence": { "frequency": frequency, "count": count },
+    });
+    if let Some(c) = capacity {
+        body.as_object_mut().unwrap().insert("capacity".into(), json!(c));
+    }
+
+    println!("[bot][api] -> POST {url}");
+    let resp = app.post_action(&url, |r| r.bearer_auth(access_token).json(&body)).await?;
+    let status = resp.status();
+    let text = resp.text().await.unwrap_or_default();
+    println!("[bot][api] <- status={status} {}", truncate(&text, 300));
+
+    if !status.is_success() {
+        anyhow::bail!("HTTP {}: {}", status, extract_err_message(&text));
+    }
+    let v: serde_json::Value = serde_json::from_str(&text)
+        .map_err(|e| anyhow!("decode series: {e}"))?;
+    Ok(v["events"].as_array().map(|a| a.len()).unwrap_or(0))
+}
+
 pub async fn manager_update_event_title(
     app: &Arc<App>,
     token: &str,
@@ -316,7 +399,7 @@ pub async fn manager_update_event_title(
     let url = format!("{}/api/v1/events/{event_id}", app.base_url);
     let body = json!({ "title": new_title });
     println!("[bot][api] -> PATCH {url} {}", body);
-    let resp = app.http.patch(&url).bearer_auth(token).json(&body).send().await?;
+    let resp = app.patch_action(&url, |r| r.bearer_auth(token).json(&body)).await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
     println!("[bot][api] <- status={status} {}", truncate(&text, 300));
@@ -330,9 +413,9 @@ pub async fn manager_update_event_title(
 pub async fn manager_list_company_events(
     app: &Arc<App>, access_token: &str, company_id: Uuid
 ) -> Result<Vec<dto::EventShort>> {
-    let url = format!("{}/api/v1/events/companies/{}", app.base_url, company_id);
+    let url = format!("{}/api/v1/events/companies/{}?limit=100", app.base_url, company_id);
     println!("[bot][api] -> GET {url}");
-    let resp = app.http.get(&url).bearer_auth(access_token).send().await?;
+    let resp = app.get(&url).bearer_auth(access_token).send().await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
     println!("[bot][api] <- status={status}");
@@ -341,9 +424,9 @@ pub async fn manager_list_company_events(
         let msg = extract_err_message(&text);
         return Err(anyhow::anyhow!("HTTP {}: {}", status, msg));
     }
-    let items: Vec<dto::EventShort> = serde_json::from_str(&text)
+    let page: dto::EventPage = serde_json::from_str(&text)
         .map_err(|e| anyhow::anyhow!("decode company events: {e}"))?;
-    Ok(items)
+    Ok(page.items)
 }
 
 // получить ивент
@@ -352,7 +435,7 @@ pub async fn manager_get_event(
 ) -> Result<serde_json::Value> {
     let url = format!("{}/api/v1/events/{}", app.base_url, event_id);
     println!("[bot][api] -> GET {url}");
-    let resp = app.http.get(&url).bearer_auth(access_token).send().await?;
+    let resp = app.get(&url).bearer_auth(access_token).send().await?;
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
     println!("[bot][api] <- status={status}");
@@ -367,7 +450,7 @@ pub async fn manager_get_event(
 // publish / unpublish
 pub async fn manager_publish_event(app: &Arc<App>, token: &str, event_id: Uuid) -> Result<()> {
     let url = format!("{}/api/v1/events/{}/publish", app.base_url, event_id);
-    let r = app.http.post(&url).bearer_auth(token).send().await?;
+    let r = app.post_action(&url, |r| r.bearer_auth(token)).await?;
     let status = r.status();
     let t = r.text().await.unwrap_or_default();
 
@@ -379,7 +462,7 @@ pub async fn manager_publish_event(app: &Arc<App>, token: &str, event_id: Uuid)
 
 pub async fn manager_unpublish_event(app: &Arc<App>, token: &str, event_id: Uuid) -> Result<()> {
     let url = format!("{}/api/v1/events/{}/unpublish", app.base_url, event_id);
-    let r = app.http.post(&url).bearer_auth(token).send().await?;
+    let r = app.post_action(&url, |r| r.bearer_auth(token)).await?;
     let status = r.status();
     let t = r.text().await.unwrap_or_default();
 
@@ -396,7 +479,7 @@ pub async fn manager_set_event_deadline(
     let url = format!("{}/api/v1/events/{}/deadline", app.base_url, event_id);
     let body = json!({ "deadline": iso_opt });
     println!("[bot][api] -> POST {url} body={}", body);
-    let r = app.http.post(&url).bearer_auth(token).json(&body).send().await?;
+    let r = app.post_action(&url, |r| r.bearer_auth(token).json(&body)).await?;
     let status = r.status();
     let text = r.text().await.unwrap_or_default();
     println!("[bot][api] <- status={status} {}", truncate(&text, 300));
@@ -409,7 +492,7 @@ pub async fn manager_set_event_deadline(
 pub async fn get_user(app:&Arc<App>, token:&str, user_id:Uuid) -> Result<dto::UserOut> {
     let url = format!("{}/api/v1/users/{}", app.base_url, user_id);
     println!("[bot][api] -> GET {url}");
-    let r = app.http.get(&url).bearer_auth(token).send().await?;
+    let r = app.get(&url).bearer_auth(token).send().await?;
     let status = r.status();
     let text = r.text().await.unwrap_or_default();
     println!("[bot][api] <- status={status}");
@@ -427,10 +510,7 @@ pub async fn manager_set_event_published(app: &Arc<App>, access_token: &str, eve
     let url = format!("{}/api/v1/events/{}/{}", app.base_url, event_id, action);
 
     println!("[bot][api] -> POST {url}");
-    let resp = app.http.post(&url)
-        .bearer_auth(access_token)
-        .send()
-        .await?;
+    let resp = app.post_action(&url, |r| r.bearer_auth(access_token)).await?;
 
     let status = resp.status();
     let text = resp.text().await.unwrap_or_default();
@@ -451,7 +531,7 @@ pub async fn manager_set_event_published(app: &Arc<App>, access_token: &str, eve
 //     println!("[bot][api] -> POST {url} (auth)");
 //     println!("[bot][api] body: {}", body);
 //
-//     let resp = app.http.post(&url).bearer_auth(access_token).json(&body).send().await?;
+//     let resp = app.post(&url).bearer_auth(access_token).json(&body).send().await?;
 //     let status = resp.status();
 //     let text = resp.text().await.unwrap_or_default();
 //
@@ -1,5 +1,12 @@
 use std::sync::Arc;
-use reqwest::Client;
+use std::time::Duration;
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use uuid::Uuid;
+
+// попыток на одно действие пользователя, включая первую
+const ACTION_ATTEMPTS: u32 = 3;
+// без таймаута зависший запрос никогда не дошёл бы до повтора
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
 
 #[derive(Clone)]
 pub struct App {
@@ -16,12 +23,64 @@ impl App {
         let ping_url = std::env::var("BACKEND_PING_URL")
             .unwrap_or_else(|_| format!("{base_url}/health"));
         Self {
-            http: Client::new(),
+            http: Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("reqwest client"),
             base_url,
             ping_url,
         }
     }
 
+    // Каждый запрос к бэкенду получает свой x-request-id: он печатается здесь
+    // и возвращается бэкендом в ответах об ошибках, так что логи можно сопоставить
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let request_id = Uuid::new_v4().to_string();
+        println!("[bot][api] request-id={request_id} {method} {url}");
+        self.http.request(method, url).header("x-request-id", request_id)
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder { self.request(Method::GET, url) }
+    pub fn post(&self, url: &str) -> RequestBuilder { self.request(Method::POST, url) }
+
+    // Изменяющий запрос от имени пользователя. Idempotency-Key создаётся один раз на действие
+    // и повторяется в ретраях: если ответ потерялся по дороге, бэкенд вернёт сохранённый
+    // результат вместо второй записи или второго события. build вызывается на каждую попытку
+    pub async fn send_action(
+        &self,
+        method: Method,
+        url: &str,
+        build: impl Fn(RequestBuilder) -> RequestBuilder,
+    ) -> reqwest::Result<Response> {
+        let key = Uuid::new_v4().to_string();
+        let mut attempt = 1;
+        loop {
+            let res = build(self.request(method.clone(), url).header("idempotency-key", &key))
+                .send()
+                .await;
+            let retry = match &res {
+                Ok(r) => matches!(
+                    r.status(),
+                    StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+                ),
+                Err(e) => e.is_connect() || e.is_timeout(),
+            };
+            if !retry || attempt == ACTION_ATTEMPTS {
+                return res;
+            }
+            println!("[bot][api] retry {attempt}/{} idempotency-key={key} {method} {url}", ACTION_ATTEMPTS - 1);
+            tokio::time::sleep(Duration::from_millis(500 * u64::from(attempt))).await;
+            attempt += 1;
+        }
+    }
+
+    pub async fn post_action(&self, url: &str, build: impl Fn(RequestBuilder) -> RequestBuilder) -> reqwest::Result<Response> {
+        self.send_action(Method::POST, url, build).await
+    }
+    pub async fn patch_action(&self, url: &str, build: impl Fn(RequestBuilder) -> RequestBuilder) -> reqwest::Result<Response> {
+        self.send_action(Method::PATCH, url, build).await
+    }
+
     pub fn shared() -> Arc<Self> {
         Arc::new(Self::from_env())
     }
@@ -65,6 +65,7 @@ pub enum State {
     ManagerNewEventLocation{ token: String, company_id: Uuid, title: String, short_desc: String, starts_at: String, ends_at: String, signup_deadline: String },
     ManagerNewEventCapacity{ token: String, company_id: Uuid, title: String, short_desc: String, starts_at: String, ends_at: String, signup_deadline: String, location: String },
     ManagerNewEventPublish { token: String, company_id: Uuid, title: String, short_desc: String, starts_at: String, ends_at: String, signup_deadline: String, location: String, capacity: Option<i32> },
+    ManagerNewEventRepeat  { token: String, company_id: Uuid, title: String, short_desc: String, starts_at: String, ends_at: String, signup_deadline: String, location: String, capacity: Option<i32>, publish: bool },
 }
 
 impl Default for State {
@@ -292,14 +293,22 @@ pub async fn handle_message(bot: Bot, msg: Message, d: MyDialogue, app: Arc<App>
             match api::register_student_and_link(&app, &email, &text, tg_id).await {
                 Ok(tokens) => {
                     d.update(State::StudentMenu { token: tokens.access_token }).await?;
-                    bot.send_message(chat_id, "Студент зарегистрирован! Меню студента:")
+                    bot.send_message(chat_id, "Студент зарегистрирован! Мы отправили письмо для подтверждения email — перейдите по ссылке из него. Меню студента:")
                         .reply_markup(student_keyboard())
                         .await?;
                 }
                 Err(e) => {
                     bot.send_message(chat_id, format!("Ошибка регистрации: {e}")).await?;
-                    bot.send_message(chat_id, "Введите email ещё раз:").await?;
-                    d.update(State::RegStudentEmail).await?;
+                    // email принят, не подошёл только пароль — спрашиваем только его
+                    let only_password = e.downcast_ref::<api::BackendError>()
+                        .is_some_and(|b| b.has_field("password") && !b.has_field("email"));
+                    if only_password {
+                        bot.send_message(chat_id, "Придумайте другой пароль:").await?;
+                        d.update(State::RegStudentPassword { email }).await?;
+                    } else {
+                        bot.send_message(chat_id, "Введите email ещё раз:").await?;
+                        d.update(State::RegStudentEmail).await?;
+                    }
                 }
             }
         }
@@ -535,6 +544,7 @@ pub async fn handle_message(bot: Bot, msg: Message, d: MyDialogue, app: Arc<App>
                                  "Редактирование:\n\
          /publish on|off — опубликовать/снять\n\
          /deadline <ISO8601|null> — задать дедлайн или убрать\n\
+         /title <название> — переименовать\n\
          \"Назад\" — вернуться"
                 )
                     .reply_markup(manager_event_menu_keyboard())
@@ -548,7 +558,12 @@ pub async fn handle_message(bot: Bot, msg: Message, d: MyDialogue, app: Arc<App>
                         } else {
                             let mut lines = vec!["Записавшиеся:".to_string()];
                             for r in regs {
-                                lines.push(format!("{} <{}> — {}", r.student_name, r.student_email, r.registered_at));
+                                let mark = match r.status.as_deref() {
+                                    Some("attended") => " ✅",
+                                    Some("no_show") => " ❌",
+                                    _ => "",
+                                };
+                                lines.push(format!("{} <{}> — {}{mark}", r.student_name, r.student_email, r.registered_at));
                             }
                             bot.send_message(chat_id, lines.join("\n")).await?;
                         }
@@ -603,7 +618,7 @@ pub async fn handle_message(bot: Bot, msg: Message, d: MyDialogue, app: Arc<App>
                         Ok(_) => {
                             bot.send_message(
                                 chat_id,
-                                if flag { "Опубликовано." } else { "Снято с публикации." }
+                                if flag { "Опубликовано (или отправлено на модерацию)." } else { "Снято с публикации." }
                             ).await?;
                         }
                         Err(e) => {
@@ -624,10 +639,19 @@ pub async fn handle_message(bot: Bot, msg: Message, d: MyDialogue, app: Arc<App>
                             bot.send_message(chat_id, format!("Ошибка: {e}")).await?;
                         }
                     }
+                } else if let Some(title) = other.strip_prefix("/title ").map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                    match api::manager_update_event_title(&app, &token, event_id, title).await {
+                        Ok(_) => {
+                            bot.send_message(chat_id, "Название обновлено.").await?;
+                        }
+                        Err(e) => {
+                            bot.send_message(chat_id, format!("Ошибка: {e}")).await?;
+                        }
+                    }
                 } else {
                     bot.send_message(
                         chat_id,
-                        "Команды: /publish on|off, /deadline <ISO|null>, либо \"Назад\"."
+                        "Команды: /publish on|off, /deadline <ISO|null>, /title <название>, либо \"Назад\"."
                     ).await?;
                 }
             }
@@ -755,15 +779,45 @@ pub async fn handle_message(bot: Bot, msg: Message, d: MyDialogue, app: Arc<App>
 
         State::ManagerNewEventPublish { token, company_id, title, short_desc, starts_at, ends_at, signup_deadline, location, capacity } => {
             let publish = matches!(text.to_lowercase().as_str(), "да" | "yes" | "y" | "true" | "1");
-            match api::manager_create_event(
-                &app, &token, company_id,
-                &title, &short_desc,
-                &starts_at, &ends_at, &signup_deadline,
-                &location, capacity, publish
-            ).await {
-                Ok(_) => {
+            d.update(State::ManagerNewEventRepeat {
+                token, company_id, title, short_desc, starts_at, ends_at, signup_deadline, location, capacity, publish
+            }).await?;
+            bot.send_message(chat_id, "Повторять? «нет», «еженедельно N» или «раз в 2 недели N», где N — число встреч.").await?;
+        }
+
+        State::ManagerNewEventRepeat { token, company_id, title, short_desc, starts_at, ends_at, signup_deadline, location, capacity, publish } => {
+            let repeat = match parse_repeat(&text) {
+                Ok(r) => r,
+                Err(hint) => {
+                    bot.send_message(chat_id, hint).await?;
+                    return Ok(());
+                }
+            };
+            let res = match repeat {
+                None => api::manager_create_event(
+                    &app, &token, company_id,
+                    &title, &short_desc,
+                    &starts_at, &ends_at, &signup_deadline,
+                    &location, capacity, publish
+                ).await.map(|v| (None, v["status"] == "pending_review")),
+                Some((frequency, count)) => api::manager_create_event_series(
+                    &app, &token,
+                    &title, &short_desc,
+                    &starts_at, &ends_at, &signup_deadline,
+                    &location, capacity, publish,
+                    frequency, count
+                ).await.map(|n| (Some(n), false)),
+            };
+            match res {
+                Ok((series, pending)) => {
                     d.update(State::ManagerMenu { token, company_id: Some(company_id) }).await?;
-                    bot.send_message(chat_id, if publish { "Ивент создан и опубликован." } else { "Ивент создан (черновик)." })
+                    let msg = match series {
+                        Some(n) => format!("Создана серия из {n} ивентов{}.", if publish { " (опубликованы или отправлены на модерацию)" } else { " (черновики)" }),
+                        None if pending => "Ивент создан и отправлен деканату на модерацию.".to_string(),
+                        None if publish => "Ивент создан и опубликован.".to_string(),
+                        None => "Ивент создан (черновик).".to_string(),
+                    };
+                    bot.send_message(chat_id, msg)
                         .reply_markup(manager_keyboard())
                         .await?;
                 }
@@ -777,6 +831,26 @@ pub async fn handle_message(bot: Bot, msg: Message, d: MyDialogue, app: Arc<App>
     Ok(())
 }
 
+// «нет» -> None; «еженедельно 8» -> weekly x8; «раз в 2 недели 6» -> biweekly x6
+fn parse_repeat(text: &str) -> Result<Option<(&'static str, i32)>, &'static str> {
+    const HINT: &str = "Не понял. Пример: «нет», «еженедельно 8», «раз в 2 недели 6».";
+    let t = text.trim().to_lowercase();
+    if t.is_empty() || t == "нет" || t == "no" {
+        return Ok(None);
+    }
+    let frequency = if t.contains("2 недел") || t.contains("biweekly") {
+        "biweekly"
+    } else if t.contains("недел") || t.contains("weekly") {
+        "weekly"
+    } else {
+        return Err(HINT);
+    };
+    match t.split_whitespace().last().and_then(|n| n.parse::<i32>().ok()) {
+        Some(n) if (1..=100).contains(&n) => Ok(Some((frequency, n))),
+        _ => Err(HINT),
+    }
+}
+
 /* ===== Callbacks (inline) ===== */
 
 pub async fn handle_callback(bot: Bot, q: CallbackQuery, d: MyDialogue, app: Arc<App>) -> anyhow::Result<()> {
@@ -791,6 +865,10 @@ pub async fn handle_callback(bot: Bot, q: CallbackQuery, d: MyDialogue, app: Arc
             if let Some(State::StudentMenu { token }) = d.get().await? {
                 bot.answer_callback_query(q.id.clone()).await.ok();
                 match api::student_register_event(&app, &token, eid).await {
+                    Ok(st) if st.status == "waitlisted" => {
+                        let pos = st.waitlist_position.map(|p| p.to_string()).unwrap_or_else(|| "?".into());
+                        bot.edit_message_text(chat_id, msg_id, format!("Мест нет — вы в листе ожидания, позиция {pos} ⏳")).await.ok();
+                    }
                     Ok(_) => {
                         bot.edit_message_text(chat_id, msg_id, "Готово: записан ✅").await.ok();
                     }
@@ -829,7 +907,7 @@ pub async fn handle_callback(bot: Bot, q: CallbackQuery, d: MyDialogue, app: Arc
                 match api::register_manager_and_link(&app, &email, &password, company_id, q.from.id.0 as i64).await {
                     Ok(tokens) => {
                         d.update(State::ManagerMenu { token: tokens.access_token, company_id: Some(company_id) }).await?;
-                        bot.send_message(chat_id, "Менеджер зарегистрирован! Заявка отправлена на одобрение.")
+                        bot.send_message(chat_id, "Менеджер зарегистрирован! Заявка отправлена на одобрение. Подтвердите email по ссылке из письма.")
                             .await?;
                         bot.send_message(chat_id, "Меню менеджера:")
                             .reply_markup(manager_keyboard())
@@ -837,6 +915,12 @@ pub async fn handle_callback(bot: Bot, q: CallbackQuery, d: MyDialogue, app: Arc
                     }
                     Err(e) => {
                         bot.send_message(chat_id, format!("Ошибка регистрации: {e}")).await?;
+                        let bad_password = e.downcast_ref::<api::BackendError>()
+                            .is_some_and(|b| b.has_field("password"));
+                        if bad_password {
+                            bot.send_message(chat_id, "Придумайте другой пароль:").await?;
+                            d.update(State::RegManagerPassword { email }).await?;
+                        }
                     }
                 }
             }
@@ -63,6 +63,12 @@ pub struct EventShort {
     // is_registered
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct EventPage {
+    pub items: Vec<EventShort>,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct RegistrationEntry {
@@ -70,4 +76,11 @@ pub struct RegistrationEntry {
     pub student_name: String,
     pub student_email: String,
     pub registered_at: String, // ISO
-}
\ No newline at end of file
+    pub status: Option<String>, // "registered" | "attended" | "no_show"
+}
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct RegistrationState {
+    pub status: String,                  // "registered" | "waitlisted" | ...
+    pub waitlist_position: Option<i64>,
+}
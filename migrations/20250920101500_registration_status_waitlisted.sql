@@ -0,0 +1,2 @@
+-- Лист ожидания: отдельная миграция, т.к. новое значение enum нельзя использовать в той же транзакции
+ALTER TYPE registration_status ADD VALUE IF NOT EXISTS 'waitlisted';
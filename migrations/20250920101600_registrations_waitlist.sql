@@ -0,0 +1,8 @@
+-- Момент постановки в очередь: определяет порядок листа ожидания
+ALTER TABLE registrations
+    ADD COLUMN IF NOT EXISTS waitlisted_at timestamptz NULL;
+
+-- Очередь по событию: голова очереди — самый ранний waitlisted_at
+CREATE INDEX IF NOT EXISTS ix_registrations_event_waitlist
+    ON registrations (event_id, waitlisted_at, student_id)
+    WHERE status = 'waitlisted';
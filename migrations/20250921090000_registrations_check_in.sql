@@ -0,0 +1,7 @@
+-- Отметка о посещении: кто и когда отметил студента на входе
+ALTER TABLE registrations
+    ADD COLUMN IF NOT EXISTS checked_in_at timestamptz NULL,
+    ADD COLUMN IF NOT EXISTS checked_in_by uuid NULL REFERENCES users (id) ON DELETE SET NULL;
+
+-- Для перевода registered -> no_show после окончания события
+CREATE INDEX IF NOT EXISTS ix_events_ends_at ON events (COALESCE(ends_at, starts_at));
@@ -0,0 +1,32 @@
+-- Серии событий: правило повторения, по которому материализуются строки events
+DO $$ BEGIN
+    CREATE TYPE series_frequency AS ENUM ('weekly', 'biweekly', 'custom');
+EXCEPTION WHEN duplicate_object THEN NULL; END $$;
+
+CREATE TABLE IF NOT EXISTS event_series
+(
+    id           uuid PRIMARY KEY,
+    company_id   uuid             NOT NULL REFERENCES companies (id) ON DELETE RESTRICT,
+    manager_id   uuid             NOT NULL REFERENCES managers (user_id) ON DELETE RESTRICT,
+    frequency    series_frequency NOT NULL,
+    until        timestamptz      NULL,
+    occurrences  integer          NULL CHECK (occurrences IS NULL OR occurrences >= 1),
+    custom_dates timestamptz[]    NOT NULL DEFAULT '{}',
+    created_at   timestamptz      NOT NULL DEFAULT now(),
+    updated_at   timestamptz      NOT NULL DEFAULT now()
+);
+
+DROP TRIGGER IF EXISTS set_event_series_updated_at ON event_series;
+CREATE TRIGGER set_event_series_updated_at
+    BEFORE UPDATE
+    ON event_series
+    FOR EACH ROW
+EXECUTE FUNCTION trg_set_updated_at();
+
+-- Вхождение серии: ссылка и порядковый номер (с 0)
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS series_id    uuid    NULL REFERENCES event_series (id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS series_index integer NULL;
+
+CREATE INDEX IF NOT EXISTS ix_events_series ON events (series_id, starts_at)
+    WHERE series_id IS NOT NULL;
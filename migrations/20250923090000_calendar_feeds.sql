@@ -0,0 +1,80 @@
+-- iCalendar: номер ревизии события (SEQUENCE в RFC 5545);
+-- was_published — событие хоть раз попадало в ленты и снимать его надо через CANCELLED
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS sequence      integer NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS was_published boolean NOT NULL DEFAULT false;
+
+UPDATE events SET was_published = true WHERE is_published AND NOT was_published;
+
+-- ревизия растёт только при изменениях, видимых в календаре
+CREATE OR REPLACE FUNCTION trg_bump_event_sequence()
+    RETURNS trigger AS
+$$
+BEGIN
+    NEW.was_published := NEW.was_published OR NEW.is_published;
+    IF TG_OP = 'UPDATE' AND (NEW.title IS DISTINCT FROM OLD.title
+        OR NEW.description IS DISTINCT FROM OLD.description
+        OR NEW.location IS DISTINCT FROM OLD.location
+        OR NEW.starts_at IS DISTINCT FROM OLD.starts_at
+        OR NEW.ends_at IS DISTINCT FROM OLD.ends_at
+        OR NEW.is_published IS DISTINCT FROM OLD.is_published) THEN
+        NEW.sequence := OLD.sequence + 1;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS bump_events_sequence ON events;
+CREATE TRIGGER bump_events_sequence
+    BEFORE INSERT OR UPDATE
+    ON events
+    FOR EACH ROW
+EXECUTE FUNCTION trg_bump_event_sequence();
+
+-- Удалённые события: остаются в лентах как CANCELLED, чтобы календари их убрали
+CREATE TABLE IF NOT EXISTS deleted_events
+(
+    id          uuid PRIMARY KEY,
+    company_id  uuid        NOT NULL,
+    title       text        NOT NULL,
+    starts_at   timestamptz NOT NULL,
+    ends_at     timestamptz NULL,
+    sequence    integer     NOT NULL,
+    student_ids uuid[]      NOT NULL DEFAULT '{}',
+    deleted_at  timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS ix_deleted_events_company ON deleted_events (company_id, deleted_at);
+CREATE INDEX IF NOT EXISTS ix_deleted_events_students ON deleted_events USING gin (student_ids);
+
+CREATE OR REPLACE FUNCTION trg_remember_deleted_event()
+    RETURNS trigger AS
+$$
+BEGIN
+    IF OLD.was_published THEN
+        INSERT INTO deleted_events (id, company_id, title, starts_at, ends_at, sequence, student_ids)
+        SELECT OLD.id, OLD.company_id, OLD.title, OLD.starts_at, OLD.ends_at, OLD.sequence + 1,
+               COALESCE(array_agg(r.student_id), '{}')
+        FROM registrations r
+        WHERE r.event_id = OLD.id
+          AND r.status <> 'canceled'
+        ON CONFLICT (id) DO NOTHING;
+    END IF;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS remember_deleted_event ON events;
+CREATE TRIGGER remember_deleted_event
+    BEFORE DELETE
+    ON events
+    FOR EACH ROW
+EXECUTE FUNCTION trg_remember_deleted_event();
+
+-- Секретный токен личной ленты студента; храним только sha256
+CREATE TABLE IF NOT EXISTS calendar_feed_tokens
+(
+    user_id    uuid PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    token_hash text        NOT NULL UNIQUE,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
@@ -0,0 +1,22 @@
+-- Незавершённые OAuth-авторизации Google: state → пользователь и PKCE verifier
+CREATE TABLE IF NOT EXISTS google_oauth_states
+(
+    state         text PRIMARY KEY,
+    user_id       uuid        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    code_verifier text        NOT NULL,
+    expires_at    timestamptz NOT NULL,
+    created_at    timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS ix_google_oauth_states_expires ON google_oauth_states (expires_at);
+
+-- копии в Google Calendar ищем по событию
+CREATE INDEX IF NOT EXISTS ix_registrations_gcal ON registrations (event_id)
+    WHERE gcal_event_id IS NOT NULL;
+
+DROP TRIGGER IF EXISTS set_google_accounts_updated_at ON google_accounts;
+CREATE TRIGGER set_google_accounts_updated_at
+    BEFORE UPDATE
+    ON google_accounts
+    FOR EACH ROW
+EXECUTE FUNCTION trg_set_updated_at();
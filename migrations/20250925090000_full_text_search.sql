@@ -0,0 +1,118 @@
+-- Полнотекстовый поиск: русская и английская морфология одновременно,
+-- чтобы находились и «митапы» по «митап», и «meetups» по «meetup»
+CREATE OR REPLACE FUNCTION search_doc(body text, weight "char")
+    RETURNS tsvector AS
+$$
+SELECT setweight(to_tsvector('russian', COALESCE(body, '')), weight)
+           || setweight(to_tsvector('english', COALESCE(body, '')), weight)
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE OR REPLACE FUNCTION search_query(q text)
+    RETURNS tsquery AS
+$$
+SELECT websearch_to_tsquery('russian', q) || websearch_to_tsquery('english', q)
+$$ LANGUAGE sql IMMUTABLE;
+
+ALTER TABLE companies
+    ADD COLUMN IF NOT EXISTS search_tsv tsvector NOT NULL DEFAULT ''::tsvector;
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS search_tsv tsvector NOT NULL DEFAULT ''::tsvector;
+
+CREATE OR REPLACE FUNCTION trg_companies_search_tsv()
+    RETURNS trigger AS
+$$
+BEGIN
+    NEW.search_tsv := search_doc(NEW.name, 'A') || search_doc(NEW.description, 'C');
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS companies_search_tsv ON companies;
+CREATE TRIGGER companies_search_tsv
+    BEFORE INSERT OR UPDATE OF name, description
+    ON companies
+    FOR EACH ROW
+EXECUTE FUNCTION trg_companies_search_tsv();
+
+-- событие ищется и по названию компании
+CREATE OR REPLACE FUNCTION trg_events_search_tsv()
+    RETURNS trigger AS
+$$
+DECLARE
+    company_name text;
+BEGIN
+    SELECT c.name INTO company_name FROM companies c WHERE c.id = NEW.company_id;
+    NEW.search_tsv := search_doc(NEW.title, 'A')
+                          || search_doc(company_name, 'B')
+                          || search_doc(NEW.location, 'B')
+                          || search_doc(NEW.description, 'C');
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS events_search_tsv ON events;
+CREATE TRIGGER events_search_tsv
+    BEFORE INSERT OR UPDATE OF title, description, location, company_id, search_tsv
+    ON events
+    FOR EACH ROW
+EXECUTE FUNCTION trg_events_search_tsv();
+
+-- переименование компании переиндексирует её события
+CREATE OR REPLACE FUNCTION trg_companies_reindex_events()
+    RETURNS trigger AS
+$$
+BEGIN
+    UPDATE events SET search_tsv = ''::tsvector WHERE company_id = NEW.id;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS companies_reindex_events ON companies;
+CREATE TRIGGER companies_reindex_events
+    AFTER UPDATE OF name
+    ON companies
+    FOR EACH ROW
+    WHEN (OLD.name IS DISTINCT FROM NEW.name)
+EXECUTE FUNCTION trg_companies_reindex_events();
+
+-- переиндексация из триггера не меняет менеджера, проверять его статус незачем:
+-- иначе события заблокированного менеджера не дали бы переименовать компанию
+CREATE OR REPLACE FUNCTION trg_events_manager_must_be_confirmed() RETURNS trigger AS $$
+DECLARE st manager_status;
+BEGIN
+    IF TG_OP = 'UPDATE' AND pg_trigger_depth() > 1 AND NEW.manager_id = OLD.manager_id THEN
+        RETURN NEW;
+    END IF;
+    SELECT m.status INTO st FROM managers m WHERE m.user_id = NEW.manager_id;
+    IF st IS NULL THEN
+        RAISE EXCEPTION 'manager % not found', NEW.manager_id;
+    ELSIF st <> 'confirmed' THEN
+        RAISE EXCEPTION 'manager % must be confirmed (got %)', NEW.manager_id, st;
+    END IF;
+    RETURN NEW;
+END; $$ LANGUAGE plpgsql;
+
+-- заполнение существующих строк; updated_at при этом не трогаем
+ALTER TABLE companies DISABLE TRIGGER set_companies_updated_at;
+UPDATE companies SET search_tsv = search_doc(name, 'A') || search_doc(description, 'C');
+ALTER TABLE companies ENABLE TRIGGER set_companies_updated_at;
+
+ALTER TABLE events DISABLE TRIGGER events_manager_guard;
+ALTER TABLE events DISABLE TRIGGER set_events_updated_at;
+UPDATE events SET search_tsv = ''::tsvector;
+ALTER TABLE events ENABLE TRIGGER set_events_updated_at;
+ALTER TABLE events ENABLE TRIGGER events_manager_guard;
+
+CREATE INDEX IF NOT EXISTS ix_companies_search ON companies USING gin (search_tsv);
+CREATE INDEX IF NOT EXISTS ix_events_search ON events USING gin (search_tsv);
+
+-- Фрагмент с подсветкой совпадений; текст экранируется до вставки <mark>,
+-- поэтому результат можно отдавать клиенту как HTML
+CREATE OR REPLACE FUNCTION search_snippet(body text, q tsquery)
+    RETURNS text AS
+$$
+SELECT ts_headline('russian',
+                   replace(replace(replace(COALESCE(body, ''), '&', '&amp;'), '<', '&lt;'), '>', '&gt;'),
+                   q,
+                   'StartSel=<mark>, StopSel=</mark>, MaxWords=30, MinWords=12, MaxFragments=2, FragmentDelimiter=" … "')
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,27 @@
+-- Словарь тегов ведёт деканат; category группирует теги в меню фильтров
+-- (например «направление»: backend, ml; «формат»: career-fair)
+CREATE TABLE IF NOT EXISTS tags
+(
+    id         uuid PRIMARY KEY,
+    slug       text        NOT NULL UNIQUE CHECK (slug ~ '^[a-z0-9]+(-[a-z0-9]+)*$'),
+    name       text        NOT NULL CHECK (length(trim(name)) > 0),
+    category   text        NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz NOT NULL DEFAULT now()
+);
+
+DROP TRIGGER IF EXISTS set_tags_updated_at ON tags;
+CREATE TRIGGER set_tags_updated_at
+    BEFORE UPDATE
+    ON tags
+    FOR EACH ROW
+EXECUTE FUNCTION trg_set_updated_at();
+
+CREATE TABLE IF NOT EXISTS event_tags
+(
+    event_id uuid NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    tag_id   uuid NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+    PRIMARY KEY (event_id, tag_id)
+);
+
+CREATE INDEX IF NOT EXISTS ix_event_tags_tag ON event_tags (tag_id, event_id);
@@ -0,0 +1,61 @@
+-- Модерация публикаций: жизненный цикл события вместо голого is_published.
+-- is_published остаётся производным (status = 'published'), на нём держатся
+-- выборки для студентов, календари и поиск
+DO
+$$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'event_status') THEN
+        CREATE TYPE event_status AS ENUM ('draft', 'pending_review', 'published', 'rejected');
+    END IF;
+END
+$$;
+
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS status        event_status NOT NULL DEFAULT 'draft',
+    ADD COLUMN IF NOT EXISTS submitted_at  timestamptz  NULL,
+    ADD COLUMN IF NOT EXISTS reviewed_by   uuid         NULL REFERENCES users (id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS reviewed_at   timestamptz  NULL,
+    ADD COLUMN IF NOT EXISTS review_reason text         NULL;
+
+-- проверенные партнёры публикуют без модерации
+ALTER TABLE companies
+    ADD COLUMN IF NOT EXISTS requires_event_review boolean NOT NULL DEFAULT true;
+
+-- Старый код пишет только is_published: переводим это в статус.
+-- Явная смена статуса главнее. Имя триггера выбрано так, чтобы он шёл раньше
+-- bump_events_sequence, которому нужен уже согласованный is_published
+CREATE OR REPLACE FUNCTION trg_align_event_status()
+    RETURNS trigger AS
+$$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        IF NEW.is_published AND NEW.status = 'draft' THEN
+            NEW.status := 'published';
+        END IF;
+    ELSIF NEW.status IS NOT DISTINCT FROM OLD.status
+        AND NEW.is_published IS DISTINCT FROM OLD.is_published THEN
+        NEW.status := CASE WHEN NEW.is_published THEN 'published' ELSE 'draft' END::event_status;
+    END IF;
+    NEW.is_published := NEW.status = 'published';
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS align_events_status ON events;
+CREATE TRIGGER align_events_status
+    BEFORE INSERT OR UPDATE
+    ON events
+    FOR EACH ROW
+EXECUTE FUNCTION trg_align_event_status();
+
+ALTER TABLE events DISABLE TRIGGER events_manager_guard;
+ALTER TABLE events DISABLE TRIGGER set_events_updated_at;
+UPDATE events SET status = 'published' WHERE is_published AND status <> 'published';
+ALTER TABLE events ENABLE TRIGGER set_events_updated_at;
+ALTER TABLE events ENABLE TRIGGER events_manager_guard;
+
+ALTER TABLE events DROP CONSTRAINT IF EXISTS chk_event_status_published;
+ALTER TABLE events
+    ADD CONSTRAINT chk_event_status_published CHECK (is_published = (status = 'published'));
+
+CREATE INDEX IF NOT EXISTS ix_events_review_queue ON events (submitted_at) WHERE status = 'pending_review';
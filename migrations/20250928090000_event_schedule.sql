@@ -0,0 +1,18 @@
+-- Отложенная публикация и автоматическое снятие с публикации.
+-- auto_*_at — когда воркер действительно отработал расписание: повторно он
+-- событие не трогает, а смена времени в расписании сбрасывает отметку
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS publish_at          timestamptz NULL,
+    ADD COLUMN IF NOT EXISTS unpublish_at        timestamptz NULL,
+    ADD COLUMN IF NOT EXISTS auto_published_at   timestamptz NULL,
+    ADD COLUMN IF NOT EXISTS auto_unpublished_at timestamptz NULL;
+
+ALTER TABLE events DROP CONSTRAINT IF EXISTS chk_event_schedule;
+ALTER TABLE events
+    ADD CONSTRAINT chk_event_schedule
+        CHECK (publish_at IS NULL OR unpublish_at IS NULL OR unpublish_at > publish_at);
+
+CREATE INDEX IF NOT EXISTS ix_events_publish_due
+    ON events (publish_at) WHERE publish_at IS NOT NULL AND auto_published_at IS NULL;
+CREATE INDEX IF NOT EXISTS ix_events_unpublish_due
+    ON events (unpublish_at) WHERE unpublish_at IS NOT NULL AND auto_unpublished_at IS NULL;
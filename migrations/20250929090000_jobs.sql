@@ -0,0 +1,43 @@
+-- Очередь фоновых задач. Задачу кладут в той же транзакции, что и изменение,
+-- которое её породило; воркеры разбирают очередь через FOR UPDATE SKIP LOCKED.
+DO
+$$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'job_status') THEN
+        CREATE TYPE job_status AS ENUM ('queued', 'running', 'done', 'dead');
+    END IF;
+END
+$$;
+
+CREATE TABLE IF NOT EXISTS jobs
+(
+    id           uuid PRIMARY KEY,
+    kind         text        NOT NULL,
+    payload      jsonb       NOT NULL DEFAULT '{}'::jsonb,
+    -- одинаковые задачи, ещё ждущие в очереди, схлопываются в одну
+    dedupe_key   text        NULL,
+    status       job_status  NOT NULL DEFAULT 'queued',
+    attempts     integer     NOT NULL DEFAULT 0,
+    max_attempts integer     NOT NULL DEFAULT 8,
+    run_at       timestamptz NOT NULL DEFAULT now(),
+    -- аренда running-задачи: если воркер упал, по истечении её заберёт другой
+    locked_until timestamptz NULL,
+    last_error   text        NULL,
+    finished_at  timestamptz NULL,
+    created_at   timestamptz NOT NULL DEFAULT now(),
+    updated_at   timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT chk_jobs_attempts CHECK (attempts >= 0 AND max_attempts > 0)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_jobs_queued_dedupe
+    ON jobs (kind, dedupe_key) WHERE status = 'queued' AND dedupe_key IS NOT NULL;
+CREATE INDEX IF NOT EXISTS ix_jobs_due ON jobs (run_at) WHERE status = 'queued';
+CREATE INDEX IF NOT EXISTS ix_jobs_lease ON jobs (locked_until) WHERE status = 'running';
+CREATE INDEX IF NOT EXISTS ix_jobs_finished ON jobs (status, finished_at) WHERE status IN ('done', 'dead');
+
+DROP TRIGGER IF EXISTS set_jobs_updated_at ON jobs;
+CREATE TRIGGER set_jobs_updated_at
+    BEFORE UPDATE
+    ON jobs
+    FOR EACH ROW
+EXECUTE FUNCTION trg_set_updated_at();
@@ -0,0 +1,45 @@
+-- Напоминания о событиях. Канал пока один; новые добавляются отдельной миграцией,
+-- т.к. новое значение enum нельзя использовать в той же транзакции
+DO
+$$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'notification_channel') THEN
+        CREATE TYPE notification_channel AS ENUM ('telegram');
+    END IF;
+END
+$$;
+
+-- отказ пользователя от уведомлений в канале
+CREATE TABLE IF NOT EXISTS notification_opt_outs
+(
+    user_id    uuid                 NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    channel    notification_channel NOT NULL,
+    created_at timestamptz          NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, channel)
+);
+
+-- какие события студент открывал: им напоминаем о дедлайне записи
+CREATE TABLE IF NOT EXISTS event_views
+(
+    event_id        uuid        NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    student_id      uuid        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    first_viewed_at timestamptz NOT NULL DEFAULT now(),
+    last_viewed_at  timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (event_id, student_id)
+);
+
+-- Запланированные напоминания. Строка вставляется вместе с задачей на отправку,
+-- поэтому после рестарта одно и то же напоминание второй раз не уходит
+CREATE TABLE IF NOT EXISTS event_reminders
+(
+    event_id   uuid                 NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    user_id    uuid                 NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    -- start-1440m, start-60m, … или deadline
+    kind       text                 NOT NULL,
+    channel    notification_channel NOT NULL,
+    created_at timestamptz          NOT NULL DEFAULT now(),
+    sent_at    timestamptz          NULL,
+    PRIMARY KEY (event_id, user_id, kind, channel)
+);
+
+CREATE INDEX IF NOT EXISTS ix_event_views_student ON event_views (student_id);
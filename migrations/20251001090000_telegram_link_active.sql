@@ -0,0 +1,5 @@
+-- Пользователь заблокировал бота: связь остаётся (вход через бота по-прежнему работает),
+-- но сообщения от бэкенда ему больше не шлём, пока он не привяжется заново
+ALTER TABLE telegram_links
+    ADD COLUMN IF NOT EXISTS is_active      boolean     NOT NULL DEFAULT true,
+    ADD COLUMN IF NOT EXISTS deactivated_at timestamptz NULL;
@@ -0,0 +1,2 @@
+-- Почта как второй канал уведомлений; от неё тоже можно отказаться в /me/notifications
+ALTER TYPE notification_channel ADD VALUE IF NOT EXISTS 'email';
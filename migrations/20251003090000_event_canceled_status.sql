@@ -0,0 +1,3 @@
+-- Отмена события — отдельный статус; значение enum добавляется отдельной миграцией,
+-- т.к. в той же транзакции его ещё нельзя использовать
+ALTER TYPE event_status ADD VALUE IF NOT EXISTS 'canceled';
@@ -0,0 +1,29 @@
+-- Отменённое событие остаётся в базе вместе с записями: студенты видят его в истории,
+-- а удалять можно только черновики без записей
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS canceled_at   timestamptz NULL,
+    ADD COLUMN IF NOT EXISTS canceled_by   uuid        NULL REFERENCES users (id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS cancel_reason text        NULL;
+
+ALTER TABLE events DROP CONSTRAINT IF EXISTS chk_event_canceled;
+ALTER TABLE events
+    ADD CONSTRAINT chk_event_canceled CHECK ((status = 'canceled') = (canceled_at IS NOT NULL));
+
+-- Отмена необратима: запись is_published старым кодом статус canceled не трогает
+CREATE OR REPLACE FUNCTION trg_align_event_status()
+    RETURNS trigger AS
+$$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        IF NEW.is_published AND NEW.status = 'draft' THEN
+            NEW.status := 'published';
+        END IF;
+    ELSIF NEW.status IS NOT DISTINCT FROM OLD.status
+        AND OLD.status <> 'canceled'
+        AND NEW.is_published IS DISTINCT FROM OLD.is_published THEN
+        NEW.status := CASE WHEN NEW.is_published THEN 'published' ELSE 'draft' END::event_status;
+    END IF;
+    NEW.is_published := NEW.status = 'published';
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
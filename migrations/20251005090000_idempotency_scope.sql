@@ -0,0 +1,12 @@
+-- Ключ идемпотентности уникален в пределах пользователя, метода и пути:
+-- два клиента с одинаковым ключом друг другу не мешают. Анонимные запросы
+-- (регистрация, вход) делят общую область с нулевым user_id
+ALTER TABLE idempotency_keys DROP CONSTRAINT IF EXISTS idempotency_keys_pkey;
+ALTER TABLE idempotency_keys
+    ADD COLUMN IF NOT EXISTS id uuid NOT NULL DEFAULT gen_random_uuid();
+ALTER TABLE idempotency_keys ADD PRIMARY KEY (id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_idempotency_scope
+    ON idempotency_keys (key, (COALESCE(user_id, '00000000-0000-0000-0000-000000000000'::uuid)), method, path);
+-- старые ключи чистит воркер
+CREATE INDEX IF NOT EXISTS ix_idempotency_created ON idempotency_keys (created_at);
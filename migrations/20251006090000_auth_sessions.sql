@@ -0,0 +1,30 @@
+-- Сессия = устройство входа и одновременно семейство refresh-токенов.
+-- Каждый refresh выдаёт новый токен, старый помечается rotated_at; предъявление
+-- уже ротированного токена означает утечку — отзывается вся сессия
+CREATE TABLE IF NOT EXISTS auth_sessions (
+    id            uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id       uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    device_label  text        NULL,
+    user_agent    text        NULL,
+    created_at    timestamptz NOT NULL DEFAULT now(),
+    last_used_at  timestamptz NOT NULL DEFAULT now(),
+    expires_at    timestamptz NOT NULL,
+    revoked_at    timestamptz NULL,
+    revoke_reason text        NULL
+);
+CREATE INDEX IF NOT EXISTS ix_auth_sessions_user ON auth_sessions (user_id) WHERE revoked_at IS NULL;
+CREATE INDEX IF NOT EXISTS ix_auth_sessions_expires ON auth_sessions (expires_at);
+
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    token_hash  text PRIMARY KEY,
+    session_id  uuid        NOT NULL REFERENCES auth_sessions(id) ON DELETE CASCADE,
+    created_at  timestamptz NOT NULL DEFAULT now(),
+    rotated_at  timestamptz NULL
+);
+-- у семейства ровно один действующий токен
+CREATE UNIQUE INDEX IF NOT EXISTS ux_refresh_tokens_current ON refresh_tokens (session_id) WHERE rotated_at IS NULL;
+
+-- единственный токен на пользователя больше не используется; старые входы просто перелогинятся
+ALTER TABLE users
+    DROP COLUMN IF EXISTS refresh_token_hash,
+    DROP COLUMN IF EXISTS refresh_token_expiration;
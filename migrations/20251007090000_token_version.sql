@@ -0,0 +1,50 @@
+-- Версия прав пользователя: попадает в access-токен и сверяется при каждом запросе.
+-- Любая смена роли, пароля, статуса студента или членства менеджера в компании
+-- увеличивает её, и ранее выданные токены перестают приниматься
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS token_version integer NOT NULL DEFAULT 0;
+
+CREATE OR REPLACE FUNCTION trg_users_bump_token_version() RETURNS trigger AS $$
+BEGIN
+    IF NEW.role IS DISTINCT FROM OLD.role OR NEW.password_hash IS DISTINCT FROM OLD.password_hash THEN
+        NEW.token_version := OLD.token_version + 1;
+    END IF;
+    RETURN NEW;
+END; $$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS users_bump_token_version ON users;
+CREATE TRIGGER users_bump_token_version
+    BEFORE UPDATE ON users
+    FOR EACH ROW EXECUTE FUNCTION trg_users_bump_token_version();
+
+CREATE OR REPLACE FUNCTION trg_students_bump_token_version() RETURNS trigger AS $$
+BEGIN
+    IF NEW.status IS DISTINCT FROM OLD.status THEN
+        UPDATE users SET token_version = token_version + 1 WHERE id = NEW.user_id;
+    END IF;
+    RETURN NEW;
+END; $$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS students_bump_token_version ON students;
+CREATE TRIGGER students_bump_token_version
+    AFTER UPDATE OF status ON students
+    FOR EACH ROW EXECUTE FUNCTION trg_students_bump_token_version();
+
+CREATE OR REPLACE FUNCTION trg_managers_bump_token_version() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        UPDATE users SET token_version = token_version + 1 WHERE id = OLD.user_id;
+        RETURN OLD;
+    END IF;
+    IF TG_OP = 'INSERT'
+       OR NEW.status IS DISTINCT FROM OLD.status
+       OR NEW.company_id IS DISTINCT FROM OLD.company_id THEN
+        UPDATE users SET token_version = token_version + 1 WHERE id = NEW.user_id;
+    END IF;
+    RETURN NEW;
+END; $$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS managers_bump_token_version ON managers;
+CREATE TRIGGER managers_bump_token_version
+    AFTER INSERT OR UPDATE OF status, company_id OR DELETE ON managers
+    FOR EACH ROW EXECUTE FUNCTION trg_managers_bump_token_version();
@@ -0,0 +1,10 @@
+-- Одноразовые токены сброса пароля; в базе только sha256 от токена
+CREATE TABLE IF NOT EXISTS password_resets (
+    token_hash text        PRIMARY KEY,
+    user_id    uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    expires_at timestamptz NOT NULL,
+    used_at    timestamptz NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_resets_user ON password_resets(user_id, created_at DESC);
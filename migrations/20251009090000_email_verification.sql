@@ -0,0 +1,16 @@
+-- Подтверждение email: до перехода по ссылке из письма адрес считается непроверенным
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS email_verified_at timestamptz NULL;
+
+-- аккаунты, заведённые до появления проверки, не блокируем
+UPDATE users SET email_verified_at = created_at WHERE email_verified_at IS NULL;
+
+-- Одноразовые токены из письма; в базе только sha256 от токена
+CREATE TABLE IF NOT EXISTS email_verifications (
+    token_hash text        PRIMARY KEY,
+    user_id    uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    expires_at timestamptz NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_verifications_user ON email_verifications(user_id, created_at DESC);
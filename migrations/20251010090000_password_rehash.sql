@@ -0,0 +1,11 @@
+-- Пересчёт хеша того же пароля (смена схемы или параметров при входе) права не меняет:
+-- такая транзакция выставляет app.password_rehash, и версия токенов не растёт
+CREATE OR REPLACE FUNCTION trg_users_bump_token_version() RETURNS trigger AS $$
+BEGIN
+    IF NEW.role IS DISTINCT FROM OLD.role
+       OR (NEW.password_hash IS DISTINCT FROM OLD.password_hash
+           AND coalesce(current_setting('app.password_rehash', true), '') <> 'on') THEN
+        NEW.token_version := OLD.token_version + 1;
+    END IF;
+    RETURN NEW;
+END; $$ LANGUAGE plpgsql;
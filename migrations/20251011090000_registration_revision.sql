@@ -0,0 +1,50 @@
+-- Ревизия записи студента: растёт при каждой смене статуса. В личной ленте
+-- SEQUENCE = events.sequence + revision, так что она не убывает ни при каком переходе
+-- (отмена -> повторная запись -> лист ожидания и т.д.)
+ALTER TABLE registrations
+    ADD COLUMN IF NOT EXISTS revision integer NOT NULL DEFAULT 0;
+
+-- прежняя формула давала 0/1/2 по статусу; начинаем не ниже, чтобы календари не увидели откат
+UPDATE registrations
+   SET revision = CASE status WHEN 'waitlisted' THEN 0 WHEN 'canceled' THEN 2 ELSE 1 END
+ WHERE revision = 0;
+
+CREATE OR REPLACE FUNCTION trg_bump_registration_revision()
+    RETURNS trigger AS
+$$
+BEGIN
+    IF NEW.status IS DISTINCT FROM OLD.status THEN
+        NEW.revision := OLD.revision + 1;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS bump_registration_revision ON registrations;
+CREATE TRIGGER bump_registration_revision
+    BEFORE UPDATE
+    ON registrations
+    FOR EACH ROW
+EXECUTE FUNCTION trg_bump_registration_revision();
+
+-- удалённое событие в личной ленте должно обогнать любую ревизию записи;
+-- для уже удалённых сохраняется прежнее смещение
+ALTER TABLE deleted_events
+    ADD COLUMN IF NOT EXISTS revision integer NOT NULL DEFAULT 2;
+
+CREATE OR REPLACE FUNCTION trg_remember_deleted_event()
+    RETURNS trigger AS
+$$
+BEGIN
+    IF OLD.was_published THEN
+        INSERT INTO deleted_events (id, company_id, title, starts_at, ends_at, sequence, student_ids, revision)
+        SELECT OLD.id, OLD.company_id, OLD.title, OLD.starts_at, OLD.ends_at, OLD.sequence + 1,
+               COALESCE(array_agg(r.student_id) FILTER (WHERE r.status <> 'canceled'), '{}'),
+               COALESCE(max(r.revision), 0) + 1
+        FROM registrations r
+        WHERE r.event_id = OLD.id
+        ON CONFLICT (id) DO NOTHING;
+    END IF;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
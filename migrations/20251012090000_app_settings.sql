@@ -0,0 +1,11 @@
+-- Общие настройки, которыми деканат управляет из интерфейса; всегда ровно одна строка
+CREATE TABLE IF NOT EXISTS app_settings
+(
+    id                     boolean PRIMARY KEY DEFAULT true CHECK (id),
+    -- подтверждать студента можно только с проверенным email
+    require_verified_email boolean     NOT NULL DEFAULT false,
+    updated_at             timestamptz NOT NULL DEFAULT now(),
+    updated_by             uuid        NULL REFERENCES users (id) ON DELETE SET NULL
+);
+
+INSERT INTO app_settings (id) VALUES (true) ON CONFLICT DO NOTHING;
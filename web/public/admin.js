@@ -44,8 +44,10 @@ async function loadCompanies() {
       <td>${c.name}</td>
       <td>${badge(archived ? 'archived':'active', archived?'err':'ok')}</td>
       <td>${c.eventCount ?? 0}</td>
+      <td>${c.requires_event_review ? badge('модерация','warn') : badge('без модерации','ok')}</td>
       <td class="row">
         <button onclick="viewCompanyEvents('${c.id}')">ивенты</button>
+        <button onclick="toggleReview('${c.id}', ${c.requires_event_review})">${c.requires_event_review?'доверять':'модерировать'}</button>
         <button onclick="viewCompanyManagers('${c.id}','${c.name}')">менеджеры</button>
         <button onclick="toggleCompany('${c.id}', ${archived})">${archived?'разархивировать':'в архив'}</button>
       </td>
@@ -53,7 +55,15 @@ async function loadCompanies() {
     });
 
     document.getElementById('companiesTableWrap').innerHTML =
-        table(['Компания','Статус','Ивентов','Действия'], rows);
+        table(['Компания','Статус','Ивентов','Публикация','Действия'], rows);
+}
+
+async function toggleReview(id, requires){
+    const r = await api(`/api/v1/companies/${id}`, {
+        method:'PATCH',
+        body: JSON.stringify({ requires_event_review: !requires })
+    });
+    if(r.ok) loadCompanies();
 }
 
 async function toggleCompany(id, archived){
@@ -70,7 +80,7 @@ async function createCompanyFlow(){
         body: JSON.stringify({ name })
     });
     if(r.ok) loadCompanies();
-    else alert('Ошибка создания компании');
+    else alert(await apiErrorText(r, 'Ошибка создания компании'));
 }
 
 async function viewCompanyManagers(companyId, companyName){
@@ -123,19 +133,30 @@ async function fillCompanyFilter(){
     sel.innerHTML = `<option value="">все компании</option>` + list.map(c=>`<option value="${c.id}">${c.name}</option>`).join('');
 }
 
-async function loadEvents(){
+let eventsCursor = null;
+let eventsLoaded = [];
+
+async function loadEvents(more = false){
     const cid = document.getElementById('filterCompany').value;
     const pub = document.getElementById('filterPublished').value;
+    const tag = document.getElementById('filterTag').value;
     const qs = [];
     if(cid) qs.push(`company_id=${encodeURIComponent(cid)}`);
     if(pub) qs.push(`published=${pub}`);
+    if(tag) qs.push(`tags=${encodeURIComponent(tag)}`);
+    if(more && eventsCursor) qs.push(`cursor=${encodeURIComponent(eventsCursor)}`);
     const url = `/api/v1/events` + (qs.length?`?${qs.join('&')}`:'');
     const r = await api(url);
-    const list = r.ok ? await r.json() : [];
+    const page = r.ok ? await r.json() : {items: [], next_cursor: null, total: 0, facets: []};
 
-    const rows = list.map(e=>`<tr>
-    <td>${e.title}<br/><span class="badge">${new Date(e.starts_at).toLocaleString()}</span></td>
-    <td>${badge(e.is_published?'published':'draft', e.is_published?'ok':'warn')}</td>
+    eventsLoaded = more ? eventsLoaded.concat(page.items) : page.items;
+    eventsCursor = page.next_cursor;
+    if(!more) fillTagFilter(page.facets, tag);
+
+    const rows = eventsLoaded.map(e=>`<tr>
+    <td>${e.title}<br/><span class="badge">${new Date(e.starts_at).toLocaleString()}</span>
+      ${(e.tags || []).map(t=>`<span class="badge">#${escapeHtml(t)}</span>`).join(' ')}</td>
+    <td>${badge(e.status, e.status==='published'?'ok':(e.status==='rejected'?'err':'warn'))}</td>
     <td>${e.registered_count ?? 0}/${e.capacity ?? '∞'}</td>
     <td class="row">
       <button onclick="viewRegistrations('${e.id}','${e.title.replace(/"/g,'&quot;')}')">записи</button>
@@ -146,7 +167,33 @@ async function loadEvents(){
   </tr>`);
 
     document.getElementById('eventsTableWrap').innerHTML =
-        table(['Ивент','Статус','Места','Действия'], rows);
+        table(['Ивент','Статус','Места','Действия'], rows) +
+        `<div class="row"><span class="badge">${eventsLoaded.length} из ${page.total}</span>` +
+        (eventsCursor ? `<button onclick="loadEvents(true)">ещё</button>` : '') +
+        `</div>`;
+}
+
+// меню тегов строится из фасетов текущей выборки; выбранный тег остаётся в списке
+function fillTagFilter(facets, selected){
+    const sel = document.getElementById('filterTag');
+    const opts = (facets || []).map(f=>
+        `<option value="${escapeHtml(f.slug)}" ${f.slug===selected?'selected':''}>${escapeHtml(f.name)} (${f.count})</option>`);
+    if(selected && !(facets || []).some(f=>f.slug===selected))
+        opts.push(`<option value="${escapeHtml(selected)}" selected>${escapeHtml(selected)} (0)</option>`);
+    sel.innerHTML = `<option value="">все теги</option>` + opts.join('');
+}
+
+async function createTagFlow(){
+    const name = prompt('Название тега:');
+    if(!name) return;
+    const slug = prompt('Slug (латиница, цифры, дефис):', name.toLowerCase().replace(/[^a-z0-9]+/g,'-').replace(/^-|-$/g,''));
+    if(!slug) return;
+    const category = prompt('Категория (можно оставить пустой):') || null;
+    const r = await api('/api/v1/tags', {
+        method:'POST',
+        body: JSON.stringify({ slug, name, category })
+    });
+    if(!r.ok) alert('Ошибка создания тега');
 }
 
 async function publishEvent(id){
@@ -165,23 +212,60 @@ async function viewRegistrations(eventId, title){
         const name  = x.student_name  ?? x.studentName  ?? x.student_id ?? x.studentId ?? '—';
         const email = x.student_email ?? x.studentEmail ?? '';
         const when  = new Date(x.registered_at ?? x.registeredAt).toLocaleString();
+        const st    = x.status ?? 'registered';
+        const cls   = st === 'attended' ? 'ok' : (st === 'no_show' ? 'warn' : '');
 
         return `<tr>
           <td>${escapeHtml(name)}${email ? `<br/><span class="badge">${escapeHtml(email)}</span>` : ''}</td>
           <td>${escapeHtml(when)}</td>
+          <td>${badge(escapeHtml(st), cls)}</td>
         </tr>`;
     });
+    const attended = regs.filter(x => x.status === 'attended').length;
 
     document.getElementById('eventsTableWrap').innerHTML =
         `<div class="card">
           <div class="toolbar">
             <strong>Записавшиеся: ${escapeHtml(title)}</strong>
+            <span class="badge">пришли: ${attended}/${regs.length}</span>
             <button class="right" onclick="loadEvents()">← назад</button>
           </div>
-          ${table(['Студент','Когда'], rows)}
+          ${table(['Студент','Когда','Статус'], rows)}
         </div>`;
 }
 
+// ---- Модерация ивентов ----
+async function loadPendingEvents(){
+    const r = await api('/api/v1/events/review-queue');
+    const list = r.ok ? await r.json() : [];
+    const rows = list.map(e=>`<tr>
+      <td>${escapeHtml(e.title)}<br/><span class="badge">${new Date(e.starts_at).toLocaleString()}</span></td>
+      <td>${escapeHtml(e.short_desc ?? '')}</td>
+      <td class="row">
+        <button onclick="approveEvent('${e.id}')">одобрить</button>
+        <button onclick="rejectEvent('${e.id}')">отклонить</button>
+      </td>
+    </tr>`);
+    document.getElementById('pendingEventsWrap').innerHTML =
+        rows.length ? table(['Ивент','Описание','Действия'], rows)
+            : `<div class="badge">Нет ивентов на модерации</div>`;
+}
+
+async function approveEvent(id){
+    const r = await api(`/api/v1/events/${id}/approve`, {method:'POST'});
+    if(r.ok) loadPendingEvents();
+}
+
+async function rejectEvent(id){
+    const reason = prompt('Причина отклонения (её увидит менеджер):');
+    if(!reason) return;
+    const r = await api(`/api/v1/events/${id}/reject`, {
+        method:'POST',
+        body: JSON.stringify({ reason })
+    });
+    if(r.ok) loadPendingEvents();
+}
+
 // ---- Лист ожидания ----
 async function loadPendingManagers(){
     // Соберём по всем компаниям
@@ -230,7 +314,7 @@ async function loadPendingStudents(){
         const statusBadgeCls = s.status === 'linked' ? 'warn' : 'warn';
         return `<tr>
           <td>${s.name}<br/><span class="badge">${s.email}</span></td>
-          <td>${badge(s.status, statusBadgeCls)}</td>
+          <td>${badge(s.status, statusBadgeCls)} ${s.email_verified ? badge('email подтверждён','ok') : badge('email не подтверждён','err')}</td>
           <td class="row">
             <button onclick="approveStudent('${s.id}')">подтвердить</button>
             <button onclick="rejectStudent('${s.id}')">отклонить</button>
@@ -242,10 +326,30 @@ async function loadPendingStudents(){
         table(['Студент','Статус','Действия'], rows);
 }
 
+// настройка деканата: без проверенного email подтверждение студента вернёт 412
+async function loadSettings(){
+    const r = await api('/api/v1/dean/settings');
+    if (!r.ok) return;
+    const s = await r.json();
+    document.getElementById('requireVerifiedEmail').checked = s.require_verified_email;
+}
+
+async function toggleRequireVerifiedEmail(e){
+    const box = e.target;
+    const r = await api('/api/v1/dean/settings', {
+        method: 'PATCH',
+        body: JSON.stringify({ require_verified_email: box.checked })
+    });
+    if (!r.ok) {
+        box.checked = !box.checked;
+        alert(await apiErrorText(r, 'Не удалось сохранить настройку'));
+    }
+}
+
 async function approveStudent(user_id){
     const r = await api(`/api/v1/dean/students/${user_id}/approve`, { method: 'POST' });
     if (r.ok) loadPendingStudents();
-    else alert('Не удалось подтвердить студента');
+    else alert(await apiErrorText(r, 'Не удалось подтвердить студента'));
 }
 
 async function rejectStudent(user_id){
@@ -258,7 +362,7 @@ async function rejectStudent(user_id){
 window.addEventListener('DOMContentLoaded', async () => {
     // вкладки
     document.querySelectorAll('nav.tabs a').forEach(a=>{
-        a.addEventListener('click', (e)=>{ e.preventDefault(); const h=a.getAttribute('href'); history.replaceState(null,'',h); setTab(h); if(h==='#events'){ loadEvents(); } if(h==='#queue'){ loadPendingManagers(); loadPendingStudents(); }});
+        a.addEventListener('click', (e)=>{ e.preventDefault(); const h=a.getAttribute('href'); history.replaceState(null,'',h); setTab(h); if(h==='#events'){ loadEvents(); } if(h==='#queue'){ loadSettings(); loadPendingManagers(); loadPendingStudents(); loadPendingEvents(); }});
     });
     setTab(location.hash || '#companies');
 
@@ -276,9 +380,14 @@ window.addEventListener('DOMContentLoaded', async () => {
     document.getElementById('companySearch').addEventListener('input', debounce(loadCompanies, 300));
 
     // ивенты
-    document.getElementById('btnReloadEvents').addEventListener('click', loadEvents);
-    document.getElementById('filterCompany').addEventListener('change', loadEvents);
-    document.getElementById('filterPublished').addEventListener('change', loadEvents);
+    document.getElementById('btnReloadEvents').addEventListener('click', () => loadEvents());
+    document.getElementById('filterCompany').addEventListener('change', () => loadEvents());
+    document.getElementById('filterPublished').addEventListener('change', () => loadEvents());
+    document.getElementById('filterTag').addEventListener('change', () => loadEvents());
+    document.getElementById('btnNewTag').addEventListener('click', createTagFlow);
+
+    // лист ожидания
+    document.getElementById('requireVerifiedEmail').addEventListener('change', toggleRequireVerifiedEmail);
 
     // загрузка начальных данных
     const me = await loadMe();
@@ -14,14 +14,44 @@ function getAccessToken() {
     return localStorage.getItem('accessToken');
 }
 
-async function api(url, { method='GET', body=null, requireAuth=true, headers={} } = {}) {
+// Права в access-токене устарели (сменился статус, роль или пароль) — берём новый по refresh-токену.
+// Параллельные 401 ждут одно обновление: повторное предъявление того же refresh-токена сервер
+// считает кражей и отзывает сессию
+let refreshInFlight = null;
+
+function refreshTokens() {
+    if (!refreshInFlight) {
+        refreshInFlight = doRefreshTokens().finally(() => { refreshInFlight = null; });
+    }
+    return refreshInFlight;
+}
+
+async function doRefreshTokens() {
+    const refresh = localStorage.getItem('refreshToken');
+    if (!refresh) return false;
+    const r = await fetch('/api/v1/auth/refresh', {
+        method: 'POST',
+        headers: {'Content-Type':'application/json'},
+        body: JSON.stringify({ refresh_token: refresh }),
+    });
+    if (!r.ok) { clearTokens(); return false; }
+    const t = await r.json();
+    saveTokens(t);
+    return true;
+}
+
+async function api(url, { method='GET', body=null, requireAuth=true, headers={} } = {}, retried=false) {
     const h = {'Content-Type':'application/json', ...headers};
     if (requireAuth) {
         const token = getAccessToken();
         if (token) h['Authorization'] = 'Bearer ' + token;
     }
     console.log('➡️', method, url, body);
-    return fetch(url, { method, headers:h, body });
+    const r = await fetch(url, { method, headers:h, body });
+    if (r.status === 401 && requireAuth && !retried && await refreshTokens()) {
+        return api(url, { method, body, requireAuth, headers }, true);
+    }
+    return r;
 }
 
 function table(headers, rowsHtml) {
@@ -31,4 +61,16 @@ function table(headers, rowsHtml) {
       <tbody>${rowsHtml.join('')}</tbody>
     </table>`;
 }
-function badge(text, cls='') { return `<span class="badge ${cls}">${text}</span>`; }
\ No newline at end of file
+function badge(text, cls='') { return `<span class="badge ${cls}">${text}</span>`; }
+// Текст ошибки API: по каждому полю из error.fields, иначе error.message; request_id — для поддержки
+async function apiErrorText(r, fallback) {
+    let body;
+    try { body = await r.json(); } catch { return fallback; }
+    const err = body && body.error;
+    if (!err) return fallback;
+    const lines = (err.fields && err.fields.length)
+        ? err.fields.map(f => `${f.field}: ${f.message}`)
+        : [err.message || fallback];
+    if (body.request_id) lines.push(`(request id: ${body.request_id})`);
+    return lines.join('\n');
+}
@@ -14,6 +14,9 @@ use tokio::signal;
 use tower_http::services::ServeDir;
 
 static BACKEND_BASE: &str = "http://127.0.0.1:8080";
+static REQUEST_ID: &str = "x-request-id";
+// те же ограничения на чужой id, что и в бэкенде (middleware/request_id.rs)
+const REQUEST_ID_MAX_LEN: usize = 128;
 
 #[tokio::main]
 async fn main() {
@@ -39,6 +42,12 @@ async fn shutdown_signal() {
     println!("\nShutting down…");
 }
 
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= REQUEST_ID_MAX_LEN
+        && id.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'))
+}
+
 async fn proxy_api(State(client): State<Arc<Client>>, mut req: Request) -> impl IntoResponse {
     let method: Method = req.method().clone();
     let uri: Uri = req.uri().clone();
@@ -49,6 +58,16 @@ async fn proxy_api(State(client): State<Arc<Client>>, mut req: Request) -> impl
         .unwrap_or(uri.path());
     let url = format!("{BACKEND_BASE}{path_q}");
 
+    // id запроса сквозной: браузерный (если прислан и годится для логов) или новый;
+    // бэкенд вернёт его в ответе
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_owned)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
     let out_bytes = match req.body_mut().collect().await {
         Ok(collected) => collected.to_bytes(),
         Err(e) => {
@@ -57,7 +76,7 @@ async fn proxy_api(State(client): State<Arc<Client>>, mut req: Request) -> impl
         }
     };
 
-    println!("➡️  Proxying {} {}", method, path_q);
+    println!("➡️  Proxying {} {} [{}]", method, path_q, request_id);
     if !out_bytes.is_empty() {
         if let Ok(s) = std::str::from_utf8(&out_bytes) {
             println!("   Body out: {s}");
@@ -69,10 +88,11 @@ async fn proxy_api(State(client): State<Arc<Client>>, mut req: Request) -> impl
     let mut rb = client.request(method, &url).body(out_bytes);
 
     for (name, value) in req.headers().iter() {
-        if name != &http::header::HOST {
+        if name != &http::header::HOST && name != REQUEST_ID {
             rb = rb.header(name, value);
         }
     }
+    rb = rb.header(REQUEST_ID, &request_id);
 
     // Отправляем
     let resp = match rb.send().await {
@@ -100,7 +120,7 @@ async fn proxy_api(State(client): State<Arc<Client>>, mut req: Request) -> impl
         }
     };
 
-    println!("⬅️  Response {} for {}", status, path_q);
+    println!("⬅️  Response {} for {} [{}]", status, path_q, request_id);
     if !in_bytes.is_empty() {
         if let Ok(s) = std::str::from_utf8(&in_bytes) {
             println!("   Body in: {s}");